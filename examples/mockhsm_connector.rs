@@ -0,0 +1,93 @@
+//! `yubihsm-connector` compatible HTTP server backed by a `MockHsm`.
+//!
+//! This exposes the same API as the `connector_http_server` example, but
+//! serves a simulated HSM instead of a YubiHSM2 connected over USB. It allows
+//! utilities like `yubihsm-shell`, or other things written with `libyubihsm`
+//! or `python-yubihsm`, to be integration tested without any hardware.
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run --example mockhsm_connector --features=http-server,mockhsm -- [OPTIONS]
+//! ```
+//!
+//! Options:
+//!
+//! - `--addr <ADDR>`: address to listen on (default: 127.0.0.1)
+//! - `--port <PORT>`: port to listen on (default: 12345)
+//! - `--state <FILE>`: load the MockHsm's objects and settings from a state
+//!   file previously written by `MockHsm::save`
+//! - `--persist`: save the MockHsm's state to the `--state` file after every
+//!   command (creating it if it doesn't exist yet)
+
+use std::{env, path::PathBuf, process};
+use yubihsm::{
+    connector::{http::Server, HttpConfig},
+    mockhsm::MockHsm,
+    Connector,
+};
+
+fn main() {
+    let mut http_config = HttpConfig::default();
+    let mut state_path: Option<PathBuf> = None;
+    let mut persist = false;
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => http_config.addr = arg_value(&arg, args.next()),
+            "--port" => {
+                http_config.port = arg_value(&arg, args.next())
+                    .parse()
+                    .unwrap_or_else(|e| exit_with_error(&format!("invalid port: {e}")))
+            }
+            "--state" => state_path = Some(arg_value(&arg, args.next()).into()),
+            "--persist" => persist = true,
+            _ => exit_with_error(&format!("unrecognized argument: {arg}")),
+        }
+    }
+
+    let mockhsm = match &state_path {
+        Some(path) if path.exists() || !persist => {
+            println!("loading MockHsm state from {}", path.display());
+            MockHsm::load(path)
+                .unwrap_or_else(|e| exit_with_error(&format!("couldn't load MockHsm state: {e}")))
+        }
+        _ => MockHsm::new(),
+    };
+
+    if persist {
+        let path =
+            state_path.unwrap_or_else(|| exit_with_error("--persist requires a --state file"));
+
+        println!("persisting MockHsm state to {}", path.display());
+        mockhsm
+            .persist_to(path)
+            .unwrap_or_else(|e| exit_with_error(&format!("couldn't persist MockHsm state: {e}")));
+    }
+
+    println!(
+        "starting server at http://{}:{}",
+        &http_config.addr, http_config.port
+    );
+
+    let server = Server::new(&http_config, Connector::from(mockhsm)).unwrap();
+
+    println!("server started! connect by running:\n");
+    println!("    $ yubihsm-shell");
+    println!("    yubihsm> connect");
+    println!("    yubihsm> session open 1 password");
+
+    server.run().unwrap();
+}
+
+/// Get the value of a command line option, exiting if it's missing
+fn arg_value(option: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| exit_with_error(&format!("{option} requires a value")))
+}
+
+/// Print an error and exit with a non-zero status
+fn exit_with_error(msg: &str) -> ! {
+    eprintln!("error: {msg}");
+    process::exit(1);
+}
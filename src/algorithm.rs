@@ -4,7 +4,9 @@ mod error;
 
 pub use self::error::{Error, ErrorKind};
 
-use crate::{asymmetric, authentication, ecdh, ecdsa, hmac, opaque, otp, rsa, template, wrap};
+use crate::{
+    asymmetric, authentication, ecdh, ecdsa, hmac, opaque, otp, rsa, symmetric, template, wrap,
+};
 
 /// Cryptographic algorithm types supported by the `YubiHSM 2`
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -16,6 +18,9 @@ pub enum Algorithm {
     /// YubiHSM 2 symmetric PSK authentication
     Authentication(authentication::Algorithm),
 
+    /// Block cipher modes of operation for symmetric keys
+    BlockMode(symmetric::Mode),
+
     /// Elliptic Curve Diffie-Hellman (i.e. key exchange) algorithms
     Ecdh(ecdh::Algorithm),
 
@@ -34,6 +39,9 @@ pub enum Algorithm {
     /// RSA algorithms (signing and encryption)
     Rsa(rsa::Algorithm),
 
+    /// Symmetric (AES) key algorithms
+    Symmetric(symmetric::Algorithm),
+
     /// SSH template algorithms
     Template(template::Algorithm),
 
@@ -60,7 +68,9 @@ impl Algorithm {
             0x20..=0x23 => Algorithm::Mgf(rsa::mgf::Algorithm::from_u8(byte)?),
             0x24 => Algorithm::Template(template::Algorithm::from_u8(byte)?),
             0x25 | 0x27 | 0x28 => Algorithm::YubicoOtp(otp::Algorithm::from_u8(byte)?),
-            0x26 => Algorithm::Authentication(authentication::Algorithm::from_u8(byte)?),
+            0x26 | 0x31 => Algorithm::Authentication(authentication::Algorithm::from_u8(byte)?),
+            0x32..=0x34 => Algorithm::Symmetric(symmetric::Algorithm::from_u8(byte)?),
+            0x35 | 0x36 => Algorithm::BlockMode(symmetric::Mode::from_u8(byte)?),
             _ => fail!(
                 ErrorKind::TagInvalid,
                 "unknown algorithm ID: 0x{:02x}",
@@ -74,6 +84,7 @@ impl Algorithm {
         match self {
             Algorithm::Asymmetric(alg) => alg.to_u8(),
             Algorithm::Authentication(alg) => alg.to_u8(),
+            Algorithm::BlockMode(mode) => mode.to_u8(),
             Algorithm::Ecdh(alg) => alg.to_u8(),
             Algorithm::Ecdsa(alg) => alg.to_u8(),
             Algorithm::Hmac(alg) => alg.to_u8(),
@@ -81,6 +92,7 @@ impl Algorithm {
             Algorithm::Opaque(alg) => alg.to_u8(),
             Algorithm::YubicoOtp(alg) => alg.to_u8(),
             Algorithm::Rsa(alg) => alg.to_u8(),
+            Algorithm::Symmetric(alg) => alg.to_u8(),
             Algorithm::Template(alg) => alg.to_u8(),
             Algorithm::Wrap(alg) => alg.to_u8(),
         }
@@ -102,6 +114,14 @@ impl Algorithm {
         }
     }
 
+    /// Get `symmetric::Mode`
+    pub fn block_mode(self) -> Option<symmetric::Mode> {
+        match self {
+            Algorithm::BlockMode(mode) => Some(mode),
+            _ => None,
+        }
+    }
+
     /// Get `ecdh::Algorithm`
     pub fn ecdh(self) -> Option<ecdh::Algorithm> {
         match self {
@@ -158,6 +178,14 @@ impl Algorithm {
         }
     }
 
+    /// Get `symmetric::Algorithm`
+    pub fn symmetric(self) -> Option<symmetric::Algorithm> {
+        match self {
+            Algorithm::Symmetric(alg) => Some(alg),
+            _ => None,
+        }
+    }
+
     /// Get `template::Algorithm`
     pub fn template(self) -> Option<template::Algorithm> {
         match self {
@@ -231,6 +259,18 @@ impl From<rsa::mgf::Algorithm> for Algorithm {
     }
 }
 
+impl From<symmetric::Algorithm> for Algorithm {
+    fn from(alg: symmetric::Algorithm) -> Algorithm {
+        Algorithm::Symmetric(alg)
+    }
+}
+
+impl From<symmetric::Mode> for Algorithm {
+    fn from(mode: symmetric::Mode) -> Algorithm {
+        Algorithm::BlockMode(mode)
+    }
+}
+
 impl From<template::Algorithm> for Algorithm {
     fn from(alg: template::Algorithm) -> Algorithm {
         Algorithm::Template(alg)
@@ -334,6 +374,15 @@ mod tests {
         (0x2d, Algorithm::Ecdsa(ecdsa::Algorithm::Sha512)),
         (0x2e, Algorithm::Asymmetric(asymmetric::Algorithm::Ed25519)),
         (0x2f, Algorithm::Asymmetric(asymmetric::Algorithm::EcP224)),
+        (
+            0x31,
+            Algorithm::Authentication(authentication::Algorithm::EcP256),
+        ),
+        (0x32, Algorithm::Symmetric(symmetric::Algorithm::Aes128)),
+        (0x33, Algorithm::Symmetric(symmetric::Algorithm::Aes192)),
+        (0x34, Algorithm::Symmetric(symmetric::Algorithm::Aes256)),
+        (0x35, Algorithm::BlockMode(symmetric::Mode::Ecb)),
+        (0x36, Algorithm::BlockMode(symmetric::Mode::Cbc)),
     ];
 
     #[test]
@@ -2,11 +2,16 @@
 
 use crate::{asymmetric, ecdsa::algorithm::CurveAlgorithm, ed25519};
 use ::ecdsa::elliptic_curve::{
-    bigint::Integer, generic_array::GenericArray, point::PointCompression, sec1, FieldBytesSize,
-    PrimeCurve,
+    generic_array::{typenum::Unsigned, GenericArray},
+    point::PointCompression,
+    sec1, FieldBytesSize, PrimeCurve,
 };
+use rsa::{BigUint, RsaPublicKey};
 use serde::{Deserialize, Serialize};
 
+/// Public exponent used by all RSA keys generated or imported by the HSM
+const RSA_EXPONENT: u32 = 65537;
+
 /// Response from `command::get_public_key`
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct PublicKey {
@@ -51,7 +56,9 @@ impl PublicKey {
         C: PrimeCurve + CurveAlgorithm + PointCompression,
         FieldBytesSize<C>: sec1::ModulusSize,
     {
-        if self.algorithm != C::asymmetric_algorithm() || self.bytes.len() != C::Uint::BYTES * 2 {
+        if self.algorithm != C::asymmetric_algorithm()
+            || self.bytes.len() != FieldBytesSize::<C>::USIZE * 2
+        {
             return None;
         }
 
@@ -66,6 +73,26 @@ impl PublicKey {
         }
     }
 
+    /// Return the RSA public key if applicable
+    pub fn rsa(&self) -> Option<RsaPublicKey> {
+        let is_rsa = matches!(
+            self.algorithm,
+            asymmetric::Algorithm::Rsa2048
+                | asymmetric::Algorithm::Rsa3072
+                | asymmetric::Algorithm::Rsa4096
+        );
+
+        if is_rsa && self.bytes.len() == self.algorithm.key_len() {
+            RsaPublicKey::new(
+                BigUint::from_bytes_be(&self.bytes),
+                BigUint::from(RSA_EXPONENT),
+            )
+            .ok()
+        } else {
+            None
+        }
+    }
+
     /// Return the Ed25519 public key if applicable
     pub fn ed25519(&self) -> Option<ed25519::PublicKey> {
         if self.algorithm == asymmetric::Algorithm::Ed25519 {
@@ -4,7 +4,7 @@ pub(crate) mod commands;
 mod error;
 
 pub use self::{
-    commands::{LogDigest, LogEntries, LogEntry},
+    commands::{LogDigest, LogEntries, LogEntry, LOG_DIGEST_SIZE},
     error::{Error, ErrorKind},
 };
 
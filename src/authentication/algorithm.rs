@@ -9,6 +9,9 @@ pub enum Algorithm {
     /// YubiHSM AES PSK authentication
     #[default]
     YubicoAes = 0x26,
+
+    /// YubiHSM EC P-256 asymmetric authentication
+    EcP256 = 0x31,
 }
 
 impl Algorithm {
@@ -16,6 +19,7 @@ impl Algorithm {
     pub fn from_u8(tag: u8) -> Result<Self, algorithm::Error> {
         Ok(match tag {
             0x26 => Algorithm::YubicoAes,
+            0x31 => Algorithm::EcP256,
             _ => fail!(
                 algorithm::ErrorKind::TagInvalid,
                 "unknown auth algorithm ID: 0x{:02x}",
@@ -33,6 +37,7 @@ impl Algorithm {
     pub fn key_len(self) -> usize {
         match self {
             Algorithm::YubicoAes => 32,
+            Algorithm::EcP256 => 64,
         }
     }
 }
@@ -1,4 +1,5 @@
-//! Credentials used to authenticate to the HSM (key ID + `authentication::Key`).
+//! Credentials used to authenticate to the HSM (key ID + `authentication::Key`
+//! or an EC P-256 private key).
 
 use crate::{authentication, object};
 
@@ -7,23 +8,76 @@ pub const DEFAULT_AUTHENTICATION_KEY_ID: object::Id = 1;
 
 /// Credentials used to establish a session with the HSM
 #[derive(Clone, Debug)]
-pub struct Credentials {
-    /// Key ID to authenticate with
-    pub authentication_key_id: object::Id,
+#[non_exhaustive]
+pub enum Credentials {
+    /// Symmetric (AES PSK) credentials, shared between the host and the HSM
+    Symmetric {
+        /// Key ID to authenticate with
+        authentication_key_id: object::Id,
 
-    /// Auth key to authenticate with
-    pub authentication_key: authentication::Key,
+        /// Auth key to authenticate with
+        authentication_key: authentication::Key,
+    },
+
+    /// Asymmetric (EC P-256) credentials. Only the corresponding public key
+    /// is stored in the HSM.
+    #[cfg(feature = "asymmetric-auth")]
+    Asymmetric {
+        /// Key ID to authenticate with
+        authentication_key_id: object::Id,
+
+        /// P-256 private key to authenticate with
+        private_key: p256::SecretKey,
+
+        /// Expected device public key. Sessions aren't opened if the HSM
+        /// presents a different one. `None` disables this check.
+        device_public_key: Option<p256::PublicKey>,
+    },
 }
 
 impl Credentials {
-    /// Create new `Credentials` (auth key ID + `authentication::Key`)
+    /// Create new symmetric `Credentials` (auth key ID + `authentication::Key`)
     pub fn new(authentication_key_id: object::Id, authentication_key: authentication::Key) -> Self {
-        Self {
+        Credentials::Symmetric {
             authentication_key_id,
             authentication_key,
         }
     }
 
+    /// Create new asymmetric `Credentials` (auth key ID + P-256 private key),
+    /// pinned to the given device public key
+    /// (see [`Client::get_device_public_key`][crate::Client::get_device_public_key])
+    #[cfg(feature = "asymmetric-auth")]
+    pub fn new_asymmetric(
+        authentication_key_id: object::Id,
+        private_key: p256::SecretKey,
+        device_public_key: p256::PublicKey,
+    ) -> Self {
+        Credentials::Asymmetric {
+            authentication_key_id,
+            private_key,
+            device_public_key: Some(device_public_key),
+        }
+    }
+
+    /// Create new asymmetric `Credentials` which trust whichever device
+    /// public key the HSM presents.
+    ///
+    /// **WARNING**: the device public key is fetched without authentication,
+    /// so anyone able to tamper with the connection can impersonate the HSM.
+    /// Prefer [`Credentials::new_asymmetric`].
+    #[cfg(feature = "asymmetric-auth")]
+    pub fn new_asymmetric_unpinned(
+        authentication_key_id: object::Id,
+        private_key: p256::SecretKey,
+    ) -> Self {
+        Credentials::Asymmetric {
+            authentication_key_id,
+            private_key,
+            device_public_key: None,
+        }
+    }
+
     /// Create a set of credentials from the given auth key and password
     /// Uses the same password-based key derivation method as yubihsm-shell
     /// (PBKDF2 + static salt), which is not particularly strong, so use
@@ -35,6 +89,30 @@ impl Credentials {
             authentication::Key::derive_from_password(password),
         )
     }
+
+    /// Get the ID of the authentication key these credentials refer to
+    pub fn authentication_key_id(&self) -> object::Id {
+        match self {
+            Credentials::Symmetric {
+                authentication_key_id,
+                ..
+            } => *authentication_key_id,
+            #[cfg(feature = "asymmetric-auth")]
+            Credentials::Asymmetric {
+                authentication_key_id,
+                ..
+            } => *authentication_key_id,
+        }
+    }
+
+    /// Get the `authentication::Algorithm` these credentials use
+    pub fn algorithm(&self) -> authentication::Algorithm {
+        match self {
+            Credentials::Symmetric { .. } => authentication::Algorithm::YubicoAes,
+            #[cfg(feature = "asymmetric-auth")]
+            Credentials::Asymmetric { .. } => authentication::Algorithm::EcP256,
+        }
+    }
 }
 
 #[cfg(feature = "passwords")]
@@ -158,26 +158,26 @@ bitflags! {
         /// `change-authentication-key`: overwrite existing authentication key with new one
         const CHANGE_AUTHENTICATION_KEY = 0x4000_0000_0000;
 
-        /// unknown capability: bit 47
-        const UNKNOWN_CAPABILITY_47 = 0x8000_0000_0000;
+        /// `put-symmetric-key`: write symmetric key objects
+        const PUT_SYMMETRIC_KEY = 0x8000_0000_0000;
 
-        /// unknown capability: bit 48
-        const UNKNOWN_CAPABILITY_48 = 0x1_0000_0000_0000;
+        /// `generate-symmetric-key`: generate symmetric key objects
+        const GENERATE_SYMMETRIC_KEY = 0x1_0000_0000_0000;
 
-        /// unknown capability: bit 49
-        const UNKNOWN_CAPABILITY_49 = 0x2_0000_0000_0000;
+        /// `delete-symmetric-key`: delete symmetric key objects
+        const DELETE_SYMMETRIC_KEY = 0x2_0000_0000_0000;
 
-        /// unknown capability: bit 50
-        const UNKNOWN_CAPABILITY_50 = 0x4_0000_0000_0000;
+        /// `decrypt-ecb`: perform AES-ECB decryption
+        const DECRYPT_ECB = 0x4_0000_0000_0000;
 
-        /// unknown capability: bit 51
-        const UNKNOWN_CAPABILITY_51 = 0x8_0000_0000_0000;
+        /// `encrypt-ecb`: perform AES-ECB encryption
+        const ENCRYPT_ECB = 0x8_0000_0000_0000;
 
-        /// unknown capability: bit 52
-        const UNKNOWN_CAPABILITY_52 = 0x10_0000_0000_0000;
+        /// `decrypt-cbc`: perform AES-CBC decryption
+        const DECRYPT_CBC = 0x10_0000_0000_0000;
 
-        /// unknown capability: bit 53
-        const UNKNOWN_CAPABILITY_53 = 0x20_0000_0000_0000;
+        /// `encrypt-cbc`: perform AES-CBC encryption
+        const ENCRYPT_CBC = 0x20_0000_0000_0000;
 
         /// unknown capability: bit 54
         const UNKNOWN_CAPABILITY_54 = 0x40_0000_0000_0000;
@@ -211,6 +211,36 @@ bitflags! {
     }
 }
 
+impl Capability {
+    /// Former name of the capability at bit 47
+    #[deprecated(note = "use `Capability::PUT_SYMMETRIC_KEY`")]
+    pub const UNKNOWN_CAPABILITY_47: Capability = Capability::PUT_SYMMETRIC_KEY;
+
+    /// Former name of the capability at bit 48
+    #[deprecated(note = "use `Capability::GENERATE_SYMMETRIC_KEY`")]
+    pub const UNKNOWN_CAPABILITY_48: Capability = Capability::GENERATE_SYMMETRIC_KEY;
+
+    /// Former name of the capability at bit 49
+    #[deprecated(note = "use `Capability::DELETE_SYMMETRIC_KEY`")]
+    pub const UNKNOWN_CAPABILITY_49: Capability = Capability::DELETE_SYMMETRIC_KEY;
+
+    /// Former name of the capability at bit 50
+    #[deprecated(note = "use `Capability::DECRYPT_ECB`")]
+    pub const UNKNOWN_CAPABILITY_50: Capability = Capability::DECRYPT_ECB;
+
+    /// Former name of the capability at bit 51
+    #[deprecated(note = "use `Capability::ENCRYPT_ECB`")]
+    pub const UNKNOWN_CAPABILITY_51: Capability = Capability::ENCRYPT_ECB;
+
+    /// Former name of the capability at bit 52
+    #[deprecated(note = "use `Capability::DECRYPT_CBC`")]
+    pub const UNKNOWN_CAPABILITY_52: Capability = Capability::DECRYPT_CBC;
+
+    /// Former name of the capability at bit 53
+    #[deprecated(note = "use `Capability::ENCRYPT_CBC`")]
+    pub const UNKNOWN_CAPABILITY_53: Capability = Capability::ENCRYPT_CBC;
+}
+
 impl Default for Capability {
     fn default() -> Self {
         Capability::empty()
@@ -267,6 +297,13 @@ impl Display for Capability {
             Capability::UNWRAP_DATA => "unwrap-data",
             Capability::WRAP_DATA => "wrap-data",
             Capability::CHANGE_AUTHENTICATION_KEY => "change-authentication-key",
+            Capability::PUT_SYMMETRIC_KEY => "put-symmetric-key",
+            Capability::GENERATE_SYMMETRIC_KEY => "generate-symmetric-key",
+            Capability::DELETE_SYMMETRIC_KEY => "delete-symmetric-key",
+            Capability::DECRYPT_ECB => "decrypt-ecb",
+            Capability::ENCRYPT_ECB => "encrypt-ecb",
+            Capability::DECRYPT_CBC => "decrypt-cbc",
+            Capability::ENCRYPT_CBC => "encrypt-cbc",
             _ => return Err(fmt::Error), // we don't support displaying this capability yet
         };
 
@@ -326,6 +363,13 @@ impl FromStr for Capability {
             "unwrap-data" => Capability::UNWRAP_DATA,
             "wrap-data" => Capability::WRAP_DATA,
             "change-authentication-key" => Capability::CHANGE_AUTHENTICATION_KEY,
+            "put-symmetric-key" => Capability::PUT_SYMMETRIC_KEY,
+            "generate-symmetric-key" => Capability::GENERATE_SYMMETRIC_KEY,
+            "delete-symmetric-key" => Capability::DELETE_SYMMETRIC_KEY,
+            "decrypt-ecb" => Capability::DECRYPT_ECB,
+            "encrypt-ecb" => Capability::ENCRYPT_ECB,
+            "decrypt-cbc" => Capability::DECRYPT_CBC,
+            "encrypt-cbc" => Capability::ENCRYPT_CBC,
             _ => return Err(()),
         })
     }
@@ -22,20 +22,36 @@ use crate::{
     connector::Connector,
     device::{self, commands::*, StorageInfo},
     domain::Domain,
-    ecdsa::commands::*,
+    ecdh::{self, commands::*},
+    ecdsa::{
+        self,
+        algorithm::{truncate_digest, CurveAlgorithm},
+        commands::*,
+    },
     ed25519::{self, commands::*},
     hmac::{self, commands::*},
     object::{self, commands::*, generate},
     opaque::{self, commands::*},
     otp::{self, commands::*},
-    rsa::{self, oaep::commands::*},
+    rsa::{self, oaep::commands::*, pkcs1::commands::*},
     serialization::{deserialize, serialize},
     session::{self, Session},
+    symmetric::{self, commands::*},
     template::{commands::*, Template},
     uuid,
     wrap::{self, commands::*},
 };
+use ::ecdsa::{
+    elliptic_curve::{
+        generic_array::{typenum::Unsigned, ArrayLength},
+        sec1::{self, FromEncodedPoint, ToEncodedPoint},
+        AffinePoint, CurveArithmetic, FieldBytes, FieldBytesSize, PrimeCurve,
+    },
+    SignatureSize,
+};
+use signature::digest::Digest;
 use std::{
+    ops::Add,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
@@ -43,20 +59,25 @@ use std::{
 #[cfg(feature = "passwords")]
 use std::{thread, time::SystemTime};
 
+#[cfg(feature = "sha2")]
+use {
+    ::rsa::{
+        traits::{PrivateKeyParts, PublicKeyParts},
+        BigUint, RsaPrivateKey, RsaPublicKey,
+    },
+    zeroize::Zeroizing,
+};
+
 #[cfg(feature = "untested")]
 use {
     crate::{
         algorithm::Algorithm,
-        ecdh::{self, commands::*},
-        rsa::{pkcs1::commands::*, pss::commands::*},
+        rsa::pss::commands::*,
         ssh::{self, commands::*},
     },
-    sha2::{Digest, Sha256},
+    sha2::Sha256,
 };
 
-#[cfg(docsrs)]
-use crate::ecdsa;
-
 /// YubiHSM client: main API in this crate for accessing functions of the
 /// HSM hardware device.
 #[derive(Clone)]
@@ -64,11 +85,18 @@ pub struct Client {
     /// Connector for communicating with the HSM
     connector: Connector,
 
+    /// Encrypted session with the HSM and the credentials used to open it,
+    /// shared between clones of this client
+    session: Arc<Mutex<SessionState>>,
+}
+
+/// State of a client's session with the HSM, guarded by a mutex
+pub(crate) struct SessionState {
     /// Encrypted session with the HSM (if we have one open)
-    session: Arc<Mutex<Option<Session>>>,
+    pub(crate) session: Option<Session>,
 
     /// Cached `Credentials` for reconnecting closed sessions
-    credentials: Option<Credentials>,
+    pub(crate) credentials: Option<Credentials>,
 }
 
 impl Client {
@@ -84,12 +112,12 @@ impl Client {
         credentials: Credentials,
         reconnect: bool,
     ) -> Result<Self, Error> {
-        let mut client = Self::create(connector, credentials)?;
+        let client = Self::create(connector, credentials)?;
         client.connect()?;
 
         // Clear credentials if reconnecting has been disabled
         if !reconnect {
-            client.credentials = None;
+            client.session.lock().unwrap().credentials = None;
         }
 
         Ok(client)
@@ -99,8 +127,10 @@ impl Client {
     pub fn create(connector: Connector, credentials: Credentials) -> Result<Self, Error> {
         let client = Self {
             connector,
-            session: Arc::new(Mutex::new(None)),
-            credentials: Some(credentials),
+            session: Arc::new(Mutex::new(SessionState {
+                session: None,
+                credentials: Some(credentials),
+            })),
         };
 
         Ok(client)
@@ -124,7 +154,7 @@ impl Client {
         // TODO(tarcieri): handle PoisonError better?
         let mut session_mutex_guard = self.session.lock().unwrap();
 
-        if let Some(session) = session_mutex_guard.as_ref() {
+        if let Some(session) = session_mutex_guard.session.as_ref() {
             if session.is_open() {
                 return Ok(session::Guard::new(session_mutex_guard));
             }
@@ -133,7 +163,7 @@ impl Client {
         // If we don't have an open session, create a new one
         let session = Session::open(
             self.connector.clone(),
-            self.credentials.as_ref().ok_or_else(|| {
+            session_mutex_guard.credentials.as_ref().ok_or_else(|| {
                 format_err!(
                     ErrorKind::AuthenticationError,
                     "session reconnection disabled"
@@ -142,7 +172,7 @@ impl Client {
             session::Timeout::default(),
         )?;
 
-        *session_mutex_guard = Some(session);
+        session_mutex_guard.session = Some(session);
         Ok(session::Guard::new(session_mutex_guard))
     }
 
@@ -199,6 +229,116 @@ impl Client {
         Ok(())
     }
 
+    /// Change the authentication key with the given ID, which must be the
+    /// key used to authenticate the current session, in-place (i.e. without
+    /// deleting and re-adding it). Requires the `CHANGE_AUTHENTICATION_KEY`
+    /// capability.
+    ///
+    /// Only symmetric (i.e. `YubicoAes`) authentication keys can be changed
+    /// this way. Password-based keys can be changed by passing a key derived
+    /// using [`authentication::Key::derive_from_password`].
+    ///
+    /// If this client's cached `Credentials` refer to the same key ID, they
+    /// are updated (for all clones of this client) so subsequent reconnects
+    /// use the new key.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Change_Authentication_Key.html>
+    pub fn change_authentication_key<K>(
+        &self,
+        key_id: object::Id,
+        authentication_key: K,
+    ) -> Result<object::Id, Error>
+    where
+        K: Into<authentication::Key>,
+    {
+        if let Some(credentials) = self.session.lock().unwrap().credentials.as_ref() {
+            ensure!(
+                credentials.authentication_key_id() != key_id
+                    || credentials.algorithm() == authentication::Algorithm::YubicoAes,
+                ErrorKind::ProtocolError,
+                "can't change asymmetric authentication key {:?} to a symmetric key",
+                key_id
+            );
+        }
+
+        let authentication_key = authentication_key.into();
+
+        let response = self.send_command(ChangeAuthenticationKeyCommand {
+            key_id,
+            algorithm: authentication::Algorithm::YubicoAes,
+            authentication_key: authentication_key.clone(),
+        })?;
+
+        if let Some(Credentials::Symmetric {
+            authentication_key_id,
+            authentication_key: cached_key,
+        }) = self.session.lock().unwrap().credentials.as_mut()
+        {
+            if *authentication_key_id == key_id {
+                *cached_key = authentication_key;
+            }
+        }
+
+        Ok(response.key_id)
+    }
+
+    /// Create a Yubico OTP AEAD from the given OTP key and private ID,
+    /// encrypted under the given OTP AEAD key.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Create_Otp_Aead.html>
+    pub fn create_otp_aead(
+        &self,
+        key_id: object::Id,
+        otp_key: [u8; otp::KEY_SIZE],
+        private_id: otp::PrivateId,
+    ) -> Result<otp::Aead, Error> {
+        Ok(self
+            .send_command(CreateOtpAeadCommand {
+                key_id,
+                key: otp_key,
+                private_id,
+            })?
+            .0)
+    }
+
+    /// Decrypt data in AES-CBC mode using the given symmetric key and
+    /// initialization vector. The ciphertext must be a multiple of the AES block size.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Cbc.html>
+    pub fn decrypt_cbc<T>(
+        &self,
+        key_id: object::Id,
+        iv: symmetric::Iv,
+        data: T,
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: Into<Vec<u8>>,
+    {
+        Ok(self
+            .send_command(DecryptCbcCommand {
+                key_id,
+                iv,
+                data: data.into(),
+            })?
+            .0)
+    }
+
+    /// Decrypt data in AES-ECB mode using the given symmetric key.
+    /// The ciphertext must be a multiple of the AES block size.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Ecb.html>
+    pub fn decrypt_ecb<T>(&self, key_id: object::Id, data: T) -> Result<Vec<u8>, Error>
+    where
+        T: Into<Vec<u8>>,
+    {
+        Ok(self
+            .send_command(DecryptEcbCommand {
+                key_id,
+                data: data.into(),
+            })?
+            .0)
+    }
+
     /// Decrypt data encrypted with RSA-OAEP
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Oaep.html>
@@ -222,6 +362,43 @@ impl Client {
             .into())
     }
 
+    /// Decrypt a Yubico OTP using the given AEAD and the OTP AEAD key it
+    /// was created under, returning its counters and timestamp.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Otp.html>
+    pub fn decrypt_otp(
+        &self,
+        key_id: object::Id,
+        aead: otp::Aead,
+        otp: [u8; otp::OTP_SIZE],
+    ) -> Result<otp::DecryptedOtp, Error> {
+        Ok(self
+            .send_command(DecryptOtpCommand { key_id, aead, otp })?
+            .into())
+    }
+
+    /// Decrypt data encrypted with RSA PKCS#1v1.5 padding.
+    ///
+    /// Note: this is a legacy encryption scheme. Consider RSA-OAEP for new
+    /// applications (see [`Client::decrypt_oaep`]).
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Pkcs1.html>
+    pub fn decrypt_pkcs1v15<T>(
+        &self,
+        key_id: object::Id,
+        data: T,
+    ) -> Result<rsa::pkcs1::DecryptedData, Error>
+    where
+        T: Into<Vec<u8>>,
+    {
+        Ok(self
+            .send_command(DecryptPkcs1Command {
+                key_id,
+                data: data.into(),
+            })?
+            .into())
+    }
+
     /// Delete an object of the given ID and type.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Delete_Object.html>
@@ -237,23 +414,40 @@ impl Client {
         Ok(())
     }
 
-    /// Elliptic Curve Diffie-Hellman: derive a shared secret via key exchange.
-    ///
-    /// **WARNING**: This functionality has not been tested and has not yet been
-    /// confirmed to actually work! USE AT YOUR OWN RISK!
-    ///
-    /// You will need to enable the `untested` cargo feature to use it.
+    /// Elliptic Curve Diffie-Hellman: derive a shared secret via key exchange
+    /// between the given ECDH key in the HSM and the given public key.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Derive_Ecdh.html>
-    #[cfg(feature = "untested")]
-    pub fn derive_ecdh(
+    pub fn derive_ecdh<C>(
         &self,
         key_id: object::Id,
-        public_key: ecdh::UncompressedPoint,
-    ) -> Result<ecdh::UncompressedPoint, Error> {
-        Ok(self
-            .send_command(DeriveEcdhCommand { key_id, public_key })?
-            .into())
+        public_key: &ecdh::PublicKey<C>,
+    ) -> Result<ecdh::SharedSecret<C>, Error>
+    where
+        C: CurveAlgorithm + CurveArithmetic,
+        AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+        FieldBytesSize<C>: sec1::ModulusSize,
+    {
+        let public_point =
+            ecdh::UncompressedPoint::from_bytes(public_key.to_encoded_point(false).as_bytes())
+                .ok_or_else(|| {
+                    format_err!(ErrorKind::ProtocolError, "unsupported public key size")
+                })?;
+
+        let response = self.send_command(DeriveEcdhCommand {
+            key_id,
+            public_key: public_point,
+        })?;
+
+        ensure!(
+            response.0.len() == FieldBytesSize::<C>::USIZE,
+            ErrorKind::ProtocolError,
+            "invalid shared secret length: {} (expected {})",
+            response.0.len(),
+            FieldBytesSize::<C>::USIZE
+        );
+
+        Ok(FieldBytes::<C>::clone_from_slice(&response.0).into())
     }
 
     /// Get information about the HSM device.
@@ -277,6 +471,44 @@ impl Client {
             .0)
     }
 
+    /// Encrypt data in AES-CBC mode using the given symmetric key and
+    /// initialization vector. The plaintext must be a multiple of the AES block size.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Encrypt_Cbc.html>
+    pub fn encrypt_cbc<T>(
+        &self,
+        key_id: object::Id,
+        iv: symmetric::Iv,
+        data: T,
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: Into<Vec<u8>>,
+    {
+        Ok(self
+            .send_command(EncryptCbcCommand {
+                key_id,
+                iv,
+                data: data.into(),
+            })?
+            .0)
+    }
+
+    /// Encrypt data in AES-ECB mode using the given symmetric key.
+    /// The plaintext must be a multiple of the AES block size.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Encrypt_Ecb.html>
+    pub fn encrypt_ecb<T>(&self, key_id: object::Id, data: T) -> Result<Vec<u8>, Error>
+    where
+        T: Into<Vec<u8>>,
+    {
+        Ok(self
+            .send_command(EncryptEcbCommand {
+                key_id,
+                data: data.into(),
+            })?
+            .0)
+    }
+
     /// Export an encrypted object from the HSM using the given key-wrapping key.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Export_Wrapped.html>
@@ -295,6 +527,38 @@ impl Client {
             .0)
     }
 
+    /// Export an encrypted object from the HSM under an RSA public wrap key,
+    /// using an ephemeral key of the given AES algorithm. The ephemeral key
+    /// is encrypted using RSA-OAEP, with `D` as the OAEP and MGF1 digest.
+    ///
+    /// The resulting [`wrap::RsaMessage`] can be decrypted offline using
+    /// [`wrap::RsaMessage::decrypt`], or imported into an HSM holding the
+    /// corresponding RSA private key using [`Client::import_wrapped_rsa`].
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Export_Wrapped_Rsa.html>
+    #[cfg(feature = "sha2")]
+    pub fn export_wrapped_rsa<D>(
+        &self,
+        wrap_key_id: object::Id,
+        object_type: object::Type,
+        object_id: object::Id,
+        aes_algorithm: symmetric::Algorithm,
+    ) -> Result<wrap::RsaMessage, Error>
+    where
+        D: rsa::oaep::DigestAlgorithm,
+    {
+        Ok(self
+            .send_command(ExportWrappedRsaCommand {
+                wrap_key_id,
+                object_type,
+                object_id,
+                aes_algorithm,
+                oaep_algorithm: D::OAEP_ALGORITHM,
+                mgf1_algorithm: D::MGF_ALGORITHM,
+            })?
+            .0)
+    }
+
     /// Generate a new asymmetric key within the HSM.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Generate_Asymmetric_Key.html>
@@ -339,6 +603,54 @@ impl Client {
             .key_id)
     }
 
+    /// Generate a new OTP AEAD key within the HSM.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Generate_Otp_Aead_Key.html>
+    pub fn generate_otp_aead_key(
+        &self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: otp::Algorithm,
+        nonce_id: otp::Nonce,
+    ) -> Result<object::Id, Error> {
+        Ok(self
+            .send_command(GenOtpAeadKeyCommand {
+                params: generate::Params {
+                    key_id,
+                    label,
+                    domains,
+                    capabilities,
+                    algorithm: algorithm.into(),
+                },
+                nonce_id,
+            })?
+            .key_id)
+    }
+
+    /// Generate a new symmetric (AES) key within the HSM.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Generate_Symmetric_Key.html>
+    pub fn generate_symmetric_key(
+        &self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: symmetric::Algorithm,
+    ) -> Result<object::Id, Error> {
+        Ok(self
+            .send_command(GenSymmetricKeyCommand(generate::Params {
+                key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            }))?
+            .key_id)
+    }
+
     /// Generate a new wrap key within the HSM.
     ///
     /// Delegated capabilities are the set of `Capability` bits that an object is allowed to have
@@ -368,6 +680,17 @@ impl Client {
             .key_id)
     }
 
+    /// Get the device public key, used to establish sessions with
+    /// asymmetric (EC P-256) authentication keys. This command is sent
+    /// without opening a session.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Get_Device_Public_Key.html>
+    pub fn get_device_public_key(&self) -> Result<PublicKey, Error> {
+        Ok(session::securechannel::get_device_public_key(
+            &self.connector,
+        )?)
+    }
+
     /// Get audit logs from the HSM device.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Get_Log_Entries.html>
@@ -527,6 +850,38 @@ impl Client {
         ))
     }
 
+    /// Import an object encrypted under an RSA public wrap key, decrypting
+    /// it with the corresponding RSA private key held by the HSM (see
+    /// [`Client::put_rsa_wrap_key`]). `D` is the OAEP and MGF1 digest the
+    /// object was exported with.
+    ///
+    /// Imported objects can't have capabilities beyond the delegated
+    /// capabilities of the RSA wrap key.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Import_Wrapped_Rsa.html>
+    #[cfg(feature = "sha2")]
+    pub fn import_wrapped_rsa<D, M>(
+        &self,
+        unwrap_key_id: object::Id,
+        wrap_message: M,
+    ) -> Result<object::Handle, Error>
+    where
+        D: rsa::oaep::DigestAlgorithm,
+        M: Into<wrap::RsaMessage>,
+    {
+        let response = self.send_command(ImportWrappedRsaCommand {
+            unwrap_key_id,
+            oaep_algorithm: D::OAEP_ALGORITHM,
+            mgf1_algorithm: D::MGF_ALGORITHM,
+            ciphertext: wrap_message.into().into_vec(),
+        })?;
+
+        Ok(object::Handle::new(
+            response.object_id,
+            response.object_type,
+        ))
+    }
+
     /// List objects visible from the current session.
     ///
     /// Optionally apply a set of provided `filters` which select objects
@@ -584,6 +939,35 @@ impl Client {
             .key_id)
     }
 
+    /// Put an asymmetric (EC P-256) authentication key into the HSM. Only
+    /// the public key is sent: sessions are then opened using
+    /// [`Credentials::Asymmetric`] with the corresponding private key.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Put_Authentication_Key.html>
+    pub fn put_asymmetric_authentication_key(
+        &self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        public_key: &p256::PublicKey,
+    ) -> Result<object::Id, Error> {
+        Ok(self
+            .send_command(PutAsymmetricAuthenticationKeyCommand {
+                params: object::put::Params {
+                    id: key_id,
+                    label,
+                    domains,
+                    capabilities,
+                    algorithm: authentication::Algorithm::EcP256.into(),
+                },
+                delegated_capabilities,
+                public_key: public_key.to_encoded_point(false).as_bytes()[1..].into(),
+            })?
+            .key_id)
+    }
+
     /// Put an existing `authentication::Key` into the HSM.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Put_Authentication_Key.html>
@@ -696,6 +1080,7 @@ impl Client {
         domains: Domain,
         capabilities: Capability,
         algorithm: otp::Algorithm,
+        nonce_id: otp::Nonce,
         key_bytes: K,
     ) -> Result<object::Id, Error>
     where
@@ -722,11 +1107,143 @@ impl Client {
                     capabilities,
                     algorithm: algorithm.into(),
                 },
+                nonce_id,
                 data,
             })?
             .key_id)
     }
 
+    /// Put an RSA public wrap key into the HSM. Objects can then be exported
+    /// under it using [`Client::export_wrapped_rsa`], without the HSM ever
+    /// sharing a symmetric key with the backup target.
+    ///
+    /// Only the modulus is sent: the public exponent must be 65537.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Put_Public_Wrap_Key.html>
+    #[cfg(feature = "sha2")]
+    pub fn put_public_wrap_key(
+        &self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        public_key: &RsaPublicKey,
+    ) -> Result<object::Id, Error> {
+        let algorithm = rsa_algorithm(public_key)?;
+
+        Ok(self
+            .send_command(PutPublicWrapKeyCommand {
+                params: object::put::Params {
+                    id: key_id,
+                    label,
+                    domains,
+                    capabilities,
+                    algorithm: algorithm.into(),
+                },
+                delegated_capabilities,
+                public_key: public_key.n().to_bytes_be(),
+            })?
+            .key_id)
+    }
+
+    /// Put an RSA private wrap key into the HSM, used to import objects
+    /// exported under the corresponding public wrap key with
+    /// [`Client::import_wrapped_rsa`].
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Put_Wrap_Key.html>
+    #[cfg(feature = "sha2")]
+    pub fn put_rsa_wrap_key(
+        &self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        private_key: &RsaPrivateKey,
+    ) -> Result<object::Id, Error> {
+        let algorithm = rsa_algorithm(private_key)?;
+        let primes = private_key.primes();
+
+        ensure!(
+            primes.len() == 2,
+            ErrorKind::ProtocolError,
+            "unsupported multi-prime RSA key ({} primes)",
+            primes.len()
+        );
+
+        // Keys are sent as `p || q`, each padded to half the modulus size
+        let component_size = algorithm.key_len() / 2;
+        let mut data = Zeroizing::new(Vec::with_capacity(algorithm.key_len()));
+
+        for prime in primes {
+            let bytes = Zeroizing::new(prime.to_bytes_be());
+            ensure!(
+                bytes.len() <= component_size,
+                ErrorKind::ProtocolError,
+                "invalid RSA prime size: {} bytes",
+                bytes.len()
+            );
+            data.extend(std::iter::repeat(0).take(component_size - bytes.len()));
+            data.extend_from_slice(&bytes);
+        }
+
+        Ok(self
+            .send_command(PutWrapKeyCommand {
+                params: object::put::Params {
+                    id: key_id,
+                    label,
+                    domains,
+                    capabilities,
+                    algorithm: algorithm.into(),
+                },
+                delegated_capabilities,
+                data: data.to_vec(),
+            })?
+            .key_id)
+    }
+
+    /// Put an existing symmetric (AES) key into the HSM.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Put_Symmetric_Key.html>
+    pub fn put_symmetric_key<K>(
+        &self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: symmetric::Algorithm,
+        key_bytes: K,
+    ) -> Result<object::Id, Error>
+    where
+        K: Into<Vec<u8>>,
+    {
+        let symmetric_key = key_bytes.into();
+
+        if symmetric_key.len() != algorithm.key_len() {
+            fail!(
+                ErrorKind::ProtocolError,
+                "invalid key length for {:?}: {} (expected {})",
+                algorithm,
+                symmetric_key.len(),
+                algorithm.key_len()
+            );
+        }
+
+        Ok(self
+            .send_command(PutSymmetricKeyCommand {
+                params: object::put::Params {
+                    id: key_id,
+                    label,
+                    domains,
+                    capabilities,
+                    algorithm: algorithm.into(),
+                },
+                symmetric_key,
+            })?
+            .key_id)
+    }
+
     /// Put an existing wrap key into the HSM.
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Put_Wrap_Key.html>
@@ -802,6 +1319,14 @@ impl Client {
             .object_id)
     }
 
+    /// Create a Yubico OTP AEAD from a randomly generated OTP key and
+    /// private ID, encrypted under the given OTP AEAD key.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Randomize_Otp_Aead.html>
+    pub fn randomize_otp_aead(&self, key_id: object::Id) -> Result<otp::Aead, Error> {
+        Ok(self.send_command(RandomizeOtpAeadCommand { key_id })?.0)
+    }
+
     /// Reset the HSM to a factory default state and reboot, clearing all
     /// stored objects and restoring the default auth key.
     ///
@@ -851,7 +1376,7 @@ impl Client {
         self.reset_device()?;
 
         // Configure default credentials
-        self.credentials = Some(Credentials::default());
+        self.session.lock().unwrap().credentials = Some(Credentials::default());
 
         let deadline = SystemTime::now() + timeout;
 
@@ -883,6 +1408,24 @@ impl Client {
         }
     }
 
+    /// Re-encrypt a Yubico OTP AEAD from one OTP AEAD key to another.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Rewrap_Otp_Aead.html>
+    pub fn rewrap_otp_aead(
+        &self,
+        from_key_id: object::Id,
+        to_key_id: object::Id,
+        aead: otp::Aead,
+    ) -> Result<otp::Aead, Error> {
+        Ok(self
+            .send_command(RewrapOtpAeadCommand {
+                from_key_id,
+                to_key_id,
+                aead,
+            })?
+            .0)
+    }
+
     /// Configure the audit policy settings for a particular command, e.g. auditing
     /// should be `On`, `Off`, or `Fix` (i.e. fixed permanently on).
     ///
@@ -966,6 +1509,33 @@ impl Client {
         })
     }
 
+    /// Compute an ECDSA signature of the given message, hashed using the
+    /// digest algorithm `D`, with the ECDSA key of curve type `C` with the
+    /// given key ID.
+    ///
+    /// Digests larger than the curve's field size (e.g. SHA-512 with P-256)
+    /// are truncated to their leftmost bytes, as specified by FIPS 186-4.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Sign_Ecdsa.html>
+    pub fn sign_ecdsa<C, D>(
+        &self,
+        key_id: object::Id,
+        msg: &[u8],
+    ) -> Result<ecdsa::Signature<C>, Error>
+    where
+        C: CurveAlgorithm + PrimeCurve,
+        D: Digest,
+        SignatureSize<C>: ArrayLength<u8>,
+        ::ecdsa::der::MaxSize<C>: ArrayLength<u8>,
+        <FieldBytesSize<C> as Add>::Output: Add<::ecdsa::der::MaxOverhead> + ArrayLength<u8>,
+    {
+        let digest = D::digest(msg);
+        let signature = self.sign_ecdsa_prehash_raw(key_id, truncate_digest::<C>(&digest))?;
+
+        ecdsa::Signature::from_der(&signature)
+            .map_err(|e| format_err!(ErrorKind::ProtocolError, "invalid signature: {}", e).into())
+    }
+
     /// Compute an ECDSA signature of the given digest (i.e. a precomputed SHA-2 digest)
     ///
     /// <https://developers.yubico.com/YubiHSM2/Commands/Sign_Ecdsa.html>
@@ -1017,6 +1587,27 @@ impl Client {
             .into())
     }
 
+    /// Compute an RSASSA-PKCS#1v1.5 signature of the given data, hashed
+    /// using the digest algorithm `S`, with the given key ID.
+    ///
+    /// **WARNING**: This functionality has not been tested and has not yet been
+    /// confirmed to actually work! USE AT YOUR OWN RISK!
+    ///
+    /// You will need to enable the `untested` cargo feature to use it.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Sign_Pkcs1.html>
+    #[cfg(feature = "untested")]
+    pub fn sign_rsa_pkcs1v15<S>(
+        &self,
+        key_id: object::Id,
+        data: &[u8],
+    ) -> Result<rsa::pkcs1::Signature, Error>
+    where
+        S: rsa::SignatureAlgorithm,
+    {
+        self.sign_rsa_pkcs1v15_prehash(key_id, &S::digest(data))
+    }
+
     /// Compute an RSASSA-PKCS#1v1.5 signature of the SHA-256 hash of the given data.
     ///
     /// **WARNING**: This functionality has not been tested and has not yet been
@@ -1030,15 +1621,48 @@ impl Client {
         &self,
         key_id: object::Id,
         data: &[u8],
+    ) -> Result<rsa::pkcs1::Signature, Error> {
+        self.sign_rsa_pkcs1v15::<Sha256>(key_id, data)
+    }
+
+    /// Compute an RSASSA-PKCS#1v1.5 signature of a precomputed digest. The
+    /// HSM selects the digest algorithm based on the digest's length.
+    #[cfg(feature = "untested")]
+    pub(crate) fn sign_rsa_pkcs1v15_prehash(
+        &self,
+        key_id: object::Id,
+        digest: &[u8],
     ) -> Result<rsa::pkcs1::Signature, Error> {
         Ok(self
             .send_command(SignPkcs1Command {
                 key_id,
-                digest: Sha256::digest(data).as_slice().into(),
+                digest: digest.into(),
             })?
             .into())
     }
 
+    /// Compute an RSASSA-PSS signature of the given data, hashed using the
+    /// digest algorithm `S`, with the given key ID. MGF1 uses the same
+    /// digest algorithm, and the salt is the length of the digest output.
+    ///
+    /// **WARNING**: This functionality has not been tested and has not yet been
+    /// confirmed to actually work! USE AT YOUR OWN RISK!
+    ///
+    /// You will need to enable the `untested` cargo feature to use it.
+    ///
+    /// <https://developers.yubico.com/YubiHSM2/Commands/Sign_Pss.html>
+    #[cfg(feature = "untested")]
+    pub fn sign_rsa_pss<S>(
+        &self,
+        key_id: object::Id,
+        data: &[u8],
+    ) -> Result<rsa::pss::Signature, Error>
+    where
+        S: rsa::SignatureAlgorithm,
+    {
+        self.sign_rsa_pss_prehash::<S>(key_id, &S::digest(data))
+    }
+
     /// Compute an RSASSA-PSS signature of the SHA-256 hash of the given data with the given key ID.
     ///
     /// **WARNING**: This functionality has not been tested and has not yet been
@@ -1053,32 +1677,44 @@ impl Client {
         key_id: object::Id,
         data: &[u8],
     ) -> Result<rsa::pss::Signature, Error> {
+        self.sign_rsa_pss::<Sha256>(key_id, data)
+    }
+
+    /// Compute an RSASSA-PSS signature of a precomputed `S` digest.
+    #[cfg(feature = "untested")]
+    pub(crate) fn sign_rsa_pss_prehash<S>(
+        &self,
+        key_id: object::Id,
+        digest: &[u8],
+    ) -> Result<rsa::pss::Signature, Error>
+    where
+        S: rsa::SignatureAlgorithm,
+    {
         ensure!(
-            data.len() > rsa::pss::MAX_MESSAGE_SIZE,
+            digest.len() == <S as Digest>::output_size(),
             ErrorKind::ProtocolError,
-            "message too large to be signed (max: {})",
-            rsa::pss::MAX_MESSAGE_SIZE
+            "invalid digest length: {} (expected {})",
+            digest.len(),
+            <S as Digest>::output_size()
         );
 
-        let mut hasher = Sha256::default();
-
-        let length = data.len() as u16;
-        hasher.update(length.to_be_bytes());
-        hasher.update(data);
-        let digest = hasher.finalize();
-
         Ok(self
             .send_command(SignPssCommand {
                 key_id,
-                mgf1_hash_alg: rsa::mgf::Algorithm::Sha256,
-                salt_len: digest.as_slice().len() as u16,
-                digest: digest.as_slice().into(),
+                mgf1_hash_alg: S::MGF_ALGORITHM,
+                salt_len: digest.len() as u16,
+                digest: digest.into(),
             })?
             .into())
     }
 
     /// Sign an SSH certificate using the given template.
     ///
+    /// The `request` is the certificate to be signed in the OpenSSH format,
+    /// minus its trailing signature. The `signature` is made over the
+    /// `timestamp` (big endian) followed by the `request`, using the
+    /// RSA timestamp key contained in the template.
+    ///
     /// **WARNING**: This functionality has not been tested and has not yet been
     /// confirmed to actually work! USE AT YOUR OWN RISK!
     ///
@@ -1092,8 +1728,8 @@ impl Client {
         template_id: object::Id,
         algorithm: A,
         timestamp: u32,
-        signature: [u8; 32],
-        request: Vec<u8>,
+        signature: &[u8],
+        request: &[u8],
     ) -> Result<ssh::Certificate, Error>
     where
         A: Into<Algorithm>,
@@ -1104,8 +1740,7 @@ impl Client {
                 template_id,
                 algorithm: algorithm.into(),
                 timestamp,
-                signature,
-                request,
+                request: [signature, request].concat(),
             })?
             .into())
     }
@@ -1165,3 +1800,52 @@ impl Client {
             .0)
     }
 }
+
+/// Get the algorithm of an RSA key which can be used as a wrap key: the
+/// modulus must be a supported size and the public exponent must be 65537
+#[cfg(feature = "sha2")]
+fn rsa_algorithm(key: &impl PublicKeyParts) -> Result<asymmetric::Algorithm, Error> {
+    let algorithm = match key.size() {
+        256 => asymmetric::Algorithm::Rsa2048,
+        384 => asymmetric::Algorithm::Rsa3072,
+        512 => asymmetric::Algorithm::Rsa4096,
+        other => fail!(
+            ErrorKind::ProtocolError,
+            "unsupported RSA modulus size: {} bytes",
+            other
+        ),
+    };
+
+    ensure!(
+        key.e() == &BigUint::from(65537u32),
+        ErrorKind::ProtocolError,
+        "unsupported RSA public exponent: {}",
+        key.e()
+    );
+
+    Ok(algorithm)
+}
+
+#[cfg(all(test, feature = "mockhsm"))]
+mod tests {
+    use super::*;
+    use crate::{mockhsm::MockHsm, session::securechannel::MAX_COMMANDS_PER_SESSION};
+
+    #[test]
+    fn command_limit_rekey_test() {
+        let client = Client::open(MockHsm::new().into(), Credentials::default(), true).unwrap();
+
+        let session_id = client.session().unwrap().id();
+        client
+            .session()
+            .unwrap()
+            .set_messages_sent(MAX_COMMANDS_PER_SESSION);
+
+        // The command is retried on a new session
+        assert_eq!(client.echo(b"rekey").unwrap(), b"rekey");
+
+        let session = client.session().unwrap();
+        assert_ne!(session.id(), session_id);
+        assert!(session.messages_sent().unwrap() < MAX_COMMANDS_PER_SESSION as usize);
+    }
+}
@@ -17,6 +17,7 @@ pub enum Code {
     Bsl = 0x07,
     ResetDevice = 0x08,
     Command9 = 0x09, // TODO: What is Command 9???
+    GetDevicePublicKey = 0x0a,
     CloseSession = 0x40,
     GetStorageInfo = 0x41,
     PutOpaqueObject = 0x42,
@@ -62,6 +63,15 @@ pub enum Code {
     SignEddsa = 0x6a,
     BlinkDevice = 0x6b,
     ChangeAuthenticationKey = 0x6c,
+    PutSymmetricKey = 0x6d,
+    GenerateSymmetricKey = 0x6e,
+    DecryptEcb = 0x6f,
+    EncryptEcb = 0x70,
+    DecryptCbc = 0x71,
+    EncryptCbc = 0x72,
+    PutPublicWrapKey = 0x73,
+    ExportWrappedRsa = 0x76,
+    ImportWrappedRsa = 0x77,
     Error = 0x7f,
     HsmInitialization = 0xff,
 }
@@ -79,6 +89,7 @@ impl Code {
             0x07 => Code::Bsl,
             0x08 => Code::ResetDevice,
             0x09 => Code::Command9,
+            0x0a => Code::GetDevicePublicKey,
             0x40 => Code::CloseSession,
             0x41 => Code::GetStorageInfo,
             0x42 => Code::PutOpaqueObject,
@@ -124,6 +135,15 @@ impl Code {
             0x6a => Code::SignEddsa,
             0x6b => Code::BlinkDevice,
             0x6c => Code::ChangeAuthenticationKey,
+            0x6d => Code::PutSymmetricKey,
+            0x6e => Code::GenerateSymmetricKey,
+            0x6f => Code::DecryptEcb,
+            0x70 => Code::EncryptEcb,
+            0x71 => Code::DecryptCbc,
+            0x72 => Code::EncryptCbc,
+            0x73 => Code::PutPublicWrapKey,
+            0x76 => Code::ExportWrappedRsa,
+            0x77 => Code::ImportWrappedRsa,
             0x7f => Code::Error,
             0xff => Code::HsmInitialization,
             _ => fail!(ErrorKind::CodeInvalid, "invalid command type: {}", byte),
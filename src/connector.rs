@@ -75,8 +75,7 @@ impl Connector {
     /// Create a mock HSM connector (useful for testing)
     #[cfg(feature = "mockhsm")]
     pub fn mockhsm() -> Self {
-        let mockhsm: Box<dyn Connectable> = MockHsm::new().into();
-        Self::from(mockhsm)
+        MockHsm::new().into()
     }
 
     /// Send a command message to the HSM, then read and return the response
@@ -116,3 +115,11 @@ impl From<Box<dyn Connectable>> for Connector {
         }
     }
 }
+
+#[cfg(feature = "mockhsm")]
+impl From<MockHsm> for Connector {
+    fn from(mockhsm: MockHsm) -> Connector {
+        let driver: Box<dyn Connectable> = mockhsm.into();
+        Self::from(driver)
+    }
+}
@@ -3,8 +3,9 @@
 mod blink;
 mod echo;
 mod info;
+mod public_key;
 mod reset;
 mod rng;
 mod storage;
 
-pub(crate) use self::{blink::*, echo::*, info::*, reset::*, rng::*, storage::*};
+pub(crate) use self::{blink::*, echo::*, info::*, public_key::*, reset::*, rng::*, storage::*};
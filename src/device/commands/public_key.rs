@@ -0,0 +1,33 @@
+//! Get the device's public key, used to establish asymmetrically
+//! authenticated sessions
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Get_Device_Public_Key.html>
+
+use crate::{
+    asymmetric::PublicKey,
+    command::{self, Command},
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::get_device_public_key`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct GetDevicePublicKeyCommand {}
+
+impl Command for GetDevicePublicKeyCommand {
+    type ResponseType = GetDevicePublicKeyResponse;
+}
+
+/// Response from `command::get_device_public_key`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetDevicePublicKeyResponse(pub(crate) PublicKey);
+
+impl Response for GetDevicePublicKeyResponse {
+    const COMMAND_CODE: command::Code = command::Code::GetDevicePublicKey;
+}
+
+impl From<GetDevicePublicKeyResponse> for PublicKey {
+    fn from(response: GetDevicePublicKeyResponse) -> PublicKey {
+        response.0
+    }
+}
@@ -116,7 +116,7 @@ impl ErrorKind {
             0x0f => ErrorKind::CommandUnexecuted,
             0x10 => ErrorKind::GenericError,
             0x11 => ErrorKind::ObjectExists,
-            // TODO: determine correct value for YHR_DEVICE_SSH_CA_CONSTRAINT_VIOLATION
+            0x13 => ErrorKind::SshCaConstraintViolation,
             code => ErrorKind::Unknown { code },
         }
     }
@@ -142,10 +142,7 @@ impl ErrorKind {
             ErrorKind::CommandUnexecuted => 0x0f,
             ErrorKind::GenericError => 0x10,
             ErrorKind::ObjectExists => 0x11,
-            // TODO: determine correct value
-            ErrorKind::SshCaConstraintViolation => {
-                panic!("don't know device code for YHR_DEVICE_SSH_CA_CONSTRAINT_VIOLATION")
-            }
+            ErrorKind::SshCaConstraintViolation => 0x13,
         }
     }
 
@@ -20,6 +20,12 @@ impl Display for Number {
     }
 }
 
+impl From<Number> for u32 {
+    fn from(number: Number) -> u32 {
+        number.0
+    }
+}
+
 impl FromStr for Number {
     type Err = Error;
 
@@ -1,15 +1,13 @@
 //! Elliptic Curve Diffie Hellman Key Exchange.
 //!
-//! **WARNING**: This functionality has not been tested and has not yet been
-//! confirmed to actually work! USE AT YOUR OWN RISK!
-//!
-//! You will need to enable the `untested` cargo feature to use it.
+//! Supports NIST P-256, NIST P-384, and secp256k1 (with the `secp256k1`
+//! cargo feature enabled) keys.
 //!
 //! <https://developers.yubico.com/YubiHSM2/Commands/Derive_Ecdh.html>
 
 mod algorithm;
-#[cfg(feature = "untested")]
 pub(crate) mod commands;
 mod point;
 
 pub use self::{algorithm::Algorithm, point::UncompressedPoint};
+pub use ::ecdsa::elliptic_curve::{ecdh::SharedSecret, PublicKey};
@@ -1,10 +1,5 @@
 //! Elliptic Curve Diffie Hellman Commands
 //!
-//! **WARNING**: This functionality has not been tested and has not yet been
-//! confirmed to actually work! USE AT YOUR OWN RISK!
-//!
-//! You will need to enable the `untested` cargo feature to use it.
-//!
 //! <https://developers.yubico.com/YubiHSM2/Commands/Derive_Ecdh.html>
 
 use crate::{
@@ -13,6 +8,7 @@ use crate::{
     response::Response,
 };
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 /// Request parameters for `command::derive_ecdh`
 #[derive(Serialize, Deserialize, Debug)]
@@ -28,16 +24,11 @@ impl Command for DeriveEcdhCommand {
     type ResponseType = DeriveEcdhResponse;
 }
 
-/// Signed SSH certificates
-#[derive(Serialize, Deserialize, Debug)]
-pub struct DeriveEcdhResponse(ecdh::UncompressedPoint);
+/// Shared secret (i.e. x-coordinate of the shared point) computed by the HSM
+#[derive(Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub(crate) struct DeriveEcdhResponse(pub(crate) Vec<u8>);
 
 impl Response for DeriveEcdhResponse {
     const COMMAND_CODE: command::Code = command::Code::DeriveEcdh;
 }
-
-impl From<DeriveEcdhResponse> for ecdh::UncompressedPoint {
-    fn from(response: DeriveEcdhResponse) -> ecdh::UncompressedPoint {
-        response.0
-    }
-}
@@ -4,6 +4,8 @@ pub mod algorithm;
 pub mod nistp256;
 pub mod nistp384;
 
+#[cfg(feature = "nistp521")]
+pub mod nistp521;
 #[cfg(feature = "secp256k1")]
 pub mod secp256k1;
 
@@ -13,5 +15,7 @@ mod signer;
 pub use self::{algorithm::Algorithm, nistp256::NistP256, nistp384::NistP384, signer::Signer};
 pub use ::ecdsa::{der, elliptic_curve::sec1, signature, Signature};
 
+#[cfg(feature = "nistp521")]
+pub use self::nistp521::NistP521;
 #[cfg(feature = "secp256k1")]
 pub use self::secp256k1::Secp256k1;
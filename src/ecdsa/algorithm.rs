@@ -2,7 +2,10 @@
 
 use super::{NistP256, NistP384};
 use crate::{algorithm, asymmetric};
+use ::ecdsa::elliptic_curve::{generic_array::typenum::Unsigned, FieldBytesSize, PrimeCurve};
 
+#[cfg(feature = "nistp521")]
+use super::NistP521;
 #[cfg(feature = "secp256k1")]
 use super::Secp256k1;
 
@@ -47,6 +50,15 @@ impl Algorithm {
 
 impl_algorithm_serializers!(Algorithm);
 
+/// Truncate a digest which is larger than the curve's field size to its
+/// leftmost bytes, as specified by `bits2int` in FIPS 186-4 section 6.4.
+///
+/// The orders of all supported curves are a whole number of bytes long,
+/// except for P-521, whose 66-byte field is larger than any digest.
+pub(crate) fn truncate_digest<C: PrimeCurve>(digest: &[u8]) -> &[u8] {
+    &digest[..digest.len().min(FieldBytesSize::<C>::USIZE)]
+}
+
 /// Mappings from ECDSA curves to their corresponding asymmetric algorithm
 pub trait CurveAlgorithm {
     /// YubiHSM asymmetric algorithm for this elliptic curve
@@ -65,6 +77,13 @@ impl CurveAlgorithm for NistP384 {
     }
 }
 
+#[cfg(feature = "nistp521")]
+impl CurveAlgorithm for NistP521 {
+    fn asymmetric_algorithm() -> asymmetric::Algorithm {
+        asymmetric::Algorithm::EcP521
+    }
+}
+
 #[cfg(feature = "secp256k1")]
 impl CurveAlgorithm for Secp256k1 {
     fn asymmetric_algorithm() -> asymmetric::Algorithm {
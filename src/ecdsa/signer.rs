@@ -1,12 +1,23 @@
-//! ECDSA provider for the YubiHSM 2 crate (supporting NIST P-256 and secp256k1).
+//! ECDSA provider for the YubiHSM 2 crate (supporting NIST P-256, P-384, P-521
+//! and secp256k1).
 //!
+//! To enable P-521 support, build with the `nistp521` cargo feature enabled.
 //! To enable secp256k1 support, build with the `secp256k1` cargo feature enabled.
-
-use super::{algorithm::CurveAlgorithm, NistP256, NistP384};
+//!
+//! Brainpool curves (`EcBp256`, `EcBp384` and `EcBp512`) aren't supported:
+//! `bp256` and `bp384` v0.6 only provide their arithmetic as a work in
+//! progress (which serializes field elements without converting them out of
+//! Montgomery form), and there is no `bp512` crate. Keys on these curves can
+//! still be used directly with [`Client::sign_ecdsa_prehash_raw`].
+
+use super::{
+    algorithm::{truncate_digest, CurveAlgorithm},
+    NistP256, NistP384,
+};
 use crate::{object, Client};
 use ecdsa::{
     elliptic_curve::{
-        consts::U32,
+        consts::{U32, U48},
         generic_array::ArrayLength,
         point::PointCompression,
         sec1::{self, FromEncodedPoint, ToEncodedPoint},
@@ -19,6 +30,8 @@ use std::ops::Add;
 
 #[cfg(feature = "secp256k1")]
 use super::{secp256k1::RecoveryId, Secp256k1};
+#[cfg(feature = "nistp521")]
+use {super::NistP521, ecdsa::elliptic_curve::consts::U64};
 
 /// ECDSA signature provider for yubihsm-client
 #[derive(signature::Signer)]
@@ -80,7 +93,7 @@ where
 {
     fn sign_prehash_ecdsa(&self, prehash: &[u8]) -> Result<Signature<C>, Error> {
         self.client
-            .sign_ecdsa_prehash_raw(self.signing_key_id, prehash)
+            .sign_ecdsa_prehash_raw(self.signing_key_id, truncate_digest::<C>(prehash))
             .map_err(Error::from_source)
             .and_then(|der| Signature::from_der(&der))
     }
@@ -143,7 +156,7 @@ impl PrehashSigner<Signature<NistP384>> for Signer<NistP384> {
 
 impl<D> DigestSigner<D, Signature<NistP384>> for Signer<NistP384>
 where
-    D: Digest<OutputSize = U32> + Default,
+    D: Digest<OutputSize = U48> + Default,
 {
     /// Compute a fixed-sized P-384 ECDSA signature of the given digest
     fn try_sign_digest(&self, digest: D) -> Result<Signature<NistP384>, Error> {
@@ -151,6 +164,25 @@ where
     }
 }
 
+#[cfg(feature = "nistp521")]
+impl PrehashSigner<Signature<NistP521>> for Signer<NistP521> {
+    /// Compute a fixed-size P-521 ECDSA signature of a digest output.
+    fn sign_prehash(&self, prehash: &[u8]) -> Result<Signature<NistP521>, Error> {
+        self.sign_prehash_ecdsa(prehash)
+    }
+}
+
+#[cfg(feature = "nistp521")]
+impl<D> DigestSigner<D, Signature<NistP521>> for Signer<NistP521>
+where
+    D: Digest<OutputSize = U64> + Default,
+{
+    /// Compute a fixed-sized P-521 ECDSA signature of the given digest
+    fn try_sign_digest(&self, digest: D) -> Result<Signature<NistP521>, Error> {
+        self.sign_prehash(&digest.finalize())
+    }
+}
+
 #[cfg(feature = "secp256k1")]
 impl PrehashSigner<Signature<Secp256k1>> for Signer<Secp256k1> {
     fn sign_prehash(&self, prehash: &[u8]) -> Result<Signature<Secp256k1>, Error> {
@@ -71,7 +71,7 @@ pub mod ecdsa;
 pub mod ed25519;
 pub mod hmac;
 #[cfg(feature = "mockhsm")]
-pub(crate) mod mockhsm;
+pub mod mockhsm;
 pub mod object;
 pub mod opaque;
 pub mod otp;
@@ -81,6 +81,7 @@ pub mod session;
 #[cfg(feature = "setup")]
 pub mod setup;
 pub mod ssh;
+pub mod symmetric;
 pub mod template;
 mod uuid;
 pub mod wrap;
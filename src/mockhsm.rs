@@ -4,32 +4,59 @@
 #[cfg(not(debug_assertions))]
 compile_error!("MockHsm is not intended for use in release builds");
 
-use std::sync::{Arc, Mutex};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
+mod access;
+mod attestation;
 mod audit;
+mod builder;
+mod clock;
 mod command;
 mod connection;
 mod digest;
 mod error;
+mod fault;
 mod object;
+mod otp_aead;
 mod session;
+mod snapshot;
+#[cfg(feature = "untested")]
+mod ssh;
 mod state;
 
-use self::state::State;
 pub use self::{
+    builder::Builder,
+    clock::{Clock, ManualClock, SystemClock},
     connection::MockConnection,
     error::{Error, ErrorKind},
+    fault::{Fault, FaultPlan},
+};
+use self::state::State;
+use crate::{
+    audit::{AuditOption, LogEntries},
+    command::Code,
+    connector::{self, Connectable, Connection},
+    object::{Id as ObjectId, Info as ObjectInfo, Type as ObjectType},
 };
-use crate::connector::{self, Connectable, Connection};
 
-/// Mock serial number for the MockHsm
+/// Mock serial number for the MockHsm, unless configured otherwise using a
+/// [`Builder`]
 pub const MOCK_SERIAL_NUMBER: &str = "0123456789";
 
+/// Mock firmware version (major, minor, build) for the MockHsm, unless
+/// configured otherwise using a [`Builder`]
+const MOCK_FIRMWARE_VERSION: (u8, u8, u8) = (2, 0, 0);
+
 /// Software simulation of a `YubiHSM 2` intended for testing
 /// implemented as a `yubihsm::Connection`.
 ///
-/// This only implements a subset of the YubiHSM's functionality, and does
-/// *NOT* properly enforce access control / capabilities!
+/// This only implements a subset of the YubiHSM's functionality. Commands
+/// are checked against the capabilities and domains of the session's
+/// authentication key and the objects they operate on, however this is no
+/// substitute for testing access control policies against a real device.
 ///
 /// It is *STRONGLY* recommended to also test live against a real device.
 ///
@@ -40,7 +67,96 @@ pub struct MockHsm(Arc<Mutex<State>>);
 impl MockHsm {
     /// Create a new MockHsm
     pub fn new() -> Self {
-        MockHsm(Arc::new(Mutex::new(State::new())))
+        Self::with_clock(SystemClock)
+    }
+
+    /// Create a new MockHsm whose session inactivity timeouts and audit log
+    /// are driven by the given clock
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Self::builder().clock(clock).build().unwrap()
+    }
+
+    /// Create a builder for a MockHsm with custom device information and
+    /// preloaded objects
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Load a MockHsm from a snapshot previously written by [`MockHsm::save`].
+    ///
+    /// Use [`Builder::load`] to drive the loaded MockHsm with another clock.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::builder().load(path)
+    }
+
+    /// Save a snapshot of the MockHsm's objects, device-wide options (i.e.
+    /// audit and FIPS mode settings) and identity (i.e. serial number,
+    /// firmware version, device key and attestation key and certificate)
+    /// to the given path.
+    ///
+    /// Sessions and the audit log aren't saved.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        snapshot::write(&self.0.lock().unwrap(), path.as_ref())
+    }
+
+    /// Get information about all objects in the MockHsm, regardless of
+    /// their domains
+    pub fn objects(&self) -> Vec<ObjectInfo> {
+        let state = self.0.lock().unwrap();
+        state
+            .objects
+            .iter()
+            .map(|(_, obj)| obj.object_info.clone())
+            .collect()
+    }
+
+    /// Get information about an object in the MockHsm
+    pub fn object_info(&self, object_id: ObjectId, object_type: ObjectType) -> Option<ObjectInfo> {
+        let state = self.0.lock().unwrap();
+        state
+            .objects
+            .get(object_id, object_type)
+            .map(|obj| obj.object_info.clone())
+    }
+
+    /// Get the entries presently held in the audit log, without consuming
+    /// them
+    pub fn log_entries(&self) -> LogEntries {
+        self.0.lock().unwrap().audit_log.entries()
+    }
+
+    /// Get the audit setting for the given command
+    pub fn command_audit_option(&self, command_type: Code) -> AuditOption {
+        self.0
+            .lock()
+            .unwrap()
+            .command_audit_options
+            .get(command_type)
+    }
+
+    /// Get the force audit setting
+    pub fn force_audit_option(&self) -> AuditOption {
+        self.0.lock().unwrap().force_audit
+    }
+
+    /// Get the FIPS mode setting
+    pub fn fips_mode(&self) -> AuditOption {
+        self.0.lock().unwrap().fips
+    }
+
+    /// Inject faults into connections to the MockHsm and its responses
+    /// according to the given plan, replacing any previous plan
+    pub fn set_fault_plan(&self, fault_plan: FaultPlan) {
+        self.0.lock().unwrap().fault_plan = fault_plan;
+    }
+
+    /// Save a snapshot to the given path after every command which modifies
+    /// the MockHsm's objects or options, so its state can be reused by other
+    /// processes
+    pub fn persist_to(&self, path: impl Into<PathBuf>) -> Result<(), Error> {
+        let mut state = self.0.lock().unwrap();
+        state.persist_path = Some(path.into());
+        state.persist()
     }
 }
 
@@ -52,6 +168,10 @@ impl Connectable for MockHsm {
 
     /// Create a new connection with a clone of the MockHsm state
     fn connect(&self) -> Result<Box<dyn Connection>, connector::Error> {
+        if self.0.lock().unwrap().fault_plan.connect_failed() {
+            return Err(fault::connect_error());
+        }
+
         Ok(Box::new(MockConnection::new(self)))
     }
 }
@@ -0,0 +1,527 @@
+//! Access control for the `MockHsm`.
+//!
+//! Commands are checked against the capabilities and domains of the
+//! authentication key used to establish the session, as well as the
+//! capabilities of the objects they operate on, returning the same error
+//! codes as the `YubiHSM 2`.
+
+use super::{command::parse_command_prefix, object::Objects};
+use crate::{
+    command::Code,
+    device, object,
+    object::{generate, put},
+    Capability, Domain,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+/// Capabilities which operate on asymmetric keys, one of which a session
+/// needs in order to read their public keys
+const ASYMMETRIC_KEY_CAPABILITIES: Capability = Capability::GENERATE_ASYMMETRIC_KEY
+    .union(Capability::PUT_ASYMMETRIC_KEY)
+    .union(Capability::SIGN_ECDSA)
+    .union(Capability::SIGN_EDDSA)
+    .union(Capability::SIGN_PKCS)
+    .union(Capability::SIGN_PSS)
+    .union(Capability::DECRYPT_PKCS)
+    .union(Capability::DECRYPT_OAEP)
+    .union(Capability::DERIVE_ECDH)
+    .union(Capability::SIGN_ATTESTATION_CERTIFICATE)
+    .union(Capability::SIGN_SSH_CERTIFICATE);
+
+/// Permissions granted to a session by its authentication key
+#[derive(Debug)]
+pub(crate) struct Permissions {
+    /// Capabilities the session can use
+    pub capabilities: Capability,
+
+    /// Capabilities the session can grant to new objects
+    pub delegated_capabilities: Capability,
+
+    /// Domains the session can access
+    pub domains: Domain,
+}
+
+impl Permissions {
+    /// Get the permissions of a session authenticated with the given key.
+    ///
+    /// If the key has since been deleted, the session retains no permissions.
+    pub fn for_session(objects: &Objects, authentication_key_id: object::Id) -> Self {
+        match objects.get(authentication_key_id, object::Type::AuthenticationKey) {
+            Some(obj) => Self {
+                capabilities: obj.object_info.capabilities,
+                delegated_capabilities: obj.object_info.delegated_capabilities,
+                domains: obj.object_info.domains,
+            },
+            None => Self {
+                capabilities: Capability::empty(),
+                delegated_capabilities: Capability::empty(),
+                domains: Domain::empty(),
+            },
+        }
+    }
+
+    /// Is the given object accessible from this session?
+    pub fn can_access(&self, info: &object::Info) -> bool {
+        self.domains.intersects(info.domains)
+    }
+
+    /// Check whether this session may perform the given command.
+    ///
+    /// Commands too malformed to be authorized are rejected with the same
+    /// errors their handlers would return.
+    pub fn authorize(
+        &self,
+        objects: &Objects,
+        command_type: Code,
+        data: &[u8],
+    ) -> Result<(), device::ErrorKind> {
+        use object::Type::*;
+
+        match command_type {
+            Code::ChangeAuthenticationKey => self.require(Capability::CHANGE_AUTHENTICATION_KEY),
+            Code::CreateOtpAead => {
+                self.use_key(objects, data, OtpAeadKey, Capability::CREATE_OTP_AEAD)
+            }
+            Code::DecryptCbc => self.use_key(objects, data, SymmetricKey, Capability::DECRYPT_CBC),
+            Code::DecryptEcb => self.use_key(objects, data, SymmetricKey, Capability::DECRYPT_ECB),
+            Code::DecryptOaep => {
+                self.use_key(objects, data, AsymmetricKey, Capability::DECRYPT_OAEP)
+            }
+            Code::DecryptOtp => self.use_key(objects, data, OtpAeadKey, Capability::DECRYPT_OTP),
+            Code::DecryptPkcs1 => {
+                self.use_key(objects, data, AsymmetricKey, Capability::DECRYPT_PKCS)
+            }
+            Code::DeleteObject => {
+                parse(data, |handle: object::Handle| self.delete(objects, handle))
+            }
+            Code::DeriveEcdh => self.use_key(objects, data, AsymmetricKey, Capability::DERIVE_ECDH),
+            Code::EncryptCbc => self.use_key(objects, data, SymmetricKey, Capability::ENCRYPT_CBC),
+            Code::EncryptEcb => self.use_key(objects, data, SymmetricKey, Capability::ENCRYPT_ECB),
+            Code::ExportWrapped => self.export(objects, data, WrapKey),
+            Code::ExportWrappedRsa => self.export(objects, data, PublicWrapKey),
+            Code::GenerateAsymmetricKey => parse(data, |params: generate::Params| {
+                self.require(Capability::GENERATE_ASYMMETRIC_KEY)?;
+                self.create(
+                    objects,
+                    generated(params),
+                    AsymmetricKey,
+                    Capability::empty(),
+                )
+            }),
+            Code::GenerateHmacKey => parse(data, |params: generate::Params| {
+                self.require(Capability::GENERATE_HMAC_KEY)?;
+                self.create(objects, generated(params), HmacKey, Capability::empty())
+            }),
+            Code::GenerateOtpAead => parse(data, |params: generate::Params| {
+                self.require(Capability::GENERATE_OTP_AEAD_KEY)?;
+                self.create(objects, generated(params), OtpAeadKey, Capability::empty())
+            }),
+            Code::GenerateSymmetricKey => parse(data, |params: generate::Params| {
+                self.require(Capability::GENERATE_SYMMETRIC_KEY)?;
+                self.create(
+                    objects,
+                    generated(params),
+                    SymmetricKey,
+                    Capability::empty(),
+                )
+            }),
+            Code::GenerateWrapKey => parse(data, |cmd: Delegated<generate::Params>| {
+                self.require(Capability::GENERATE_WRAP_KEY)?;
+                self.create(
+                    objects,
+                    generated(cmd.params),
+                    WrapKey,
+                    cmd.delegated_capabilities,
+                )
+            }),
+            Code::GetLogEntries | Code::SetLogIndex => self.require(Capability::GET_LOG_ENTRIES),
+            Code::GetObjectInfo => parse(data, |handle: object::Handle| {
+                self.lookup(objects, handle.object_id, handle.object_type)
+                    .map(|_| ())
+            }),
+            Code::GetOpaqueObject => parse(data, |id: object::Id| {
+                self.require(Capability::GET_OPAQUE)?;
+
+                // Opaque object 0 is the device attestation certificate
+                if id != 0 {
+                    self.lookup(objects, id, Opaque)?;
+                }
+
+                Ok(())
+            }),
+            Code::GetOption => self.require(Capability::GET_OPTION),
+            Code::GetPseudoRandom => self.require(Capability::GET_PSEUDO_RANDOM),
+            Code::GetPublicKey => parse(data, |id: object::Id| {
+                self.require_any(ASYMMETRIC_KEY_CAPABILITIES)?;
+                self.lookup(objects, id, AsymmetricKey).map(|_| ())
+            }),
+            Code::GetTemplate => parse(data, |id: object::Id| {
+                self.require(Capability::GET_TEMPLATE)?;
+                self.lookup(objects, id, Template).map(|_| ())
+            }),
+            Code::ImportWrapped | Code::ImportWrappedRsa => {
+                self.use_key(objects, data, WrapKey, Capability::IMPORT_WRAPPED)
+            }
+            Code::PutAsymmetricKey => parse(data, |params: put::Params| {
+                self.require(Capability::PUT_ASYMMETRIC_KEY)?;
+                self.create(objects, params, AsymmetricKey, Capability::empty())
+            }),
+            Code::PutAuthenticationKey => parse(data, |cmd: Delegated<put::Params>| {
+                self.require(Capability::PUT_AUTHENTICATION_KEY)?;
+                self.create(
+                    objects,
+                    cmd.params,
+                    AuthenticationKey,
+                    cmd.delegated_capabilities,
+                )
+            }),
+            Code::PutHmacKey => parse(data, |params: put::Params| {
+                self.require(Capability::PUT_HMAC_KEY)?;
+                self.create(objects, params, HmacKey, Capability::empty())
+            }),
+            Code::PutOpaqueObject => parse(data, |params: put::Params| {
+                self.require(Capability::PUT_OPAQUE)?;
+                self.create(objects, params, Opaque, Capability::empty())
+            }),
+            Code::PutOtpAead => parse(data, |params: put::Params| {
+                self.require(Capability::PUT_OTP_AEAD_KEY)?;
+                self.create(objects, params, OtpAeadKey, Capability::empty())
+            }),
+            // There is no separate capability for importing RSA public wrap keys
+            Code::PutPublicWrapKey => parse(data, |cmd: Delegated<put::Params>| {
+                self.require(Capability::PUT_WRAP_KEY)?;
+                self.create(
+                    objects,
+                    cmd.params,
+                    PublicWrapKey,
+                    cmd.delegated_capabilities,
+                )
+            }),
+            Code::PutSymmetricKey => parse(data, |params: put::Params| {
+                self.require(Capability::PUT_SYMMETRIC_KEY)?;
+                self.create(objects, params, SymmetricKey, Capability::empty())
+            }),
+            Code::PutTemplate => parse(data, |params: put::Params| {
+                self.require(Capability::PUT_TEMPLATE)?;
+                self.create(objects, params, Template, Capability::empty())
+            }),
+            Code::PutWrapKey => parse(data, |cmd: Delegated<put::Params>| {
+                self.require(Capability::PUT_WRAP_KEY)?;
+                self.create(objects, cmd.params, WrapKey, cmd.delegated_capabilities)
+            }),
+            Code::RandomizeOtpAead => {
+                self.use_key(objects, data, OtpAeadKey, Capability::RANDOMIZE_OTP_AEAD)
+            }
+            Code::ResetDevice => self.require(Capability::RESET_DEVICE),
+            Code::RewrapOtpAead => parse(
+                data,
+                |(from_key_id, to_key_id): (object::Id, object::Id)| {
+                    self.use_object(
+                        objects,
+                        from_key_id,
+                        OtpAeadKey,
+                        Capability::REWRAP_FROM_OTP_AEAD_KEY,
+                    )?;
+                    self.use_object(
+                        objects,
+                        to_key_id,
+                        OtpAeadKey,
+                        Capability::REWRAP_TO_OTP_AEAD_KEY,
+                    )
+                },
+            ),
+            Code::SetOption => self.require(Capability::PUT_OPTION),
+            Code::SignAttestationCertificate => {
+                parse(
+                    data,
+                    |(key_id, attestation_key_id): (object::Id, object::Id)| {
+                        self.lookup(objects, key_id, AsymmetricKey)?;
+
+                        // Attestation key ID 0 selects the device attestation key
+                        if attestation_key_id == 0 {
+                            self.require(Capability::SIGN_ATTESTATION_CERTIFICATE)
+                        } else {
+                            self.use_object(
+                                objects,
+                                attestation_key_id,
+                                AsymmetricKey,
+                                Capability::SIGN_ATTESTATION_CERTIFICATE,
+                            )
+                        }
+                    },
+                )
+            }
+            Code::SignEcdsa => self.use_key(objects, data, AsymmetricKey, Capability::SIGN_ECDSA),
+            Code::SignEddsa => self.use_key(objects, data, AsymmetricKey, Capability::SIGN_EDDSA),
+            Code::SignHmac => self.use_key(objects, data, HmacKey, Capability::SIGN_HMAC),
+            Code::SignPkcs1 => self.use_key(objects, data, AsymmetricKey, Capability::SIGN_PKCS),
+            Code::SignPss => self.use_key(objects, data, AsymmetricKey, Capability::SIGN_PSS),
+            Code::SignSshCertificate => {
+                parse(data, |(key_id, template_id): (object::Id, object::Id)| {
+                    self.use_object(
+                        objects,
+                        key_id,
+                        AsymmetricKey,
+                        Capability::SIGN_SSH_CERTIFICATE,
+                    )?;
+                    self.lookup(objects, template_id, Template).map(|_| ())
+                })
+            }
+            Code::UnwrapData => self.use_key(objects, data, WrapKey, Capability::UNWRAP_DATA),
+            Code::VerifyHmac => self.use_key(objects, data, HmacKey, Capability::VERIFY_HMAC),
+            Code::WrapData => self.use_key(objects, data, WrapKey, Capability::WRAP_DATA),
+
+            // Commands which don't require any capabilities. `ListObjects`
+            // only returns objects in the session's domains
+            _ => Ok(()),
+        }
+    }
+
+    /// Require the session to have the given capability
+    fn require(&self, capability: Capability) -> Result<(), device::ErrorKind> {
+        if self.capabilities.contains(capability) {
+            Ok(())
+        } else {
+            debug!("authentication key lacks {:?} capability", capability);
+            Err(device::ErrorKind::InsufficientPermissions)
+        }
+    }
+
+    /// Require the session to have at least one of the given capabilities
+    fn require_any(&self, capabilities: Capability) -> Result<(), device::ErrorKind> {
+        if self.capabilities.intersects(capabilities) {
+            Ok(())
+        } else {
+            debug!(
+                "authentication key lacks any of {:?} capabilities",
+                capabilities
+            );
+            Err(device::ErrorKind::InsufficientPermissions)
+        }
+    }
+
+    /// Look up an object, which must be in one of the session's domains
+    fn lookup<'a>(
+        &self,
+        objects: &'a Objects,
+        object_id: object::Id,
+        object_type: object::Type,
+    ) -> Result<&'a object::Info, device::ErrorKind> {
+        match objects.get(object_id, object_type) {
+            Some(obj) if self.can_access(&obj.object_info) => Ok(&obj.object_info),
+            _ => {
+                debug!("no such {:?} object: {:?}", object_type, object_id);
+                Err(device::ErrorKind::ObjectNotFound)
+            }
+        }
+    }
+
+    /// Use the key whose ID is the first field of the command, requiring both
+    /// the session and the key to have the given capability
+    fn use_key(
+        &self,
+        objects: &Objects,
+        data: &[u8],
+        key_type: object::Type,
+        capability: Capability,
+    ) -> Result<(), device::ErrorKind> {
+        parse(data, |key_id: object::Id| {
+            self.use_object(objects, key_id, key_type, capability)
+        })
+    }
+
+    /// Use an object, requiring both the session and the object to have the
+    /// given capability
+    fn use_object(
+        &self,
+        objects: &Objects,
+        object_id: object::Id,
+        object_type: object::Type,
+        capability: Capability,
+    ) -> Result<(), device::ErrorKind> {
+        self.require(capability)?;
+
+        if self
+            .lookup(objects, object_id, object_type)?
+            .capabilities
+            .contains(capability)
+        {
+            Ok(())
+        } else {
+            debug!(
+                "{:?} object {:?} lacks {:?} capability",
+                object_type, object_id, capability
+            );
+            Err(device::ErrorKind::InsufficientPermissions)
+        }
+    }
+
+    /// Create a new object, whose domains and capabilities (including
+    /// delegated capabilities) must be within those of the session
+    fn create(
+        &self,
+        objects: &Objects,
+        params: put::Params,
+        object_type: object::Type,
+        delegated_capabilities: Capability,
+    ) -> Result<(), device::ErrorKind> {
+        if !self.domains.contains(params.domains) {
+            debug!(
+                "domains {:?} not a subset of the session's domains",
+                params.domains
+            );
+            return Err(device::ErrorKind::InsufficientPermissions);
+        }
+
+        let capabilities = params.capabilities | delegated_capabilities;
+
+        if !self.delegated_capabilities.contains(capabilities) {
+            debug!(
+                "capabilities {:?} not delegated to the authentication key",
+                capabilities - self.delegated_capabilities
+            );
+            return Err(device::ErrorKind::InsufficientPermissions);
+        }
+
+        if objects.get(params.id, object_type).is_some() {
+            debug!("{:?} object {:?} already exists", object_type, params.id);
+            return Err(device::ErrorKind::ObjectExists);
+        }
+
+        Ok(())
+    }
+
+    /// Delete an object, requiring the capability to delete its type
+    fn delete(&self, objects: &Objects, handle: object::Handle) -> Result<(), device::ErrorKind> {
+        use object::Type::*;
+
+        self.require(match handle.object_type {
+            AsymmetricKey => Capability::DELETE_ASYMMETRIC_KEY,
+            AuthenticationKey => Capability::DELETE_AUTHENTICATION_KEY,
+            HmacKey => Capability::DELETE_HMAC_KEY,
+            Opaque => Capability::DELETE_OPAQUE,
+            OtpAeadKey => Capability::DELETE_OTP_AEAD_KEY,
+            SymmetricKey => Capability::DELETE_SYMMETRIC_KEY,
+            Template => Capability::DELETE_TEMPLATE,
+            WrapKey | PublicWrapKey => Capability::DELETE_WRAP_KEY,
+        })?;
+
+        self.lookup(objects, handle.object_id, handle.object_type)
+            .map(|_| ())
+    }
+
+    /// Export an object under a wrap key, which must be able to export it
+    /// with all of its capabilities
+    fn export(
+        &self,
+        objects: &Objects,
+        data: &[u8],
+        wrap_key_type: object::Type,
+    ) -> Result<(), device::ErrorKind> {
+        parse(data, |(wrap_key_id, object_type, object_id)| {
+            let target = object::Handle::new(object_id, object_type);
+            self.use_object(
+                objects,
+                wrap_key_id,
+                wrap_key_type,
+                Capability::EXPORT_WRAPPED,
+            )?;
+
+            let wrap_key = self.lookup(objects, wrap_key_id, wrap_key_type)?;
+            let object = self.lookup(objects, target.object_id, target.object_type)?;
+
+            if !object
+                .capabilities
+                .contains(Capability::EXPORTABLE_UNDER_WRAP)
+            {
+                debug!(
+                    "{:?} object {:?} lacks EXPORTABLE_UNDER_WRAP capability",
+                    target.object_type, target.object_id
+                );
+                return Err(device::ErrorKind::InsufficientPermissions);
+            }
+
+            if !wrap_key
+                .delegated_capabilities
+                .contains(object.capabilities)
+            {
+                debug!(
+                    "capabilities {:?} not delegated to wrap key {:?}",
+                    object.capabilities - wrap_key.delegated_capabilities,
+                    wrap_key_id
+                );
+                return Err(device::ErrorKind::InsufficientPermissions);
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// Parameters for creating objects which carry delegated capabilities
+#[derive(Deserialize)]
+struct Delegated<P> {
+    params: P,
+    delegated_capabilities: Capability,
+}
+
+/// Parse the leading fields of a command needed to authorize it and
+/// apply the given check
+fn parse<T, F>(data: &[u8], check: F) -> Result<(), device::ErrorKind>
+where
+    T: DeserializeOwned,
+    F: FnOnce(T) -> Result<(), device::ErrorKind>,
+{
+    check(parse_command_prefix(data)?)
+}
+
+/// Convert key generation parameters into the equivalent put parameters
+fn generated(params: generate::Params) -> put::Params {
+    put::Params {
+        id: params.key_id,
+        label: params.label,
+        domains: params.domains,
+        capabilities: params.capabilities,
+        algorithm: params.algorithm,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Permissions of a session authenticated with the default key
+    fn default_permissions(objects: &Objects) -> Permissions {
+        Permissions::for_session(
+            objects,
+            crate::authentication::DEFAULT_AUTHENTICATION_KEY_ID,
+        )
+    }
+
+    #[test]
+    fn truncated_command_test() {
+        let objects = Objects::default();
+        let permissions = default_permissions(&objects);
+
+        assert_eq!(
+            permissions.authorize(&objects, Code::SignEcdsa, &[0x00]),
+            Err(device::ErrorKind::WrongLength)
+        );
+        assert_eq!(
+            permissions.authorize(&objects, Code::DeleteObject, &[0x00, 0x64]),
+            Err(device::ErrorKind::WrongLength)
+        );
+    }
+
+    #[test]
+    fn invalid_command_test() {
+        let objects = Objects::default();
+        let permissions = default_permissions(&objects);
+
+        // 0xff isn't a valid object type
+        assert_eq!(
+            permissions.authorize(&objects, Code::DeleteObject, &[0x00, 0x64, 0xff]),
+            Err(device::ErrorKind::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! X.509 attestation certificates issued by the MockHsm.
+//!
+//! Certificates carry the same Yubico extensions as the ones issued by a
+//! real YubiHSM 2:
+//!
+//! <https://developers.yubico.com/YubiHSM2/Concepts/Attestation.html>
+
+use super::object::{Object, Payload};
+use crate::device::{self, SerialNumber};
+use ::rsa::{pkcs8::EncodePublicKey, Pkcs1v15Sign};
+use ecdsa::elliptic_curve::sec1::ToEncodedPoint;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use signature::Signer;
+use std::{
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use x509_cert::{
+    certificate::{Certificate, TbsCertificate, Version},
+    der::{
+        asn1::{BitString, GeneralizedTime, OctetString, UtcTime},
+        oid::{
+            db::{rfc5280, rfc5912, rfc8410},
+            ObjectIdentifier,
+        },
+        Any, DateTime, Decode, Encode,
+    },
+    ext::{pkix::BasicConstraints, Extension},
+    name::Name,
+    serial_number,
+    spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned},
+    time::{Time, Validity},
+};
+
+#[cfg(feature = "nistp521")]
+use signature::RandomizedSigner;
+
+/// Firmware version extension
+const FIRMWARE_VERSION_EXTENSION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.1");
+
+/// Serial number extension
+const SERIAL_NUMBER_EXTENSION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.2");
+
+/// Origin extension
+const ORIGIN_EXTENSION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.3");
+
+/// Domains extension
+const DOMAINS_EXTENSION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.4");
+
+/// Capabilities extension
+const CAPABILITIES_EXTENSION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.5");
+
+/// Object ID extension
+const OBJECT_ID_EXTENSION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.6");
+
+/// Label extension
+const LABEL_EXTENSION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.41482.4.9");
+
+/// secp256k1 elliptic curve (not in `const-oid`'s database)
+const SECP_256_K_1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.10");
+
+/// Issue a self-signed certificate for the device attestation key
+pub(super) fn device_certificate(key: &Payload, serial_number: SerialNumber) -> Vec<u8> {
+    let name = name(&format!("YubiHSM Attestation ({serial_number})"));
+
+    let basic_constraints = BasicConstraints {
+        ca: true,
+        path_len_constraint: None,
+    };
+
+    certificate(
+        key,
+        name.clone(),
+        name,
+        public_key_info(key).expect("unsupported device attestation key"),
+        vec![extension(rfc5280::ID_CE_BASIC_CONSTRAINTS, true, &basic_constraints).unwrap()],
+    )
+    .expect("unsupported device attestation key")
+}
+
+/// Issue a certificate attesting to the given asymmetric key, signed by the
+/// attestation key with the given certificate
+pub(super) fn attestation_certificate(
+    attestation_key: &Payload,
+    attestation_certificate: &[u8],
+    subject: &Object,
+    serial_number: SerialNumber,
+    firmware_version: (u8, u8, u8),
+) -> Result<Vec<u8>, device::ErrorKind> {
+    let issuer = Certificate::from_der(attestation_certificate)
+        .map_err(|e| {
+            debug!("can't parse attestation certificate: {}", e);
+            device::ErrorKind::InvalidData
+        })?
+        .tbs_certificate
+        .subject;
+
+    let info = &subject.object_info;
+
+    let public_key_info = public_key_info(&subject.payload).ok_or_else(|| {
+        debug!("can't attest to {:?} keys", info.algorithm);
+        device::ErrorKind::InvalidData
+    })?;
+
+    let (major, minor, build) = firmware_version;
+    let label = info.label.try_as_str().unwrap_or_default();
+
+    let extensions = (|| {
+        Ok(vec![
+            extension(
+                FIRMWARE_VERSION_EXTENSION,
+                false,
+                &OctetString::new([major, minor, build])?,
+            )?,
+            extension(SERIAL_NUMBER_EXTENSION, false, &u32::from(serial_number))?,
+            extension(
+                ORIGIN_EXTENSION,
+                false,
+                &BitString::from_bytes(&[info.origin.to_u8()])?,
+            )?,
+            extension(
+                DOMAINS_EXTENSION,
+                false,
+                &BitString::from_bytes(&info.domains.bits().to_be_bytes())?,
+            )?,
+            extension(
+                CAPABILITIES_EXTENSION,
+                false,
+                &BitString::from_bytes(&info.capabilities.bits().to_be_bytes())?,
+            )?,
+            extension(OBJECT_ID_EXTENSION, false, &info.object_id)?,
+            extension(LABEL_EXTENSION, false, &label.to_owned())?,
+        ])
+    })()
+    .map_err(encoding_error)?;
+
+    certificate(
+        attestation_key,
+        issuer,
+        name(&format!("YubiHSM Attestation id:0x{:04x}", info.object_id)),
+        public_key_info,
+        extensions,
+    )
+}
+
+/// Build and sign an X.509 v3 certificate
+fn certificate(
+    signing_key: &Payload,
+    issuer: Name,
+    subject: Name,
+    subject_public_key_info: SubjectPublicKeyInfoOwned,
+    extensions: Vec<Extension>,
+) -> Result<Vec<u8>, device::ErrorKind> {
+    let signature_algorithm = signature_algorithm(signing_key).ok_or_else(|| {
+        debug!(
+            "can't sign attestation certificates with {:?} keys",
+            signing_key.algorithm()
+        );
+        device::ErrorKind::InvalidData
+    })?;
+
+    let tbs_certificate = TbsCertificate {
+        version: Version::V3,
+        serial_number: serial_number().map_err(encoding_error)?,
+        signature: signature_algorithm.clone(),
+        issuer,
+        validity: validity().map_err(encoding_error)?,
+        subject,
+        subject_public_key_info,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions: Some(extensions),
+    };
+
+    let signature = sign(signing_key, &tbs_certificate.to_der().map_err(encoding_error)?);
+
+    Certificate {
+        tbs_certificate,
+        signature_algorithm,
+        signature: BitString::from_bytes(&signature).map_err(encoding_error)?,
+    }
+    .to_der()
+    .map_err(encoding_error)
+}
+
+/// AlgorithmIdentifier of the signature algorithm used with the given key
+fn signature_algorithm(key: &Payload) -> Option<AlgorithmIdentifierOwned> {
+    let (oid, parameters) = match key {
+        Payload::EcdsaNistP256(_) | Payload::EcdsaSecp256k1(_) => {
+            (rfc5912::ECDSA_WITH_SHA_256, None)
+        }
+        Payload::EcdsaNistP384(_) => (rfc5912::ECDSA_WITH_SHA_384, None),
+        #[cfg(feature = "nistp521")]
+        Payload::EcdsaNistP521(_) => (rfc5912::ECDSA_WITH_SHA_512, None),
+        Payload::RsaKey(..) => (rfc5912::SHA_256_WITH_RSA_ENCRYPTION, Some(Any::null())),
+        _ => return None,
+    };
+
+    Some(AlgorithmIdentifierOwned { oid, parameters })
+}
+
+/// Sign the given data with the algorithm given by `signature_algorithm`
+fn sign(key: &Payload, data: &[u8]) -> Vec<u8> {
+    match key {
+        Payload::EcdsaNistP256(k) => {
+            let signature: p256::ecdsa::Signature = p256::ecdsa::SigningKey::from(k).sign(data);
+            signature.to_der().as_bytes().into()
+        }
+        Payload::EcdsaNistP384(k) => {
+            let signature: p384::ecdsa::Signature = p384::ecdsa::SigningKey::from(k).sign(data);
+            signature.to_der().as_bytes().into()
+        }
+        #[cfg(feature = "nistp521")]
+        Payload::EcdsaNistP521(k) => {
+            let signature: p521::ecdsa::Signature =
+                p521::ecdsa::SigningKey::from_slice(&k.to_bytes())
+                    .unwrap()
+                    .sign_with_rng(&mut OsRng, data);
+            signature.to_der().as_bytes().into()
+        }
+        Payload::EcdsaSecp256k1(k) => {
+            let signature: k256::ecdsa::Signature = k256::ecdsa::SigningKey::from(k).sign(data);
+            signature.to_der().as_bytes().into()
+        }
+        Payload::RsaKey(_, k) => k
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(data))
+            .expect("RSA signing failure!"),
+        _ => unreachable!("no signature algorithm for {:?}", key.algorithm()),
+    }
+}
+
+/// SubjectPublicKeyInfo for the given asymmetric key
+fn public_key_info(key: &Payload) -> Option<SubjectPublicKeyInfoOwned> {
+    let ec_public_key = |curve: ObjectIdentifier, point: &[u8]| {
+        Some(SubjectPublicKeyInfoOwned {
+            algorithm: AlgorithmIdentifierOwned {
+                oid: rfc5912::ID_EC_PUBLIC_KEY,
+                parameters: Some(Any::encode_from(&curve).ok()?),
+            },
+            subject_public_key: BitString::from_bytes(point).ok()?,
+        })
+    };
+
+    match key {
+        Payload::EcdsaNistP256(k) => ec_public_key(
+            rfc5912::SECP_256_R_1,
+            k.public_key().to_encoded_point(false).as_bytes(),
+        ),
+        Payload::EcdsaNistP384(k) => ec_public_key(
+            rfc5912::SECP_384_R_1,
+            k.public_key().to_encoded_point(false).as_bytes(),
+        ),
+        #[cfg(feature = "nistp521")]
+        Payload::EcdsaNistP521(k) => ec_public_key(
+            rfc5912::SECP_521_R_1,
+            k.public_key().to_encoded_point(false).as_bytes(),
+        ),
+        Payload::EcdsaSecp256k1(k) => ec_public_key(
+            SECP_256_K_1,
+            k.public_key().to_encoded_point(false).as_bytes(),
+        ),
+        Payload::Ed25519Key(k) => Some(SubjectPublicKeyInfoOwned {
+            algorithm: AlgorithmIdentifierOwned {
+                oid: rfc8410::ID_ED_25519,
+                parameters: None,
+            },
+            subject_public_key: BitString::from_bytes(k.verifying_key().as_bytes()).ok()?,
+        }),
+        Payload::RsaKey(_, k) => {
+            let der = k.to_public_key().to_public_key_der().ok()?;
+            SubjectPublicKeyInfoOwned::from_der(der.as_bytes()).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Name consisting of a single common name
+fn name(common_name: &str) -> Name {
+    Name::from_str(&format!("CN={common_name}")).expect("invalid common name")
+}
+
+/// Extension with the given OID and value
+fn extension(
+    extn_id: ObjectIdentifier,
+    critical: bool,
+    value: &impl Encode,
+) -> x509_cert::der::Result<Extension> {
+    Ok(Extension {
+        extn_id,
+        critical,
+        extn_value: OctetString::new(value.to_der()?)?,
+    })
+}
+
+/// Random positive certificate serial number
+fn serial_number() -> x509_cert::der::Result<serial_number::SerialNumber> {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes[0] = (bytes[0] & 0x7f) | 0x40;
+    serial_number::SerialNumber::new(&bytes)
+}
+
+/// Validity period of a certificate issued now, which never expires
+fn validity() -> x509_cert::der::Result<Validity> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before UNIX epoch");
+
+    Ok(Validity {
+        not_before: Time::UtcTime(UtcTime::from_unix_duration(now)?),
+        not_after: Time::GeneralTime(GeneralizedTime::from_date_time(DateTime::INFINITY)),
+    })
+}
+
+/// Error encoding part of a certificate
+fn encoding_error(e: x509_cert::der::Error) -> device::ErrorKind {
+    debug!("error encoding attestation certificate: {}", e);
+    device::ErrorKind::InvalidData
+}
@@ -1,9 +1,17 @@
-//! (Partial) support for audit logging within the MockHsm
-//!
-//! No logging is performed and these settings are not yet enforced
+//! Audit logging within the MockHsm
 
-use crate::{audit::*, command, serialization::serialize};
-use std::collections::BTreeMap;
+use crate::{audit::*, command, device, object, response, serialization::serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Instant,
+};
+
+/// Maximum number of entries held in the audit log
+pub const LOG_CAPACITY: usize = 62;
+
+/// Object ID recorded for log fields which don't apply to a command
+const NO_OBJECT: object::Id = 0xffff;
 
 /// Default per-command auditing options
 pub const DEFAULT_COMMAND_AUDIT_OPTIONS: &[AuditCommand] = &[
@@ -14,6 +22,7 @@ pub const DEFAULT_COMMAND_AUDIT_OPTIONS: &[AuditCommand] = &[
     AuditCommand(command::Code::DeviceInfo, AuditOption::Off),
     AuditCommand(command::Code::Bsl, AuditOption::Off),
     AuditCommand(command::Code::Command9, AuditOption::Off),
+    AuditCommand(command::Code::GetDevicePublicKey, AuditOption::Off),
     AuditCommand(command::Code::ResetDevice, AuditOption::On),
     AuditCommand(command::Code::CloseSession, AuditOption::On),
     AuditCommand(command::Code::GetStorageInfo, AuditOption::On),
@@ -59,6 +68,15 @@ pub const DEFAULT_COMMAND_AUDIT_OPTIONS: &[AuditCommand] = &[
     AuditCommand(command::Code::UnwrapData, AuditOption::On),
     AuditCommand(command::Code::SignEddsa, AuditOption::On),
     AuditCommand(command::Code::BlinkDevice, AuditOption::On),
+    AuditCommand(command::Code::PutSymmetricKey, AuditOption::On),
+    AuditCommand(command::Code::GenerateSymmetricKey, AuditOption::On),
+    AuditCommand(command::Code::DecryptEcb, AuditOption::On),
+    AuditCommand(command::Code::EncryptEcb, AuditOption::On),
+    AuditCommand(command::Code::DecryptCbc, AuditOption::On),
+    AuditCommand(command::Code::EncryptCbc, AuditOption::On),
+    AuditCommand(command::Code::PutPublicWrapKey, AuditOption::On),
+    AuditCommand(command::Code::ExportWrappedRsa, AuditOption::On),
+    AuditCommand(command::Code::ImportWrappedRsa, AuditOption::On),
 ];
 
 /// Per-command auditing settings
@@ -77,6 +95,19 @@ impl CommandAuditOptions {
         serialize(&audit_command).unwrap()
     }
 
+    /// Get the audit setting for the given command
+    pub fn get(&self, command_type: command::Code) -> AuditOption {
+        self.0
+            .get(&command_type)
+            .copied()
+            .unwrap_or(AuditOption::Off)
+    }
+
+    /// Is the given command logged?
+    pub fn is_audited(&self, command_type: command::Code) -> bool {
+        self.get(command_type) != AuditOption::Off
+    }
+
     /// Change a setting for a particular command
     pub fn put(&mut self, command_type: command::Code, audit_option: AuditOption) {
         self.0.insert(command_type, audit_option);
@@ -94,3 +125,164 @@ impl Default for CommandAuditOptions {
         CommandAuditOptions(result)
     }
 }
+
+/// Audit log: a ring buffer of entries whose truncated SHA-256 digests form
+/// a hash chain, each covering the entry and the digest of its predecessor
+#[derive(Debug)]
+pub struct AuditLog {
+    /// Entries presently held in the log
+    entries: VecDeque<LogEntry>,
+
+    /// Index of the last entry consumed via `SetLogIndex`
+    last_index: u16,
+
+    /// Authentication events which couldn't be logged
+    unlogged_auth_events: u16,
+
+    /// Time the log was initialized, used to compute ticks
+    boot_time: Instant,
+}
+
+impl AuditLog {
+    /// Create a new audit log containing an initialization entry. The
+    /// digest chain starts from an all-zero digest.
+    pub fn new(boot_time: Instant) -> Self {
+        let mut log = Self {
+            entries: VecDeque::with_capacity(LOG_CAPACITY),
+            last_index: 0,
+            unlogged_auth_events: 0,
+            boot_time,
+        };
+
+        log.push(LogEntry {
+            item: 1,
+            cmd: command::Code::HsmInitialization,
+            length: 0xffff,
+            session_key: NO_OBJECT,
+            target_key: NO_OBJECT,
+            second_key: NO_OBJECT,
+            result: response::Code::Success(command::Code::Error),
+            tick: u32::MAX,
+            digest: LogDigest([0u8; LOG_DIGEST_SIZE]),
+        });
+
+        log
+    }
+
+    /// Are there as many unconsumed entries as the log can hold?
+    pub fn is_full(&self) -> bool {
+        usize::from(self.last_item().wrapping_sub(self.last_index)) >= LOG_CAPACITY
+    }
+
+    /// Record a command in the log, evicting the oldest entry if needed
+    pub fn record(
+        &mut self,
+        command: &command::Message,
+        session_key: object::Id,
+        result: response::Code,
+        now: Instant,
+    ) {
+        let (target_key, second_key) = target_keys(command);
+
+        self.push(LogEntry {
+            item: self.last_item().wrapping_add(1),
+            cmd: command.command_type,
+            length: command.len() as u16,
+            session_key,
+            target_key,
+            second_key,
+            result,
+            tick: now.duration_since(self.boot_time).as_millis() as u32,
+            digest: LogDigest([0u8; LOG_DIGEST_SIZE]),
+        });
+    }
+
+    /// Note an authentication event which couldn't be logged
+    pub fn record_unlogged_auth_event(&mut self) {
+        self.unlogged_auth_events = self.unlogged_auth_events.saturating_add(1);
+    }
+
+    /// Mark all entries up to and including the given index as consumed
+    pub fn set_index(&mut self, log_index: u16) -> Result<(), device::ErrorKind> {
+        if !self.entries.iter().any(|entry| entry.item == log_index) {
+            debug!("no such log entry: {}", log_index);
+            return Err(device::ErrorKind::InvalidData);
+        }
+
+        self.last_index = log_index;
+        Ok(())
+    }
+
+    /// Get the entries presently held in the log
+    pub fn entries(&self) -> LogEntries {
+        LogEntries {
+            unlogged_boot_events: 0,
+            unlogged_auth_events: self.unlogged_auth_events,
+            num_entries: self.entries.len() as u8,
+            entries: self.entries.iter().cloned().collect(),
+        }
+    }
+
+    /// Number of the most recent entry
+    fn last_item(&self) -> u16 {
+        self.entries.back().map(|entry| entry.item).unwrap_or(0)
+    }
+
+    /// Chain the given entry onto the log and append it
+    fn push(&mut self, mut entry: LogEntry) {
+        let previous_digest = self
+            .entries
+            .back()
+            .map(|entry| entry.digest.0)
+            .unwrap_or_default();
+
+        let serialized_entry = serialize(&entry).unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update(&serialized_entry[..serialized_entry.len() - LOG_DIGEST_SIZE]);
+        hasher.update(previous_digest);
+        entry
+            .digest
+            .0
+            .copy_from_slice(&hasher.finalize()[..LOG_DIGEST_SIZE]);
+
+        if self.entries.len() == LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+}
+
+/// Get the IDs of the objects a command operates on, as recorded in the log
+fn target_keys(command: &command::Message) -> (object::Id, object::Id) {
+    let id_at = |offset: usize| {
+        command
+            .data
+            .get(offset..offset + 2)
+            .map(|bytes| object::Id::from_be_bytes([bytes[0], bytes[1]]))
+            .unwrap_or(NO_OBJECT)
+    };
+
+    match command.command_type {
+        command::Code::Echo
+        | command::Code::CreateSession
+        | command::Code::AuthenticateSession
+        | command::Code::DeviceInfo
+        | command::Code::ResetDevice
+        | command::Code::CloseSession
+        | command::Code::GetStorageInfo
+        | command::Code::ListObjects
+        | command::Code::GetLogEntries
+        | command::Code::SetLogIndex
+        | command::Code::SetOption
+        | command::Code::GetOption
+        | command::Code::GetPseudoRandom
+        | command::Code::BlinkDevice => (NO_OBJECT, NO_OBJECT),
+        command::Code::ExportWrapped | command::Code::ExportWrappedRsa => (id_at(0), id_at(3)),
+        command::Code::RewrapOtpAead
+        | command::Code::SignAttestationCertificate
+        | command::Code::SignSshCertificate => (id_at(0), id_at(2)),
+        _ => (id_at(0), NO_OBJECT),
+    }
+}
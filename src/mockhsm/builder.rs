@@ -0,0 +1,380 @@
+//! Builder for `MockHsm` instances preloaded with objects
+
+#![allow(clippy::too_many_arguments)]
+
+use super::{
+    clock::{Clock, SystemClock},
+    fault::FaultPlan,
+    snapshot::Snapshot,
+    state::State,
+    Error, MockHsm, MOCK_FIRMWARE_VERSION, MOCK_SERIAL_NUMBER,
+};
+use crate::{
+    asymmetric, authentication, device::SerialNumber, hmac, object, opaque, otp, symmetric,
+    template::Template, wrap, Capability, Domain,
+};
+use ecdsa::elliptic_curve::sec1::ToEncodedPoint;
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+/// Builder for a `MockHsm` with custom device information and preloaded
+/// objects (e.g. keys provisioned for an application's integration tests).
+///
+/// Like a factory-reset YubiHSM 2, the built `MockHsm` contains the default
+/// authentication key, unless an authentication key with the same ID is
+/// added to the builder, in which case it replaces the default one.
+#[derive(Debug)]
+pub struct Builder {
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
+
+    /// Serial number of the device
+    serial_number: SerialNumber,
+
+    /// Firmware version of the device
+    firmware_version: (u8, u8, u8),
+
+    /// Faults to inject into connections and responses
+    fault_plan: FaultPlan,
+
+    /// Objects to put into the device
+    objects: Vec<SeedObject>,
+}
+
+impl Builder {
+    /// Create a new `MockHsm` builder
+    pub fn new() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            serial_number: MOCK_SERIAL_NUMBER.parse().unwrap(),
+            firmware_version: MOCK_FIRMWARE_VERSION,
+            fault_plan: FaultPlan::default(),
+            objects: vec![],
+        }
+    }
+
+    /// Set the clock driving session inactivity timeouts and the audit log
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Set the serial number reported by the device
+    pub fn serial_number(mut self, serial_number: SerialNumber) -> Self {
+        self.serial_number = serial_number;
+        self
+    }
+
+    /// Set the firmware version reported by the device
+    pub fn firmware_version(mut self, major: u8, minor: u8, build: u8) -> Self {
+        self.firmware_version = (major, minor, build);
+        self
+    }
+
+    /// Inject faults into connections and responses according to the
+    /// given plan
+    pub fn fault_plan(mut self, fault_plan: FaultPlan) -> Self {
+        self.fault_plan = fault_plan;
+        self
+    }
+
+    /// Add an asymmetric (EC P-256) authentication key to the device, for
+    /// opening sessions with the corresponding private key
+    pub fn asymmetric_authentication_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        public_key: &p256::PublicKey,
+    ) -> Self {
+        self.object(
+            object::Type::AuthenticationKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: authentication::Algorithm::EcP256.into(),
+            },
+            delegated_capabilities,
+            public_key.to_encoded_point(false).as_bytes()[1..].into(),
+        )
+    }
+
+    /// Add an asymmetric key to the device
+    pub fn asymmetric_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: asymmetric::Algorithm,
+        key_bytes: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.object(
+            object::Type::AsymmetricKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            },
+            Capability::default(),
+            key_bytes.into(),
+        )
+    }
+
+    /// Add a symmetric (YubicoAes) authentication key to the device
+    pub fn authentication_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        authentication_key: impl Into<authentication::Key>,
+    ) -> Self {
+        self.object(
+            object::Type::AuthenticationKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: authentication::Algorithm::YubicoAes.into(),
+            },
+            delegated_capabilities,
+            authentication_key.into().0.to_vec(),
+        )
+    }
+
+    /// Add an HMAC key to the device
+    pub fn hmac_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: hmac::Algorithm,
+        key_bytes: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.object(
+            object::Type::HmacKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            },
+            Capability::default(),
+            key_bytes.into(),
+        )
+    }
+
+    /// Add an opaque object (e.g. an X.509 certificate) to the device
+    pub fn opaque(
+        self,
+        object_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: opaque::Algorithm,
+        opaque_data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.object(
+            object::Type::Opaque,
+            object::put::Params {
+                id: object_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            },
+            Capability::default(),
+            opaque_data.into(),
+        )
+    }
+
+    /// Add an OTP AEAD key to the device
+    pub fn otp_aead_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: otp::Algorithm,
+        nonce_id: otp::Nonce,
+        key_bytes: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.object(
+            object::Type::OtpAeadKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            },
+            Capability::default(),
+            [nonce_id.as_ref(), &key_bytes.into()].concat(),
+        )
+    }
+
+    /// Add a symmetric (AES) key to the device
+    pub fn symmetric_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        algorithm: symmetric::Algorithm,
+        key_bytes: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.object(
+            object::Type::SymmetricKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            },
+            Capability::default(),
+            key_bytes.into(),
+        )
+    }
+
+    /// Add a template (e.g. an SSH certificate template) to the device
+    pub fn template(
+        self,
+        object_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        template: impl Into<Template>,
+    ) -> Self {
+        let template = template.into();
+
+        self.object(
+            object::Type::Template,
+            object::put::Params {
+                id: object_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: template.algorithm().into(),
+            },
+            Capability::default(),
+            template.as_ref().into(),
+        )
+    }
+
+    /// Add a wrap key to the device
+    pub fn wrap_key(
+        self,
+        key_id: object::Id,
+        label: object::Label,
+        domains: Domain,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        algorithm: wrap::Algorithm,
+        key_bytes: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.object(
+            object::Type::WrapKey,
+            object::put::Params {
+                id: key_id,
+                label,
+                domains,
+                capabilities,
+                algorithm: algorithm.into(),
+            },
+            delegated_capabilities,
+            key_bytes.into(),
+        )
+    }
+
+    /// Build the `MockHsm`, returning an error if the objects don't fit in
+    /// its storage
+    pub fn build(self) -> Result<MockHsm, Error> {
+        self.build_from(None)
+    }
+
+    /// Build a `MockHsm` from a snapshot previously written by
+    /// [`MockHsm::save`], driven by this builder's clock and fault plan.
+    ///
+    /// The serial number and firmware version are those in the snapshot.
+    /// Objects added to the builder replace the snapshot's objects with the
+    /// same ID and type.
+    pub fn load(self, path: impl AsRef<Path>) -> Result<MockHsm, Error> {
+        let snapshot = Snapshot::from_bytes(&fs::read(path)?)?;
+        self.build_from(Some(snapshot))
+    }
+
+    /// Build the `MockHsm`, optionally restoring a snapshot before putting
+    /// the objects added to the builder
+    fn build_from(self, snapshot: Option<Snapshot>) -> Result<MockHsm, Error> {
+        let mut state = State::new(self.clock, self.serial_number, self.firmware_version);
+        state.fault_plan = self.fault_plan;
+
+        if let Some(snapshot) = snapshot {
+            state.restore(snapshot);
+        }
+
+        for seed in self.objects {
+            let params = seed.params;
+            state.objects.remove(params.id, seed.object_type);
+            state.objects.put(
+                params.id,
+                seed.object_type,
+                params.algorithm,
+                params.label,
+                params.capabilities,
+                seed.delegated_capabilities,
+                params.domains,
+                &seed.data,
+            )?;
+        }
+
+        Ok(MockHsm(Arc::new(Mutex::new(state))))
+    }
+
+    /// Add an object to the device
+    fn object(
+        mut self,
+        object_type: object::Type,
+        params: object::put::Params,
+        delegated_capabilities: Capability,
+        data: Vec<u8>,
+    ) -> Self {
+        self.objects.push(SeedObject {
+            object_type,
+            params,
+            delegated_capabilities,
+            data,
+        });
+        self
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Object to put into the device when it's built
+#[derive(Debug)]
+struct SeedObject {
+    object_type: object::Type,
+    params: object::put::Params,
+    delegated_capabilities: Capability,
+    data: Vec<u8>,
+}
@@ -0,0 +1,73 @@
+//! Clocks driving the time-dependent behavior of the `MockHsm`, i.e. session
+//! inactivity timeouts and audit log ticks
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Source of the current time for the `MockHsm`
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current time
+    fn now(&self) -> Instant;
+
+    /// Wait for the given duration, e.g. to delay a response
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Clock which follows the system's monotonic clock (the default)
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock which only moves forward when explicitly advanced, for testing
+/// timeouts deterministically. Clones of a `ManualClock` share its time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    /// Time at which the clock was created
+    start: Instant,
+
+    /// Amount of time the clock has been advanced by
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Create a new manually advanced clock
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::default())),
+        }
+    }
+
+    /// Move the clock forward by the given duration
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    /// Advance the clock instead of waiting
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
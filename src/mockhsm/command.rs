@@ -1,147 +1,558 @@
 //! Commands supported by the `MockHsm`
 
-use super::{object::Payload, state::State, MOCK_SERIAL_NUMBER};
+#[cfg(feature = "untested")]
+use super::ssh;
+use super::{
+    access::Permissions, attestation, fault::Fault, object::Payload, otp_aead, snapshot,
+    state::State, Error,
+};
+#[cfg(feature = "untested")]
+use crate::ssh::commands::*;
 use crate::{
     algorithm::*,
     asymmetric::{self, commands::*, PublicKey},
+    attestation::{commands::*, Certificate},
     audit::{commands::*, AuditCommand, AuditOption, AuditTag},
     authentication::{self, commands::*},
     command::{Code, Message},
     connector,
-    device::{self, commands::*, SerialNumber, StorageInfo},
-    ecdh,
+    device::{self, commands::*},
+    ecdh::{self, commands::*},
     ecdsa::{self, commands::*},
     ed25519::commands::*,
     hmac::{self, commands::*},
     object::{self, commands::*},
     opaque::{self, commands::*},
-    otp,
+    otp::{self, commands::*},
     response::{self, Response},
-    rsa,
-    serialization::deserialize,
-    session::{self, commands::*},
-    template,
+    rsa::{self, oaep::commands::*, pkcs1::commands::*, pss::commands::*},
+    serialization::{self, deserialize, deserialize_exact},
+    session::{
+        self,
+        commands::*,
+        securechannel::{asymmetric::EPHEMERAL_PUBLIC_KEY_SIZE, Challenge},
+    },
+    symmetric::{self, commands::*},
+    template::{self, commands::*},
     wrap::{self, commands::*},
     Capability,
 };
 use ::ecdsa::{
-    elliptic_curve::{bigint::U256, generic_array::GenericArray, ops::Reduce, Field},
+    elliptic_curve::{
+        self,
+        ecdh::diffie_hellman,
+        generic_array::{ArrayLength, GenericArray},
+        ops::Invert,
+        sec1::{self, FromEncodedPoint, ToEncodedPoint},
+        subtle::CtOption,
+        AffinePoint, CurveArithmetic, Field, FieldBytes, FieldBytesSize, PrimeCurve, Scalar,
+        SecretKey,
+    },
     hazmat::SignPrimitive,
+    SignatureSize,
 };
 use ::hmac::{Hmac, Mac};
+use ::rsa::{
+    hazmat::rsa_decrypt_and_check, traits::PublicKeyParts, BigUint, Pkcs1v15Sign, Pss,
+    RsaPrivateKey,
+};
+use aes::cipher::{
+    block_padding::NoPadding, consts::U16, BlockCipher, BlockDecrypt, BlockDecryptMut,
+    BlockEncrypt, BlockEncryptMut, InnerIvInit,
+};
 use rand_core::{OsRng, RngCore};
-use sha2::Sha256;
+use serde::de::DeserializeOwned;
+use sha2::{Sha256, Sha384, Sha512};
 use signature::Signer;
-use std::{io::Cursor, str::FromStr};
-use subtle::ConstantTimeEq;
+use std::{cmp, io::Cursor};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroizing;
+
+/// Parse the data of a command, returning the error response a device sends
+/// for malformed commands from the current function if it fails
+macro_rules! parse_or_reply {
+    ($cmd_data:expr) => {
+        match parse_command($cmd_data) {
+            Ok(command) => command,
+            Err(kind) => return kind.into(),
+        }
+    };
+}
 
 /// Create a new HSM session
 pub(crate) fn create_session(
     state: &mut State,
     cmd_message: &Message,
 ) -> Result<Vec<u8>, connector::Error> {
-    let cmd: CreateSessionCommand = deserialize(cmd_message.data.as_ref())
-        .unwrap_or_else(|e| panic!("error parsing CreateSession command data: {e:?}"));
+    // Asymmetric session requests carry an ephemeral public key instead of
+    // a host challenge
+    if cmd_message.data.len() == 2 + EPHEMERAL_PUBLIC_KEY_SIZE {
+        return create_asymmetric_session(state, cmd_message);
+    }
 
-    let session = state.create_session(cmd.authentication_key_id, cmd.host_challenge);
+    let cmd: CreateSessionCommand = match parse_command(&cmd_message.data) {
+        Ok(cmd) => cmd,
+        Err(kind) => return Ok(response::Message::from(kind).into()),
+    };
+
+    let card_challenge = Challenge::new();
+    let session = match state.create_session(
+        cmd.authentication_key_id,
+        cmd.host_challenge,
+        card_challenge,
+    ) {
+        Ok(session) => session,
+        Err(kind) => {
+            return Ok(session_error(
+                state,
+                cmd_message,
+                cmd.authentication_key_id,
+                kind,
+            ))
+        }
+    };
 
     let mut response = CreateSessionResponse {
-        card_challenge: *session.card_challenge(),
-        card_cryptogram: session.card_cryptogram(),
+        card_challenge,
+        card_cryptogram: session.channel.card_cryptogram(),
+    }
+    .serialize();
+
+    response.session_id = Some(session.id);
+    state.audit(cmd_message, cmd.authentication_key_id, response.code);
+    Ok(response.into())
+}
+
+/// Create a new HSM session authenticated with an asymmetric key
+fn create_asymmetric_session(
+    state: &mut State,
+    cmd_message: &Message,
+) -> Result<Vec<u8>, connector::Error> {
+    let cmd: CreateAsymmetricSessionCommand = match parse_command(&cmd_message.data) {
+        Ok(cmd) => cmd,
+        Err(kind) => return Ok(response::Message::from(kind).into()),
+    };
+
+    let (session, card_public_key, receipt) =
+        match state.create_asymmetric_session(cmd.authentication_key_id, &cmd.host_public_key) {
+            Ok(result) => result,
+            Err(kind) => {
+                return Ok(session_error(
+                    state,
+                    cmd_message,
+                    cmd.authentication_key_id,
+                    kind,
+                ))
+            }
+        };
+
+    let mut response = CreateAsymmetricSessionResponse {
+        card_public_key,
+        receipt,
     }
     .serialize();
 
     response.session_id = Some(session.id);
+    state.audit(cmd_message, cmd.authentication_key_id, response.code);
     Ok(response.into())
 }
 
+/// Respond to a session creation request which failed, recording it in the
+/// audit log
+fn session_error(
+    state: &mut State,
+    cmd_message: &Message,
+    authentication_key_id: object::Id,
+    kind: device::ErrorKind,
+) -> Vec<u8> {
+    let response = response::Message::from(kind);
+    state.audit(cmd_message, authentication_key_id, response.code);
+    response.into()
+}
+
+/// Get the device public key (sent outside of a session)
+pub(crate) fn get_device_public_key(state: &State) -> Result<Vec<u8>, connector::Error> {
+    let public_key = state.device_public_key();
+
+    Ok(GetDevicePublicKeyResponse(PublicKey {
+        algorithm: asymmetric::Algorithm::EcP256,
+        bytes: public_key.to_encoded_point(false).as_bytes()[1..].into(),
+    })
+    .serialize()
+    .into())
+}
+
 /// Authenticate an HSM session
 pub(crate) fn authenticate_session(
     state: &mut State,
     command: &Message,
 ) -> Result<Vec<u8>, connector::Error> {
-    let session_id = command
-        .session_id
-        .unwrap_or_else(|| panic!("no session ID in command: {:?}", command.command_type));
+    let session_id = match command.session_id {
+        Some(session_id) => session_id,
+        None => {
+            debug!("no session ID in command: {:?}", command.command_type);
+            return Ok(response::Message::from(device::ErrorKind::InvalidSession).into());
+        }
+    };
+
+    let session = match state.get_session(session_id) {
+        Ok(session) => session,
+        Err(kind) => return Ok(response::Message::from(kind).into()),
+    };
 
-    Ok(state
-        .get_session(session_id)?
-        .channel
-        .verify_authenticate_session(command)
-        .unwrap()
-        .into())
+    let authentication_key_id = session.authentication_key_id;
+
+    let response = match session.channel.verify_authenticate_session(command) {
+        Ok(response) => response,
+        Err(e) => {
+            // The session's channel is terminated, so it can't be used again
+            debug!("error authenticating session {:?}: {}", session_id, e);
+            state
+                .close_session(session_id)
+                .err()
+                .unwrap_or(device::ErrorKind::AuthenticationFailed)
+                .into()
+        }
+    };
+
+    state.audit(command, authentication_key_id, response.code);
+    Ok(response.into())
 }
 
-/// Encrypted session messages
+/// Encrypted session messages, returning the response along with the fault
+/// to inject into it (if any) once the state lock is released
 pub(crate) fn session_message(
     state: &mut State,
     encrypted_command: Message,
-) -> Result<Vec<u8>, connector::Error> {
-    let session_id = encrypted_command.session_id.unwrap_or_else(|| {
-        panic!(
-            "no session ID in command: {:?}",
-            encrypted_command.command_type
+) -> Result<(Vec<u8>, Option<Fault>), connector::Error> {
+    let session_id = match encrypted_command.session_id {
+        Some(session_id) => session_id,
+        None => {
+            debug!(
+                "no session ID in command: {:?}",
+                encrypted_command.command_type
+            );
+            return Ok((
+                response::Message::from(device::ErrorKind::InvalidSession).into(),
+                None,
+            ));
+        }
+    };
+
+    let session = match state.get_session(session_id) {
+        Ok(session) => session,
+        Err(kind) => return Ok((response::Message::from(kind).into(), None)),
+    };
+
+    let authentication_key_id = session.authentication_key_id;
+    let command = match session.decrypt_command(encrypted_command) {
+        Ok(command) => command,
+        Err(e) => {
+            // The session's channel is terminated, so it can't be used again
+            debug!(
+                "error decrypting command in session {:?}: {}",
+                session_id, e
+            );
+            let kind = state
+                .close_session(session_id)
+                .err()
+                .unwrap_or(device::ErrorKind::AuthenticationFailed);
+
+            return Ok((response::Message::from(kind).into(), None));
+        }
+    };
+    let fault = state.fault_plan.session_command_fault(command.command_type);
+
+    let response = if let Some(Fault::DeviceError(kind)) = fault {
+        debug!("injecting {:?} error into {:?}", kind, command.command_type);
+        kind.into()
+    } else if state.audit_log_full(command.command_type)
+        && !matches!(
+            command.command_type,
+            Code::GetLogEntries | Code::SetLogIndex
         )
-    });
+    {
+        debug!("audit log full; refusing {:?}", command.command_type);
+        device::ErrorKind::LogFull.into()
+    } else {
+        let permissions = Permissions::for_session(&state.objects, authentication_key_id);
 
-    let command = state
-        .get_session(session_id)?
-        .decrypt_command(encrypted_command);
+        match permissions.authorize(&state.objects, command.command_type, &command.data) {
+            Ok(()) => perform_command(state, session_id, &permissions, &command)?,
+            Err(kind) => kind.into(),
+        }
+    };
+
+    state.audit(&command, authentication_key_id, response.code);
+    let succeeded = response.code.is_success();
 
-    let response = match command.command_type {
+    let encrypted_response = match state.get_session(session_id) {
+        Ok(session) => session.encrypt_response(response),
+        Err(kind) => return Ok((response::Message::from(kind).into(), None)),
+    };
+
+    match command.command_type {
+        Code::CloseSession => {
+            if let Err(kind) = state.close_session(session_id) {
+                return Ok((response::Message::from(kind).into(), None));
+            }
+        }
+        Code::ResetDevice if encrypted_response.code.is_success() => state.reset(),
+        _ => (),
+    }
+
+    // The command has already taken effect, so its response is returned even
+    // if the snapshot can't be saved
+    if succeeded && snapshot::is_modified_by(command.command_type) {
+        if let Err(e) = state.persist() {
+            warn!("error saving MockHsm snapshot: {}", e);
+        }
+    }
+
+    Ok((encrypted_response.into(), fault))
+}
+
+/// Perform a command sent within an authenticated session
+fn perform_command(
+    state: &mut State,
+    session_id: session::Id,
+    permissions: &Permissions,
+    command: &Message,
+) -> Result<response::Message, connector::Error> {
+    Ok(match command.command_type {
         Code::BlinkDevice => BlinkDeviceResponse {}.serialize(),
-        Code::CloseSession => return close_session(state, session_id),
+        Code::ChangeAuthenticationKey => {
+            change_authentication_key(state, session_id, &command.data)?
+        }
+        Code::CloseSession => CloseSessionResponse {}.serialize(),
+        Code::CreateOtpAead => create_otp_aead(state, &command.data),
+        Code::DecryptCbc => decrypt_cbc(state, &command.data),
+        Code::DecryptEcb => decrypt_ecb(state, &command.data),
+        Code::DecryptOaep => decrypt_oaep(state, &command.data),
+        Code::DecryptOtp => decrypt_otp(state, &command.data),
+        Code::DecryptPkcs1 => decrypt_pkcs1(state, &command.data),
         Code::DeleteObject => delete_object(state, &command.data),
-        Code::DeviceInfo => device_info(),
+        Code::DeriveEcdh => derive_ecdh(state, &command.data),
+        Code::DeviceInfo => device_info(state),
         Code::Echo => echo(&command.data),
+        Code::EncryptCbc => encrypt_cbc(state, &command.data),
+        Code::EncryptEcb => encrypt_ecb(state, &command.data),
         Code::ExportWrapped => export_wrapped(state, &command.data),
+        Code::ExportWrappedRsa => export_wrapped_rsa(state, &command.data),
         Code::GenerateAsymmetricKey => gen_asymmetric_key(state, &command.data),
         Code::GenerateHmacKey => gen_hmac_key(state, &command.data),
+        Code::GenerateOtpAead => gen_otp_aead_key(state, &command.data),
+        Code::GenerateSymmetricKey => gen_symmetric_key(state, &command.data),
         Code::GenerateWrapKey => gen_wrap_key(state, &command.data),
-        Code::GetLogEntries => get_log_entries(),
+        Code::GetLogEntries => get_log_entries(state),
         Code::GetObjectInfo => get_object_info(state, &command.data),
         Code::GetOpaqueObject => get_opaque(state, &command.data),
         Code::GetOption => get_option(state, &command.data),
         Code::GetPseudoRandom => get_pseudo_random(state, &command.data),
         Code::GetPublicKey => get_public_key(state, &command.data),
         Code::SignHmac => sign_hmac(state, &command.data),
-        Code::ImportWrapped => import_wrapped(state, &command.data),
-        Code::ListObjects => list_objects(state, &command.data),
+        Code::ImportWrapped => import_wrapped(state, permissions, &command.data),
+        Code::ImportWrappedRsa => import_wrapped_rsa(state, permissions, &command.data),
+        Code::ListObjects => list_objects(state, permissions, &command.data),
         Code::PutAsymmetricKey => put_asymmetric_key(state, &command.data),
         Code::PutAuthenticationKey => put_authentication_key(state, &command.data),
         Code::PutHmacKey => put_hmac_key(state, &command.data),
         Code::PutOpaqueObject => put_opaque(state, &command.data),
+        Code::PutOtpAead => put_otp_aead_key(state, &command.data),
+        Code::PutPublicWrapKey => put_public_wrap_key(state, &command.data),
         Code::SetOption => put_option(state, &command.data),
+        Code::PutSymmetricKey => put_symmetric_key(state, &command.data),
+        Code::PutTemplate => put_template(state, &command.data),
         Code::PutWrapKey => put_wrap_key(state, &command.data),
-        Code::ResetDevice => return Ok(reset_device(state, session_id)),
-        Code::SetLogIndex => SetLogIndexResponse {}.serialize(),
+        Code::RandomizeOtpAead => randomize_otp_aead(state, &command.data),
+        Code::ResetDevice => ResetDeviceResponse(0x01).serialize(),
+        Code::RewrapOtpAead => rewrap_otp_aead(state, &command.data),
+        Code::SetLogIndex => set_log_index(state, &command.data),
+        Code::SignAttestationCertificate => sign_attestation_certificate(state, &command.data),
         Code::SignEcdsa => sign_ecdsa(state, &command.data),
         Code::SignEddsa => sign_eddsa(state, &command.data),
-        Code::GetStorageInfo => get_storage_info(),
+        Code::SignPkcs1 => sign_pkcs1(state, &command.data),
+        Code::SignPss => sign_pss(state, &command.data),
+        #[cfg(feature = "untested")]
+        Code::SignSshCertificate => sign_ssh_certificate(state, &command.data),
+        Code::GetStorageInfo => get_storage_info(state),
+        Code::GetTemplate => get_template(state, &command.data),
+        Code::UnwrapData => unwrap_data(state, &command.data),
         Code::VerifyHmac => verify_hmac(state, &command.data),
-        unsupported => panic!("unsupported command type: {unsupported:?}"),
+        Code::WrapData => wrap_data(state, &command.data),
+        unsupported => {
+            debug!("unsupported command type: {:?}", unsupported);
+            device::ErrorKind::InvalidCommand.into()
+        }
+    })
+}
+
+/// Change the authentication key used to establish the current session
+fn change_authentication_key(
+    state: &mut State,
+    session_id: session::Id,
+    cmd_data: &[u8],
+) -> Result<response::Message, connector::Error> {
+    let command: ChangeAuthenticationKeyCommand = match parse_command(cmd_data) {
+        Ok(command) => command,
+        Err(kind) => return Ok(kind.into()),
+    };
+
+    let session_key_id = match state.get_session(session_id) {
+        Ok(session) => session.authentication_key_id,
+        Err(kind) => return Ok(kind.into()),
     };
 
-    Ok(state
-        .get_session(session_id)?
-        .encrypt_response(response)
-        .into())
+    if command.key_id != session_key_id {
+        debug!(
+            "can only change the session's own authentication key: {:?} (session key: {:?})",
+            command.key_id, session_key_id
+        );
+        return Ok(device::ErrorKind::InvalidId.into());
+    }
+
+    let obj = match state
+        .objects
+        .get_mut(command.key_id, object::Type::AuthenticationKey)
+    {
+        Some(obj) => obj,
+        None => {
+            debug!("session authentication key missing: {:?}", command.key_id);
+            return Ok(device::ErrorKind::ObjectNotFound.into());
+        }
+    };
+
+    if obj.payload.authentication_key().is_none() {
+        debug!("can't replace an asymmetric authentication key with a symmetric one");
+        return Ok(device::ErrorKind::InvalidData.into());
+    }
+
+    if !obj
+        .object_info
+        .capabilities
+        .contains(Capability::CHANGE_AUTHENTICATION_KEY)
+    {
+        debug!("authentication key lacks CHANGE_AUTHENTICATION_KEY capability");
+        return Ok(device::ErrorKind::InsufficientPermissions.into());
+    }
+
+    obj.payload = Payload::AuthenticationKey(command.authentication_key);
+
+    Ok(ChangeAuthenticationKeyResponse {
+        key_id: command.key_id,
+    }
+    .serialize())
 }
 
-/// Close an active session
-fn close_session(state: &mut State, session_id: session::Id) -> Result<Vec<u8>, connector::Error> {
-    let response = state
-        .get_session(session_id)?
-        .encrypt_response(CloseSessionResponse {}.serialize());
+/// Create a Yubico OTP AEAD from a YubiKey's OTP key and private ID
+fn create_otp_aead(state: &State, cmd_data: &[u8]) -> response::Message {
+    let CreateOtpAeadCommand {
+        key_id,
+        key,
+        private_id,
+    } = parse_or_reply!(cmd_data);
 
-    state.close_session(session_id);
-    Ok(response.into())
+    match get_otp_aead_key(state, key_id)
+        .and_then(|otp_aead_key| otp_aead::create_aead(otp_aead_key, &key, &private_id))
+    {
+        Ok(aead) => CreateOtpAeadResponse(aead).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Decrypt data using a symmetric key in AES-CBC mode
+fn decrypt_cbc(state: &State, cmd_data: &[u8]) -> response::Message {
+    let DecryptCbcCommand { key_id, iv, data } = parse_or_reply!(cmd_data);
+
+    match symmetric_cipher(state, key_id, CipherOp::DecryptCbc(iv), data) {
+        Ok(plaintext) => DecryptCbcResponse(plaintext).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Decrypt data using a symmetric key in AES-ECB mode
+fn decrypt_ecb(state: &State, cmd_data: &[u8]) -> response::Message {
+    let DecryptEcbCommand { key_id, data } = parse_or_reply!(cmd_data);
+
+    match symmetric_cipher(state, key_id, CipherOp::DecryptEcb, data) {
+        Ok(plaintext) => DecryptEcbResponse(plaintext).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Decrypt data using RSA-OAEP
+fn decrypt_oaep(state: &State, cmd_data: &[u8]) -> response::Message {
+    // The ciphertext is followed by the label hash, so the boundary between
+    // them depends on the key size and the command must be parsed by hand
+    if cmd_data.len() < 3 {
+        debug!("DecryptOaep command too short: {}", cmd_data.len());
+        return device::ErrorKind::WrongLength.into();
+    }
+
+    let key_id = object::Id::from_be_bytes([cmd_data[0], cmd_data[1]]);
+
+    let mgf1_algorithm = match rsa::mgf::Algorithm::from_u8(cmd_data[2]) {
+        Ok(alg) => alg,
+        Err(e) => {
+            debug!("{}", e);
+            return device::ErrorKind::InvalidData.into();
+        }
+    };
+
+    let private_key = match get_rsa_key(state, key_id) {
+        Ok(key) => key,
+        Err(kind) => return kind.into(),
+    };
+
+    if cmd_data.len() < 3 + private_key.size() {
+        debug!("ciphertext shorter than RSA modulus");
+        return device::ErrorKind::WrongLength.into();
+    }
+
+    let (ciphertext, label_hash) = cmd_data[3..].split_at(private_key.size());
+
+    match rsa_oaep_decrypt(private_key, mgf1_algorithm, ciphertext, label_hash) {
+        Some(plaintext) => DecryptOaepResponse(rsa::oaep::DecryptedData(plaintext)).serialize(),
+        None => {
+            debug!("RSA-OAEP decryption failed");
+            device::ErrorKind::InvalidData.into()
+        }
+    }
+}
+
+/// Decrypt a Yubico OTP using an AEAD and the OTP AEAD key it was created with
+fn decrypt_otp(state: &State, cmd_data: &[u8]) -> response::Message {
+    let DecryptOtpCommand { key_id, aead, otp } = parse_or_reply!(cmd_data);
+
+    match get_otp_aead_key(state, key_id)
+        .and_then(|otp_aead_key| otp_aead::decrypt_otp(otp_aead_key, &aead, &otp))
+    {
+        Ok(decrypted_otp) => DecryptOtpResponse::from(decrypted_otp).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Decrypt data using RSA with PKCS#1v1.5 padding
+fn decrypt_pkcs1(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: DecryptPkcs1Command = parse_or_reply!(cmd_data);
+
+    let private_key = match get_rsa_key(state, command.key_id) {
+        Ok(key) => key,
+        Err(kind) => return kind.into(),
+    };
+
+    match private_key.decrypt(::rsa::Pkcs1v15Encrypt, &command.data) {
+        Ok(plaintext) => DecryptPkcs1Response(rsa::pkcs1::DecryptedData(plaintext)).serialize(),
+        Err(e) => {
+            debug!("RSA PKCS#1v1.5 decryption failed: {}", e);
+            device::ErrorKind::InvalidData.into()
+        }
+    }
 }
 
 /// Delete an object
 fn delete_object(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let command: DeleteObjectCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::DeleteObject: {e:?}"));
+    let command: DeleteObjectCommand = parse_or_reply!(cmd_data);
 
     if state
         .objects
@@ -155,13 +566,50 @@ fn delete_object(state: &mut State, cmd_data: &[u8]) -> response::Message {
     }
 }
 
+/// Derive a shared secret using Elliptic Curve Diffie-Hellman
+fn derive_ecdh(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: DeriveEcdhCommand = parse_or_reply!(cmd_data);
+
+    if let Some(obj) = state
+        .objects
+        .get(command.key_id, object::Type::AsymmetricKey)
+    {
+        let public_key = command.public_key.as_slice();
+
+        let shared_secret = match &obj.payload {
+            Payload::EcdsaNistP256(secret_key) => ecdh_shared_secret(secret_key, public_key),
+            Payload::EcdsaNistP384(secret_key) => ecdh_shared_secret(secret_key, public_key),
+            #[cfg(feature = "nistp521")]
+            Payload::EcdsaNistP521(secret_key) => ecdh_shared_secret(secret_key, public_key),
+            Payload::EcdsaSecp256k1(secret_key) => ecdh_shared_secret(secret_key, public_key),
+            _ => {
+                debug!("not an ECDH key: {:?}", obj.algorithm());
+                return device::ErrorKind::InvalidCommand.into();
+            }
+        };
+
+        match shared_secret {
+            Some(shared_secret) => DeriveEcdhResponse(shared_secret).serialize(),
+            None => {
+                debug!("invalid ECDH public key for {:?}", obj.algorithm());
+                device::ErrorKind::InvalidData.into()
+            }
+        }
+    } else {
+        debug!("no such object ID: {:?}", command.key_id);
+        device::ErrorKind::ObjectNotFound.into()
+    }
+}
+
 /// Generate a mock device information report
-fn device_info() -> response::Message {
+pub(crate) fn device_info(state: &State) -> response::Message {
+    let (major_version, minor_version, build_version) = state.firmware_version;
+
     let info = device::Info {
-        major_version: 2,
-        minor_version: 0,
-        build_version: 0,
-        serial_number: SerialNumber::from_str(MOCK_SERIAL_NUMBER).unwrap(),
+        major_version,
+        minor_version,
+        build_version,
+        serial_number: state.serial_number,
         log_store_capacity: 62,
         log_store_used: 62,
         algorithms: vec![
@@ -212,6 +660,11 @@ fn device_info() -> response::Message {
             Algorithm::Ecdsa(ecdsa::Algorithm::Sha512),
             Algorithm::Asymmetric(asymmetric::Algorithm::Ed25519),
             Algorithm::Asymmetric(asymmetric::Algorithm::EcP224),
+            Algorithm::Symmetric(symmetric::Algorithm::Aes128),
+            Algorithm::Symmetric(symmetric::Algorithm::Aes192),
+            Algorithm::Symmetric(symmetric::Algorithm::Aes256),
+            Algorithm::BlockMode(symmetric::Mode::Ecb),
+            Algorithm::BlockMode(symmetric::Mode::Cbc),
         ],
     };
 
@@ -219,18 +672,37 @@ fn device_info() -> response::Message {
 }
 
 /// Echo a message back to the host
-fn echo(cmd_data: &[u8]) -> response::Message {
+pub(crate) fn echo(cmd_data: &[u8]) -> response::Message {
     EchoResponse(cmd_data.into()).serialize()
 }
 
+/// Encrypt data using a symmetric key in AES-CBC mode
+fn encrypt_cbc(state: &State, cmd_data: &[u8]) -> response::Message {
+    let EncryptCbcCommand { key_id, iv, data } = parse_or_reply!(cmd_data);
+
+    match symmetric_cipher(state, key_id, CipherOp::EncryptCbc(iv), data) {
+        Ok(ciphertext) => EncryptCbcResponse(ciphertext).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Encrypt data using a symmetric key in AES-ECB mode
+fn encrypt_ecb(state: &State, cmd_data: &[u8]) -> response::Message {
+    let EncryptEcbCommand { key_id, data } = parse_or_reply!(cmd_data);
+
+    match symmetric_cipher(state, key_id, CipherOp::EncryptEcb, data) {
+        Ok(ciphertext) => EncryptEcbResponse(ciphertext).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
 /// Export an object from the HSM in encrypted form
 fn export_wrapped(state: &mut State, cmd_data: &[u8]) -> response::Message {
     let ExportWrappedCommand {
         wrap_key_id,
         object_type,
         object_id,
-    } = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::ExportWrapped: {e:?}"));
+    } = parse_or_reply!(cmd_data);
 
     let nonce = wrap::Nonce::generate();
 
@@ -239,19 +711,48 @@ fn export_wrapped(state: &mut State, cmd_data: &[u8]) -> response::Message {
         .wrap_obj(wrap_key_id, object_id, object_type, &nonce)
     {
         Ok(ciphertext) => ExportWrappedResponse(wrap::Message { nonce, ciphertext }).serialize(),
-        Err(e) => {
-            debug!("error wrapping object: {}", e);
-            device::ErrorKind::InvalidCommand.into()
+        Err(e) => object_error(e),
+    }
+}
+
+/// Export an object from the HSM encrypted under an RSA public wrap key
+fn export_wrapped_rsa(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let ExportWrappedRsaCommand {
+        wrap_key_id,
+        object_type,
+        object_id,
+        aes_algorithm,
+        oaep_algorithm,
+        mgf1_algorithm,
+    } = parse_or_reply!(cmd_data);
+
+    let result = match (oaep_algorithm, mgf1_algorithm) {
+        (rsa::oaep::Algorithm::Sha256, rsa::mgf::Algorithm::Sha256) => state
+            .objects
+            .wrap_obj_rsa::<Sha256>(wrap_key_id, object_id, object_type, aes_algorithm),
+        (rsa::oaep::Algorithm::Sha384, rsa::mgf::Algorithm::Sha384) => state
+            .objects
+            .wrap_obj_rsa::<Sha384>(wrap_key_id, object_id, object_type, aes_algorithm),
+        (rsa::oaep::Algorithm::Sha512, rsa::mgf::Algorithm::Sha512) => state
+            .objects
+            .wrap_obj_rsa::<Sha512>(wrap_key_id, object_id, object_type, aes_algorithm),
+        other => {
+            debug!("unsupported OAEP/MGF1 algorithms: {:?}", other);
+            return device::ErrorKind::InvalidCommand.into();
         }
+    };
+
+    match result {
+        Ok(message) => ExportWrappedRsaResponse(message).serialize(),
+        Err(e) => object_error(e),
     }
 }
 
 /// Generate a new random asymmetric key
 fn gen_asymmetric_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let GenAsymmetricKeyCommand(command) = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::GenAsymmetricKey: {e:?}"));
+    let GenAsymmetricKeyCommand(command) = parse_or_reply!(cmd_data);
 
-    state.objects.generate(
+    match state.objects.generate(
         command.key_id,
         object::Type::AsymmetricKey,
         command.algorithm,
@@ -259,20 +760,20 @@ fn gen_asymmetric_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
         command.capabilities,
         Capability::default(),
         command.domains,
-    );
-
-    GenAsymmetricKeyResponse {
-        key_id: command.key_id,
+    ) {
+        Ok(()) => GenAsymmetricKeyResponse {
+            key_id: command.key_id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
     }
-    .serialize()
 }
 
 /// Generate a new random HMAC key
 fn gen_hmac_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let GenHmacKeyCommand(command) =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::GenHMACKey: {e:?}"));
+    let GenHmacKeyCommand(command) = parse_or_reply!(cmd_data);
 
-    state.objects.generate(
+    match state.objects.generate(
         command.key_id,
         object::Type::HmacKey,
         command.algorithm,
@@ -280,12 +781,63 @@ fn gen_hmac_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
         command.capabilities,
         Capability::default(),
         command.domains,
-    );
+    ) {
+        Ok(()) => GenHmacKeyResponse {
+            key_id: command.key_id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
+    }
+}
 
-    GenHmacKeyResponse {
-        key_id: command.key_id,
+/// Generate a new random OTP AEAD key
+fn gen_otp_aead_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let GenOtpAeadKeyCommand { params, nonce_id } = parse_or_reply!(cmd_data);
+
+    let algorithm = match params.algorithm.otp() {
+        Some(alg) => alg,
+        None => {
+            debug!("not an OTP AEAD key algorithm: {:?}", params.algorithm);
+            return device::ErrorKind::InvalidData.into();
+        }
+    };
+
+    match state.objects.insert_generated(
+        params.key_id,
+        object::Type::OtpAeadKey,
+        params.label,
+        params.capabilities,
+        Capability::default(),
+        params.domains,
+        Payload::generate_otp_aead_key(algorithm, nonce_id),
+    ) {
+        Ok(()) => GenOtpAeadKeyResponse {
+            key_id: params.key_id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Generate a new random symmetric (AES) key
+fn gen_symmetric_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let GenSymmetricKeyCommand(command) = parse_or_reply!(cmd_data);
+
+    match state.objects.generate(
+        command.key_id,
+        object::Type::SymmetricKey,
+        command.algorithm,
+        command.label,
+        command.capabilities,
+        Capability::default(),
+        command.domains,
+    ) {
+        Ok(()) => GenSymmetricKeyResponse {
+            key_id: command.key_id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
     }
-    .serialize()
 }
 
 /// Generate a new random wrap (i.e. AES-CCM) key
@@ -293,9 +845,9 @@ fn gen_wrap_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
     let GenWrapKeyCommand {
         params,
         delegated_capabilities,
-    } = deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::GenWrapKey: {e:?}"));
+    } = parse_or_reply!(cmd_data);
 
-    state.objects.generate(
+    match state.objects.generate(
         params.key_id,
         object::Type::WrapKey,
         params.algorithm,
@@ -303,30 +855,23 @@ fn gen_wrap_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
         params.capabilities,
         delegated_capabilities,
         params.domains,
-    );
-
-    GenWrapKeyResponse {
-        key_id: params.key_id,
+    ) {
+        Ok(()) => GenWrapKeyResponse {
+            key_id: params.key_id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
     }
-    .serialize()
 }
 
-/// Get mock log information
-fn get_log_entries() -> response::Message {
-    // TODO: mimic the YubiHSM's actual audit log
-    LogEntries {
-        unlogged_boot_events: 0,
-        unlogged_auth_events: 0,
-        num_entries: 0,
-        entries: vec![],
-    }
-    .serialize()
+/// Get entries from the audit log
+fn get_log_entries(state: &State) -> response::Message {
+    state.audit_log.entries().serialize()
 }
 
 /// Get detailed info about a specific object
 fn get_object_info(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: GetObjectInfoCommand = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::GetObjectInfo: {e:?}"));
+    let command: GetObjectInfoCommand = parse_or_reply!(cmd_data);
 
     if let Some(obj) = state
         .objects
@@ -341,10 +886,11 @@ fn get_object_info(state: &State, cmd_data: &[u8]) -> response::Message {
 
 /// Get an opaque object (X.509 certificate or other data) stored in the HSM
 fn get_opaque(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: GetOpaqueCommand = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::GetOpaqueObject: {e:?}"));
+    let command: GetOpaqueCommand = parse_or_reply!(cmd_data);
 
-    if let Some(obj) = state.objects.get(command.object_id, object::Type::Opaque) {
+    if command.object_id == 0 {
+        GetOpaqueResponse(state.attestation_certificate.clone()).serialize()
+    } else if let Some(obj) = state.objects.get(command.object_id, object::Type::Opaque) {
         GetOpaqueResponse(obj.payload.to_bytes()).serialize()
     } else {
         debug!("no such opaque object ID: {:?}", command.object_id);
@@ -354,8 +900,7 @@ fn get_opaque(state: &State, cmd_data: &[u8]) -> response::Message {
 
 /// Get an auditing option
 fn get_option(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: GetOptionCommand = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::GetOpaqueObject: {e:?}"));
+    let command: GetOptionCommand = parse_or_reply!(cmd_data);
 
     let results = match command.tag {
         AuditTag::Command => state.command_audit_options.serialize(),
@@ -368,8 +913,7 @@ fn get_option(state: &State, cmd_data: &[u8]) -> response::Message {
 
 /// Get bytes of random data
 fn get_pseudo_random(_state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: GetPseudoRandomCommand = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::GetPseudoRandom: {e:?}"));
+    let command: GetPseudoRandomCommand = parse_or_reply!(cmd_data);
 
     let mut bytes = vec![0u8; command.bytes as usize];
     OsRng.fill_bytes(&mut bytes);
@@ -379,76 +923,135 @@ fn get_pseudo_random(_state: &State, cmd_data: &[u8]) -> response::Message {
 
 /// Get the public key associated with a key in the HSM
 fn get_public_key(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: GetPublicKeyCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::GetPubKey: {e:?}"));
+    let command: GetPublicKeyCommand = parse_or_reply!(cmd_data);
 
-    if let Some(obj) = state
+    let obj = match state
         .objects
         .get(command.key_id, object::Type::AsymmetricKey)
     {
-        GetPublicKeyResponse(PublicKey {
-            algorithm: obj.algorithm().asymmetric().unwrap(),
-            bytes: obj.payload.public_key_bytes().unwrap(),
-        })
-        .serialize()
-    } else {
-        debug!("no such object ID: {:?}", command.key_id);
-        device::ErrorKind::ObjectNotFound.into()
+        Some(obj) => obj,
+        None => {
+            debug!("no such object ID: {:?}", command.key_id);
+            return device::ErrorKind::ObjectNotFound.into();
+        }
+    };
+
+    match (obj.algorithm().asymmetric(), obj.payload.public_key_bytes()) {
+        (Some(algorithm), Some(bytes)) => {
+            GetPublicKeyResponse(PublicKey { algorithm, bytes }).serialize()
+        }
+        _ => {
+            debug!("no public key for {:?}", obj.algorithm());
+            device::ErrorKind::InvalidData.into()
+        }
     }
 }
 
 /// Generate a mock storage status report
-fn get_storage_info() -> response::Message {
-    // TODO: model actual free storage
-    let info = StorageInfo {
-        total_records: 256,
-        free_records: 256,
-        total_pages: 1024,
-        free_pages: 1024,
-        page_size: 126,
-    };
+fn get_storage_info(state: &State) -> response::Message {
+    GetStorageInfoResponse(state.objects.storage_info()).serialize()
+}
+
+/// Get a certificate template (i.e. for SSH CA) stored in the HSM
+fn get_template(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: GetTemplateCommand = parse_or_reply!(cmd_data);
 
-    GetStorageInfoResponse(info).serialize()
+    if let Some(obj) = state.objects.get(command.object_id, object::Type::Template) {
+        GetTemplateResponse(obj.payload.to_bytes()).serialize()
+    } else {
+        debug!("no such template object ID: {:?}", command.object_id);
+        device::ErrorKind::ObjectNotFound.into()
+    }
 }
 
 /// Import an object encrypted under a wrap key into the HSM
-fn import_wrapped(state: &mut State, cmd_data: &[u8]) -> response::Message {
+fn import_wrapped(
+    state: &mut State,
+    permissions: &Permissions,
+    cmd_data: &[u8],
+) -> response::Message {
     let ImportWrappedCommand {
         wrap_key_id,
         nonce,
         ciphertext,
-    } = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::ImportWrapped: {e:?}"));
+    } = parse_or_reply!(cmd_data);
 
-    match state.objects.unwrap_obj(wrap_key_id, &nonce, ciphertext) {
+    match state
+        .objects
+        .unwrap_obj(wrap_key_id, permissions.domains, &nonce, ciphertext)
+    {
         Ok(obj) => ImportWrappedResponse {
             object_type: obj.object_type,
             object_id: obj.object_id,
         }
         .serialize(),
-        Err(e) => {
-            debug!("error unwrapping object: {}", e);
-            device::ErrorKind::InvalidCommand.into()
+        Err(e) => object_error(e),
+    }
+}
+
+/// Import an object encrypted under an RSA public wrap key
+fn import_wrapped_rsa(
+    state: &mut State,
+    permissions: &Permissions,
+    cmd_data: &[u8],
+) -> response::Message {
+    let ImportWrappedRsaCommand {
+        unwrap_key_id,
+        oaep_algorithm,
+        mgf1_algorithm,
+        ciphertext,
+    } = parse_or_reply!(cmd_data);
+
+    let message = wrap::RsaMessage::from_vec(ciphertext);
+
+    let result = match (oaep_algorithm, mgf1_algorithm) {
+        (rsa::oaep::Algorithm::Sha256, rsa::mgf::Algorithm::Sha256) => state
+            .objects
+            .unwrap_obj_rsa::<Sha256>(unwrap_key_id, permissions.domains, &message),
+        (rsa::oaep::Algorithm::Sha384, rsa::mgf::Algorithm::Sha384) => state
+            .objects
+            .unwrap_obj_rsa::<Sha384>(unwrap_key_id, permissions.domains, &message),
+        (rsa::oaep::Algorithm::Sha512, rsa::mgf::Algorithm::Sha512) => state
+            .objects
+            .unwrap_obj_rsa::<Sha512>(unwrap_key_id, permissions.domains, &message),
+        other => {
+            debug!("unsupported OAEP/MGF1 algorithms: {:?}", other);
+            return device::ErrorKind::InvalidCommand.into();
         }
+    };
+
+    match result {
+        Ok(obj) => ImportWrappedRsaResponse {
+            object_type: obj.object_type,
+            object_id: obj.object_id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
     }
 }
 
 /// List all objects presently accessible to a session
-fn list_objects(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: ListObjectsCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::ListObjects: {e:?}"));
+fn list_objects(state: &State, permissions: &Permissions, cmd_data: &[u8]) -> response::Message {
+    let command: ListObjectsCommand = parse_or_reply!(cmd_data);
 
     let len = command.0.len() as u64;
     let mut cursor = Cursor::new(command.0);
     let mut filters = vec![];
 
     while cursor.position() < len {
-        filters.push(object::Filter::deserialize(&mut cursor).unwrap());
+        match object::Filter::deserialize(&mut cursor) {
+            Ok(filter) => filters.push(filter),
+            Err(e) => {
+                debug!("error parsing object filter: {}", e);
+                return device::ErrorKind::InvalidData.into();
+            }
+        }
     }
 
     let list_entries = state
         .objects
         .iter()
+        .filter(|(_, object)| permissions.can_access(object.info()))
         .filter(|(_, object)| {
             if filters.is_empty() {
                 true
@@ -473,10 +1076,9 @@ fn list_objects(state: &State, cmd_data: &[u8]) -> response::Message {
 
 /// Put an existing asymmetric key into the HSM
 fn put_asymmetric_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let PutAsymmetricKeyCommand { params, data } = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::PutAsymmetricKey: {e:?}"));
+    let PutAsymmetricKeyCommand { params, data } = parse_or_reply!(cmd_data);
 
-    state.objects.put(
+    match state.objects.put(
         params.id,
         object::Type::AsymmetricKey,
         params.algorithm,
@@ -485,21 +1087,30 @@ fn put_asymmetric_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
         Capability::default(),
         params.domains,
         &data,
-    );
-
-    PutAsymmetricKeyResponse { key_id: params.id }.serialize()
+    ) {
+        Ok(()) => PutAsymmetricKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
 }
 
 /// Put a new authentication key into the HSM
 fn put_authentication_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let params: object::put::Params = match parse_command_prefix(cmd_data) {
+        Ok(params) => params,
+        Err(kind) => return kind.into(),
+    };
+
+    if params.algorithm == Algorithm::Authentication(authentication::Algorithm::EcP256) {
+        return put_asymmetric_authentication_key(state, cmd_data);
+    }
+
     let PutAuthenticationKeyCommand {
         params,
         delegated_capabilities,
         authentication_key,
-    } = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::PutAuthenticationKey: {e:?}"));
+    } = parse_or_reply!(cmd_data);
 
-    state.objects.put(
+    match state.objects.put(
         params.id,
         object::Type::AuthenticationKey,
         params.algorithm,
@@ -508,17 +1119,40 @@ fn put_authentication_key(state: &mut State, cmd_data: &[u8]) -> response::Messa
         delegated_capabilities,
         params.domains,
         &authentication_key.0,
-    );
+    ) {
+        Ok(()) => PutAuthenticationKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Put a new asymmetric (EC P-256) authentication key into the HSM
+fn put_asymmetric_authentication_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let PutAsymmetricAuthenticationKeyCommand {
+        params,
+        delegated_capabilities,
+        public_key,
+    } = parse_or_reply!(cmd_data);
 
-    PutAuthenticationKeyResponse { key_id: params.id }.serialize()
+    match state.objects.put(
+        params.id,
+        object::Type::AuthenticationKey,
+        params.algorithm,
+        params.label,
+        params.capabilities,
+        delegated_capabilities,
+        params.domains,
+        &public_key,
+    ) {
+        Ok(()) => PutAuthenticationKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
 }
 
 /// Put a new HMAC key into the HSM
 fn put_hmac_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let PutHmacKeyCommand { params, hmac_key } =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::PutHMACKey: {e:?}"));
+    let PutHmacKeyCommand { params, hmac_key } = parse_or_reply!(cmd_data);
 
-    state.objects.put(
+    match state.objects.put(
         params.id,
         object::Type::HmacKey,
         params.algorithm,
@@ -527,17 +1161,17 @@ fn put_hmac_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
         Capability::default(),
         params.domains,
         &hmac_key,
-    );
-
-    PutHmacKeyResponse { key_id: params.id }.serialize()
+    ) {
+        Ok(()) => PutHmacKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
 }
 
 /// Put an opaque object (X.509 cert or other data) into the HSM
 fn put_opaque(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let PutOpaqueCommand { params, data } = deserialize(cmd_data)
-        .unwrap_or_else(|e| panic!("error parsing Code::PutOpaqueObject: {e:?}"));
+    let PutOpaqueCommand { params, data } = parse_or_reply!(cmd_data);
 
-    state.objects.put(
+    match state.objects.put(
         params.id,
         object::Type::Opaque,
         params.algorithm,
@@ -546,51 +1180,173 @@ fn put_opaque(state: &mut State, cmd_data: &[u8]) -> response::Message {
         Capability::default(),
         params.domains,
         &data,
-    );
-
-    PutOpaqueResponse {
-        object_id: params.id,
+    ) {
+        Ok(()) => PutOpaqueResponse {
+            object_id: params.id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
     }
-    .serialize()
 }
 
 /// Change an HSM auditing setting
 fn put_option(state: &mut State, cmd_data: &[u8]) -> response::Message {
-    let SetOptionCommand { tag, length, value } =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::PutOption: {e:?}"));
+    let SetOptionCommand { tag, length, value } = parse_or_reply!(cmd_data);
+
+    if usize::from(length) != value.len() {
+        debug!(
+            "option length mismatch: {} (value: {})",
+            length,
+            value.len()
+        );
+        return device::ErrorKind::WrongLength.into();
+    }
 
     match tag {
-        AuditTag::Force => {
-            assert_eq!(length, 1);
-            state.force_audit = AuditOption::from_u8(value[0]).unwrap()
-        }
+        AuditTag::Force => match parse_audit_option(&value) {
+            Ok(option) => state.force_audit = option,
+            Err(kind) => return kind.into(),
+        },
         AuditTag::Command => {
-            assert_eq!(length, 2);
-            let audit_cmd: AuditCommand =
-                deserialize(&value).unwrap_or_else(|e| panic!("error parsing AuditCommand: {e:?}"));
+            let audit_cmd: AuditCommand = parse_or_reply!(&value);
 
             state
                 .command_audit_options
                 .put(audit_cmd.command_type(), audit_cmd.audit_option());
         }
-        AuditTag::Fips => {
-            assert_eq!(length, 1);
-            state.fips = AuditOption::from_u8(value[0]).unwrap()
-        }
+        AuditTag::Fips => match parse_audit_option(&value) {
+            Ok(option) => state.fips = option,
+            Err(kind) => return kind.into(),
+        },
     }
 
     PutOptionResponse {}.serialize()
 }
 
+/// Parse the value of a force audit or FIPS mode option
+fn parse_audit_option(value: &[u8]) -> Result<AuditOption, device::ErrorKind> {
+    if value.len() != 1 {
+        debug!("expected 1-byte audit option (got {})", value.len());
+        return Err(device::ErrorKind::WrongLength);
+    }
+
+    AuditOption::from_u8(value[0]).map_err(|e| {
+        debug!("{}", e);
+        device::ErrorKind::InvalidData
+    })
+}
+
+/// Put an existing OTP AEAD key into the HSM
+fn put_otp_aead_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let PutOtpAeadKeyCommand {
+        params,
+        nonce_id,
+        data,
+    } = parse_or_reply!(cmd_data);
+
+    match params.algorithm.otp() {
+        Some(alg) if data.len() == alg.key_len() => (),
+        _ => {
+            debug!(
+                "invalid OTP AEAD key: {:?} ({} bytes)",
+                params.algorithm,
+                data.len()
+            );
+            return device::ErrorKind::InvalidData.into();
+        }
+    }
+
+    match state.objects.put(
+        params.id,
+        object::Type::OtpAeadKey,
+        params.algorithm,
+        params.label,
+        params.capabilities,
+        Capability::default(),
+        params.domains,
+        &[nonce_id.as_ref(), &data].concat(),
+    ) {
+        Ok(()) => PutOtpAeadKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Put a new RSA public wrap key into the HSM
+fn put_public_wrap_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let PutPublicWrapKeyCommand {
+        params,
+        delegated_capabilities,
+        public_key,
+    } = parse_or_reply!(cmd_data);
+
+    match state.objects.put(
+        params.id,
+        object::Type::PublicWrapKey,
+        params.algorithm,
+        params.label,
+        params.capabilities,
+        delegated_capabilities,
+        params.domains,
+        &public_key,
+    ) {
+        Ok(()) => PutPublicWrapKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Put an existing symmetric (AES) key into the HSM
+fn put_symmetric_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let PutSymmetricKeyCommand {
+        params,
+        symmetric_key,
+    } = parse_or_reply!(cmd_data);
+
+    match state.objects.put(
+        params.id,
+        object::Type::SymmetricKey,
+        params.algorithm,
+        params.label,
+        params.capabilities,
+        Capability::default(),
+        params.domains,
+        &symmetric_key,
+    ) {
+        Ok(()) => PutSymmetricKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Put a certificate template (i.e. for SSH CA) into the HSM
+fn put_template(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let PutTemplateCommand { params, data } = parse_or_reply!(cmd_data);
+
+    match state.objects.put(
+        params.id,
+        object::Type::Template,
+        params.algorithm,
+        params.label,
+        params.capabilities,
+        Capability::default(),
+        params.domains,
+        &data,
+    ) {
+        Ok(()) => PutTemplateResponse {
+            object_id: params.id,
+        }
+        .serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
 /// Put an existing wrap (i.e. AES-CCM) key into the HSM
 fn put_wrap_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
     let PutWrapKeyCommand {
         params,
         delegated_capabilities,
         data,
-    } = deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::PutWrapKey: {e:?}"));
+    } = parse_or_reply!(cmd_data);
 
-    state.objects.put(
+    match state.objects.put(
         params.id,
         object::Type::WrapKey,
         params.algorithm,
@@ -599,74 +1355,257 @@ fn put_wrap_key(state: &mut State, cmd_data: &[u8]) -> response::Message {
         delegated_capabilities,
         params.domains,
         &data,
-    );
+    ) {
+        Ok(()) => PutWrapKeyResponse { key_id: params.id }.serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Create a Yubico OTP AEAD from a random OTP key and private ID
+fn randomize_otp_aead(state: &State, cmd_data: &[u8]) -> response::Message {
+    let RandomizeOtpAeadCommand { key_id } = parse_or_reply!(cmd_data);
+
+    let mut otp_key = [0u8; otp::KEY_SIZE];
+    let mut private_id = [0u8; otp::private_id::SIZE];
+    OsRng.fill_bytes(&mut otp_key);
+    OsRng.fill_bytes(&mut private_id);
 
-    PutWrapKeyResponse { key_id: params.id }.serialize()
+    match get_otp_aead_key(state, key_id)
+        .and_then(|otp_aead_key| otp_aead::create_aead(otp_aead_key, &otp_key, &private_id.into()))
+    {
+        Ok(aead) => RandomizeOtpAeadResponse(aead).serialize(),
+        Err(kind) => kind.into(),
+    }
 }
 
-/// Reset the MockHsm back to its default state
-fn reset_device(state: &mut State, session_id: session::Id) -> Vec<u8> {
-    let response = state
-        .get_session(session_id)
-        .unwrap()
-        .encrypt_response(ResetDeviceResponse(0x01).serialize())
-        .into();
+/// Re-encrypt a Yubico OTP AEAD from one OTP AEAD key to another
+fn rewrap_otp_aead(state: &State, cmd_data: &[u8]) -> response::Message {
+    let RewrapOtpAeadCommand {
+        from_key_id,
+        to_key_id,
+        aead,
+    } = parse_or_reply!(cmd_data);
+
+    let result = get_otp_aead_key(state, from_key_id)
+        .and_then(|from_key| otp_aead::decrypt_aead(from_key, &aead))
+        .and_then(|(otp_key, private_id)| {
+            let to_key = get_otp_aead_key(state, to_key_id)?;
+            otp_aead::create_aead(to_key, &otp_key, &private_id)
+        });
+
+    match result {
+        Ok(aead) => RewrapOtpAeadResponse(aead).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
 
-    state.reset();
-    response
+/// Mark entries in the audit log as consumed
+fn set_log_index(state: &mut State, cmd_data: &[u8]) -> response::Message {
+    let command: SetLogIndexCommand = parse_or_reply!(cmd_data);
+
+    match state.audit_log.set_index(command.log_index) {
+        Ok(()) => SetLogIndexResponse {}.serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Sign an X.509 certificate attesting to an asymmetric key
+fn sign_attestation_certificate(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: SignAttestationCertificateCommand = parse_or_reply!(cmd_data);
+
+    let subject = match state
+        .objects
+        .get(command.key_id, object::Type::AsymmetricKey)
+    {
+        Some(obj) => obj,
+        None => {
+            debug!("no such object ID: {:?}", command.key_id);
+            return device::ErrorKind::ObjectNotFound.into();
+        }
+    };
+
+    // Attestation keys other than the device's must have an X.509
+    // certificate stored as an opaque object with the same ID
+    let (attestation_key, attestation_certificate) = if command.attestation_key_id == 0 {
+        (
+            &state.attestation_key,
+            state.attestation_certificate.clone(),
+        )
+    } else {
+        let key = state
+            .objects
+            .get(command.attestation_key_id, object::Type::AsymmetricKey);
+
+        let certificate = state
+            .objects
+            .get(command.attestation_key_id, object::Type::Opaque);
+
+        match (key, certificate) {
+            (Some(key), Some(certificate)) => (&key.payload, certificate.payload.to_bytes()),
+            _ => {
+                debug!(
+                    "no attestation key/certificate with ID: {:?}",
+                    command.attestation_key_id
+                );
+                return device::ErrorKind::ObjectNotFound.into();
+            }
+        }
+    };
+
+    match attestation::attestation_certificate(
+        attestation_key,
+        &attestation_certificate,
+        subject,
+        state.serial_number,
+        state.firmware_version,
+    ) {
+        Ok(certificate) => Certificate(certificate).serialize(),
+        Err(kind) => kind.into(),
+    }
+}
+
+/// Sign an SSH certificate using the given template
+#[cfg(feature = "untested")]
+fn sign_ssh_certificate(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: SignSshCertificateCommand = parse_or_reply!(cmd_data);
+
+    let key = state
+        .objects
+        .get(command.key_id, object::Type::AsymmetricKey);
+
+    let template = state
+        .objects
+        .get(command.template_id, object::Type::Template);
+
+    let (key, template) = match (key, template) {
+        (Some(key), Some(template)) => (key, template),
+        _ => {
+            debug!(
+                "no such key/template object ID: {:?}/{:?}",
+                command.key_id, command.template_id
+            );
+            return device::ErrorKind::ObjectNotFound.into();
+        }
+    };
+
+    match ssh::sign_certificate(
+        &template.payload.to_bytes(),
+        command.key_id,
+        &key.payload,
+        command.algorithm,
+        command.timestamp,
+        &command.request,
+    ) {
+        Ok(certificate) => {
+            SignSshCertificateResponse(crate::ssh::Certificate::from_bytes(certificate)).serialize()
+        }
+        Err(kind) => kind.into(),
+    }
 }
 
 /// Sign a message using the ECDSA signature algorithm
 fn sign_ecdsa(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: SignEcdsaCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::SignEcdsa: {e:?}"));
+    let command: SignEcdsaCommand = parse_or_reply!(cmd_data);
 
     if let Some(obj) = state
         .objects
         .get(command.key_id, object::Type::AsymmetricKey)
     {
-        match &obj.payload {
+        let digest = command.digest.as_slice();
+
+        let signature = match &obj.payload {
             Payload::EcdsaNistP256(secret_key) => {
-                let k = p256::Scalar::random(&mut OsRng);
-                let z = p256::Scalar::reduce_bytes(GenericArray::from_slice(&command.digest))
-                    .to_bytes();
-                let signature = secret_key
-                    .to_nonzero_scalar()
-                    .try_sign_prehashed(k, &z)
-                    .expect("ECDSA failure!")
-                    .0;
-
-                SignEcdsaResponse(signature.to_der().as_ref().into()).serialize()
+                ecdsa_sign(secret_key, digest).to_der().to_bytes()
+            }
+            Payload::EcdsaNistP384(secret_key) => {
+                ecdsa_sign(secret_key, digest).to_der().to_bytes()
+            }
+            #[cfg(feature = "nistp521")]
+            Payload::EcdsaNistP521(secret_key) => {
+                ecdsa_sign(secret_key, digest).to_der().to_bytes()
             }
             Payload::EcdsaSecp256k1(secret_key) => {
-                let k = k256::Scalar::random(&mut OsRng);
-                let z = <k256::Scalar as Reduce<U256>>::reduce_bytes(GenericArray::from_slice(
-                    &command.digest,
-                ))
-                .to_bytes();
-                let signature = secret_key
-                    .to_nonzero_scalar()
-                    .try_sign_prehashed(k, &z)
-                    .expect("ECDSA failure!")
-                    .0;
-
-                SignEcdsaResponse(signature.to_der().as_ref().into()).serialize()
+                ecdsa_sign(secret_key, digest).to_der().to_bytes()
             }
             _ => {
                 debug!("not an ECDSA key: {:?}", obj.algorithm());
-                device::ErrorKind::InvalidCommand.into()
+                return device::ErrorKind::InvalidCommand.into();
             }
-        }
+        };
+
+        SignEcdsaResponse(signature.into()).serialize()
     } else {
         debug!("no such object ID: {:?}", command.key_id);
         device::ErrorKind::ObjectNotFound.into()
     }
 }
 
+/// Sign a precomputed digest using RSASSA-PKCS#1v1.5, selecting the
+/// digest algorithm by the length of the digest
+fn sign_pkcs1(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: SignPkcs1Command = parse_or_reply!(cmd_data);
+
+    let private_key = match get_rsa_key(state, command.key_id) {
+        Ok(key) => key,
+        Err(kind) => return kind.into(),
+    };
+
+    let padding = match command.digest.len() {
+        32 => Pkcs1v15Sign::new::<Sha256>(),
+        48 => Pkcs1v15Sign::new::<Sha384>(),
+        64 => Pkcs1v15Sign::new::<Sha512>(),
+        other => {
+            debug!("unsupported digest length: {}", other);
+            return device::ErrorKind::InvalidData.into();
+        }
+    };
+
+    match private_key.sign_with_rng(&mut OsRng, padding, &command.digest) {
+        Ok(signature) => SignPkcs1Response(rsa::pkcs1::Signature(signature)).serialize(),
+        Err(e) => {
+            debug!("RSASSA-PKCS#1v1.5 signing failed: {}", e);
+            device::ErrorKind::InvalidData.into()
+        }
+    }
+}
+
+/// Sign a precomputed digest using RSASSA-PSS
+fn sign_pss(state: &State, cmd_data: &[u8]) -> response::Message {
+    let command: SignPssCommand = parse_or_reply!(cmd_data);
+
+    let private_key = match get_rsa_key(state, command.key_id) {
+        Ok(key) => key,
+        Err(kind) => return kind.into(),
+    };
+
+    let salt_len = usize::from(command.salt_len);
+
+    // The `rsa` crate uses the same digest for the message and MGF1
+    let padding = match (command.digest.len(), command.mgf1_hash_alg) {
+        (32, rsa::mgf::Algorithm::Sha256) => Pss::new_with_salt::<Sha256>(salt_len),
+        (48, rsa::mgf::Algorithm::Sha384) => Pss::new_with_salt::<Sha384>(salt_len),
+        (64, rsa::mgf::Algorithm::Sha512) => Pss::new_with_salt::<Sha512>(salt_len),
+        (len, mgf1_algorithm) => {
+            debug!(
+                "unsupported digest length/MGF1 algorithm: {} {:?}",
+                len, mgf1_algorithm
+            );
+            return device::ErrorKind::InvalidData.into();
+        }
+    };
+
+    match private_key.sign_with_rng(&mut OsRng, padding, &command.digest) {
+        Ok(signature) => SignPssResponse(rsa::pss::Signature(signature)).serialize(),
+        Err(e) => {
+            debug!("RSASSA-PSS signing failed: {}", e);
+            device::ErrorKind::InvalidData.into()
+        }
+    }
+}
+
 /// Sign a message using the Ed25519 signature algorithm
 fn sign_eddsa(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: SignEddsaCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::SignEdDSA: {e:?}"));
+    let command: SignEddsaCommand = parse_or_reply!(cmd_data);
 
     if let Some(obj) = state
         .objects
@@ -687,12 +1626,15 @@ fn sign_eddsa(state: &State, cmd_data: &[u8]) -> response::Message {
 
 /// Compute the HMAC tag for the given data
 fn sign_hmac(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: SignHmacCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::HMACData: {e:?}"));
+    let command: SignHmacCommand = parse_or_reply!(cmd_data);
 
     if let Some(obj) = state.objects.get(command.key_id, object::Type::HmacKey) {
         if let Payload::HmacKey(alg, ref key) = obj.payload {
-            assert_eq!(alg, hmac::Algorithm::Sha256);
+            if alg != hmac::Algorithm::Sha256 {
+                debug!("MockHsm only supports HMAC-SHA256 (got {:?})", alg);
+                return device::ErrorKind::InvalidCommand.into();
+            }
+
             let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
             mac.update(&command.data);
             let tag = mac.finalize();
@@ -707,18 +1649,39 @@ fn sign_hmac(state: &State, cmd_data: &[u8]) -> response::Message {
     }
 }
 
+/// Decrypt data which was encrypted (using AES-CCM) under a wrap key
+fn unwrap_data(state: &State, cmd_data: &[u8]) -> response::Message {
+    let UnwrapDataCommand {
+        wrap_key_id,
+        nonce,
+        ciphertext,
+    } = parse_or_reply!(cmd_data);
+
+    match state.objects.unwrap_data(wrap_key_id, &nonce, ciphertext) {
+        Ok(plaintext) => UnwrapDataResponse(plaintext).serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
 /// Verify the HMAC tag for the given data
 fn verify_hmac(state: &State, cmd_data: &[u8]) -> response::Message {
-    let command: VerifyHmacCommand =
-        deserialize(cmd_data).unwrap_or_else(|e| panic!("error parsing Code::HMACData: {e:?}"));
+    let command: VerifyHmacCommand = parse_or_reply!(cmd_data);
 
     if let Some(obj) = state.objects.get(command.key_id, object::Type::HmacKey) {
         if let Payload::HmacKey(alg, ref key) = obj.payload {
-            assert_eq!(alg, hmac::Algorithm::Sha256);
+            if alg != hmac::Algorithm::Sha256 {
+                debug!("MockHsm only supports HMAC-SHA256 (got {:?})", alg);
+                return device::ErrorKind::InvalidCommand.into();
+            }
 
             // Because of a quirk of our serde parser everything winds up in the tag field
             let data = command.tag.into_vec();
 
+            if data.len() < 32 {
+                debug!("HMAC tag too short: {}", data.len());
+                return device::ErrorKind::WrongLength.into();
+            }
+
             let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
             mac.update(&data[32..]);
             let tag = mac.finalize().into_bytes();
@@ -734,3 +1697,265 @@ fn verify_hmac(state: &State, cmd_data: &[u8]) -> response::Message {
         device::ErrorKind::ObjectNotFound.into()
     }
 }
+
+/// Encrypt data (with AES-CCM) using the given wrap key
+fn wrap_data(state: &State, cmd_data: &[u8]) -> response::Message {
+    let WrapDataCommand {
+        wrap_key_id,
+        plaintext,
+    } = parse_or_reply!(cmd_data);
+
+    let nonce = wrap::Nonce::generate();
+
+    match state.objects.wrap_data(wrap_key_id, &nonce, plaintext) {
+        Ok(ciphertext) => WrapDataResponse(wrap::Message { nonce, ciphertext }).serialize(),
+        Err(e) => object_error(e),
+    }
+}
+
+/// Block cipher operations supported by symmetric keys
+#[derive(Copy, Clone, Debug)]
+enum CipherOp {
+    EncryptEcb,
+    DecryptEcb,
+    EncryptCbc(symmetric::Iv),
+    DecryptCbc(symmetric::Iv),
+}
+
+/// Encrypt or decrypt the given data using the symmetric key with the given ID
+fn symmetric_cipher(
+    state: &State,
+    key_id: object::Id,
+    op: CipherOp,
+    mut data: Vec<u8>,
+) -> Result<Vec<u8>, device::ErrorKind> {
+    let obj = state
+        .objects
+        .get(key_id, object::Type::SymmetricKey)
+        .ok_or_else(|| {
+            debug!("no such object ID: {:?}", key_id);
+            device::ErrorKind::ObjectNotFound
+        })?;
+
+    let (alg, key) = match &obj.payload {
+        Payload::SymmetricKey(alg, key) => (*alg, key),
+        _ => {
+            debug!("not a symmetric key: {:?}", obj.algorithm());
+            return Err(device::ErrorKind::InvalidCommand);
+        }
+    };
+
+    if data.is_empty() || data.len() % symmetric::BLOCK_SIZE != 0 {
+        debug!(
+            "{:?} data is not a multiple of the block size: {}",
+            op,
+            data.len()
+        );
+        return Err(device::ErrorKind::WrongLength);
+    }
+
+    match alg {
+        symmetric::Algorithm::Aes128 => aes_cipher::<aes::Aes128>(key, op, &mut data),
+        symmetric::Algorithm::Aes192 => aes_cipher::<aes::Aes192>(key, op, &mut data),
+        symmetric::Algorithm::Aes256 => aes_cipher::<aes::Aes256>(key, op, &mut data),
+    }
+
+    Ok(data)
+}
+
+/// Apply the given AES block cipher operation to block-aligned data in-place
+fn aes_cipher<C>(key: &[u8], op: CipherOp, data: &mut [u8])
+where
+    C: BlockCipher<BlockSize = U16> + BlockEncrypt + BlockDecrypt + aes::cipher::KeyInit,
+{
+    let cipher = C::new_from_slice(key).unwrap();
+
+    match op {
+        CipherOp::EncryptEcb => {
+            for block in data.chunks_exact_mut(symmetric::BLOCK_SIZE) {
+                cipher.encrypt_block(GenericArray::from_mut_slice(block));
+            }
+        }
+        CipherOp::DecryptEcb => {
+            for block in data.chunks_exact_mut(symmetric::BLOCK_SIZE) {
+                cipher.decrypt_block(GenericArray::from_mut_slice(block));
+            }
+        }
+        CipherOp::EncryptCbc(iv) => {
+            let len = data.len();
+            cbc::Encryptor::inner_iv_init(cipher, GenericArray::from_slice(&iv))
+                .encrypt_padded_mut::<NoPadding>(data, len)
+                .unwrap();
+        }
+        CipherOp::DecryptCbc(iv) => {
+            cbc::Decryptor::inner_iv_init(cipher, GenericArray::from_slice(&iv))
+                .decrypt_padded_mut::<NoPadding>(data)
+                .unwrap();
+        }
+    }
+}
+
+/// Compute an ECDH shared secret from the given SEC1-encoded public key
+fn ecdh_shared_secret<C>(secret_key: &SecretKey<C>, public_key: &[u8]) -> Option<Vec<u8>>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: sec1::ModulusSize,
+{
+    let public_key = elliptic_curve::PublicKey::<C>::from_sec1_bytes(public_key).ok()?;
+
+    Some(
+        diffie_hellman(secret_key.to_nonzero_scalar(), public_key.as_affine())
+            .raw_secret_bytes()
+            .to_vec(),
+    )
+}
+
+/// Compute an ECDSA signature over the given digest, which is truncated to
+/// (or left-padded up to) the size of the curve's field like the YubiHSM does
+fn ecdsa_sign<C>(secret_key: &SecretKey<C>, digest: &[u8]) -> ::ecdsa::Signature<C>
+where
+    C: PrimeCurve + CurveArithmetic,
+    Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+    SignatureSize<C>: ArrayLength<u8>,
+{
+    let mut z = FieldBytes::<C>::default();
+    let len = cmp::min(digest.len(), z.len());
+    let offset = z.len() - len;
+    z[offset..].copy_from_slice(&digest[..len]);
+
+    secret_key
+        .to_nonzero_scalar()
+        .try_sign_prehashed(Scalar::<C>::random(&mut OsRng), &z)
+        .expect("ECDSA failure!")
+        .0
+}
+
+/// Get the RSA private key with the given ID
+fn get_rsa_key(state: &State, key_id: object::Id) -> Result<&RsaPrivateKey, device::ErrorKind> {
+    let obj = state
+        .objects
+        .get(key_id, object::Type::AsymmetricKey)
+        .ok_or_else(|| {
+            debug!("no such object ID: {:?}", key_id);
+            device::ErrorKind::ObjectNotFound
+        })?;
+
+    obj.payload.rsa_key().ok_or_else(|| {
+        debug!("not an RSA key: {:?}", obj.algorithm());
+        device::ErrorKind::InvalidCommand
+    })
+}
+
+/// Get the OTP AEAD key with the given ID
+fn get_otp_aead_key(state: &State, key_id: object::Id) -> Result<&Payload, device::ErrorKind> {
+    state
+        .objects
+        .get(key_id, object::Type::OtpAeadKey)
+        .map(|obj| &obj.payload)
+        .ok_or_else(|| {
+            debug!("no such OTP AEAD key: {:?}", key_id);
+            device::ErrorKind::ObjectNotFound
+        })
+}
+
+/// Decrypt an RSA-OAEP ciphertext given the digest of its label, as
+/// described in RFC 8017 section 7.1.2. The `rsa` crate only supports
+/// decrypting with the label itself, so this uses its blinded RSA primitive
+/// and checks the padding in constant time, as the `rsa` crate does.
+fn rsa_oaep_decrypt(
+    key: &RsaPrivateKey,
+    mgf1_algorithm: rsa::mgf::Algorithm,
+    ciphertext: &[u8],
+    label_hash: &[u8],
+) -> Option<Vec<u8>> {
+    let mgf1: fn(&mut [u8], &[u8]) = match mgf1_algorithm {
+        rsa::mgf::Algorithm::Sha256 => rsa::mgf::mgf1_xor::<Sha256>,
+        rsa::mgf::Algorithm::Sha384 => rsa::mgf::mgf1_xor::<Sha384>,
+        rsa::mgf::Algorithm::Sha512 => rsa::mgf::mgf1_xor::<Sha512>,
+        other => {
+            debug!("unsupported MGF1 algorithm: {:?}", other);
+            return None;
+        }
+    };
+
+    let modulus_size = key.size();
+    let hash_size = label_hash.len();
+
+    if hash_size == 0 || modulus_size < 2 * hash_size + 2 {
+        return None;
+    }
+
+    let message = rsa_decrypt_and_check(key, Some(&mut OsRng), &BigUint::from_bytes_be(ciphertext))
+        .ok()?
+        .to_bytes_be();
+
+    if message.len() > modulus_size {
+        return None;
+    }
+
+    // Encoded message: 0x00 || masked seed || masked data block
+    let mut encoded = Zeroizing::new(vec![0u8; modulus_size - message.len()]);
+    encoded.extend_from_slice(&message);
+
+    let first_byte_is_zero = encoded[0].ct_eq(&0);
+    let (seed, data_block) = encoded[1..].split_at_mut(hash_size);
+    mgf1(seed, data_block);
+    mgf1(data_block, seed);
+
+    // Data block: label hash || zero padding || 0x01 || message
+    let (data_label_hash, padded_message) = data_block.split_at(hash_size);
+    let label_hash_matches = data_label_hash.ct_eq(label_hash);
+
+    // Find the 0x01 separator without branching on the padding contents
+    let mut looking_for_separator = Choice::from(1);
+    let mut separator_index = 0u32;
+    let mut invalid_padding = Choice::from(0);
+
+    for (i, byte) in padded_message.iter().enumerate() {
+        let is_zero = byte.ct_eq(&0);
+        let is_separator = byte.ct_eq(&1);
+        separator_index.conditional_assign(&(i as u32), looking_for_separator & is_separator);
+        looking_for_separator &= !is_separator;
+        invalid_padding |= looking_for_separator & !is_zero;
+    }
+
+    let valid = first_byte_is_zero & label_hash_matches & !invalid_padding & !looking_for_separator;
+
+    if bool::from(valid) {
+        Some(padded_message[separator_index as usize + 1..].to_vec())
+    } else {
+        None
+    }
+}
+
+/// Parse the data of a command, mapping failures to the errors a device
+/// responds to malformed commands with
+pub(super) fn parse_command<T: DeserializeOwned>(cmd_data: &[u8]) -> Result<T, device::ErrorKind> {
+    deserialize_exact(cmd_data).map_err(command_error)
+}
+
+/// Parse the leading fields of a command's data, ignoring the rest of it
+pub(super) fn parse_command_prefix<T: DeserializeOwned>(
+    cmd_data: &[u8],
+) -> Result<T, device::ErrorKind> {
+    deserialize(cmd_data).map_err(command_error)
+}
+
+/// Map an error parsing command data to the error a device responds with
+fn command_error(e: serialization::Error) -> device::ErrorKind {
+    debug!("error parsing command data: {}", e);
+
+    match e.kind() {
+        serialization::ErrorKind::Io
+        | serialization::ErrorKind::UnexpectedEof
+        | serialization::ErrorKind::TrailingData => device::ErrorKind::WrongLength,
+        serialization::ErrorKind::Parse => device::ErrorKind::InvalidData,
+    }
+}
+
+/// Convert an error operating on the MockHsm's objects into an error response
+fn object_error(e: Error) -> response::Message {
+    debug!("object error: {}", e);
+    device::ErrorKind::from(*e.kind()).into()
+}
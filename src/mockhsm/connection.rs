@@ -1,9 +1,15 @@
 //! Mock connection to the MockHSM
 
-use super::{command, state::State, MockHsm};
+use super::{
+    command,
+    fault::{self, Fault},
+    state::State,
+    MockHsm,
+};
 use crate::{
     command::Code,
     connector::{self, Connection, ErrorKind::ConnectionFailed, Message},
+    device, response,
 };
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
@@ -21,21 +27,266 @@ impl MockConnection {
 impl Connection for MockConnection {
     /// Send a message to the MockHsm
     fn send_message(&self, _uuid: Uuid, message: Message) -> Result<Message, connector::Error> {
-        let command = message
-            .parse()
-            .map_err(|e| format_err!(ConnectionFailed, "error parsing command: {}", e))?;
+        let command = match message.clone().parse() {
+            Ok(command) => command,
+            Err(e) => {
+                debug!("error parsing command: {}", e);
+                let response: Vec<u8> = response::Message::from(parse_error(&message)).into();
+                return Ok(response.into());
+            }
+        };
 
         let mut state = self
             .0
             .lock()
             .map_err(|e| format_err!(ConnectionFailed, "error obtaining state lock: {}", e))?;
 
-        match command.command_type {
-            Code::CreateSession => command::create_session(&mut state, &command),
-            Code::AuthenticateSession => command::authenticate_session(&mut state, &command),
-            Code::SessionMessage => command::session_message(&mut state, command),
-            unsupported => fail!(ConnectionFailed, "unsupported command: {:?}", unsupported),
+        let fault = state.fault_plan.message_fault(command.command_type);
+        let clock = state.clock();
+
+        let (response, session_fault) = match (fault, command.command_type) {
+            (Some(Fault::DeviceError(kind)), _) => (response::Message::from(kind).into(), None),
+            (_, Code::CreateSession) => (command::create_session(&mut state, &command)?, None),
+            (_, Code::AuthenticateSession) => {
+                (command::authenticate_session(&mut state, &command)?, None)
+            }
+            (_, Code::SessionMessage) => command::session_message(&mut state, command)?,
+            (_, Code::DeviceInfo) => (command::device_info(&state).into(), None),
+            (_, Code::Echo) => (command::echo(&command.data).into(), None),
+            (_, Code::GetDevicePublicKey) => (command::get_device_public_key(&state)?, None),
+            (_, unsupported) => {
+                debug!("unsupported command: {:?}", unsupported);
+                (
+                    response::Message::from(device::ErrorKind::InvalidCommand).into(),
+                    None,
+                )
+            }
+        };
+
+        // Release the state lock so faults (e.g. delays) don't block other
+        // connections to the MockHsm
+        drop(state);
+
+        let response = fault::inject(session_fault, response, clock.as_ref())?;
+        fault::inject(fault, response, clock.as_ref()).map(Message::from)
+    }
+}
+
+/// Get the error a device responds with to a message which couldn't be parsed
+fn parse_error(message: &Message) -> device::ErrorKind {
+    let bytes = message.as_ref();
+
+    if bytes.len() < 3 {
+        return device::ErrorKind::InvalidCommand;
+    }
+
+    let length = usize::from(u16::from_be_bytes([bytes[1], bytes[2]]));
+
+    match Code::from_u8(bytes[0]) {
+        Err(_) => device::ErrorKind::InvalidCommand,
+        Ok(_) if length + 3 != bytes.len() => device::ErrorKind::WrongLength,
+        Ok(Code::AuthenticateSession | Code::SessionMessage) if length == 0 => {
+            device::ErrorKind::InvalidSession
         }
-        .map(Message::from)
+        Ok(_) => device::ErrorKind::WrongLength,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockConnection;
+    use crate::{
+        command::{self, Code},
+        connector::{Connection, Connector, Message},
+        device,
+        mockhsm::MockHsm,
+        response,
+        session::securechannel::SecureChannel,
+        uuid, Credentials,
+    };
+
+    /// Send a raw message to the MockHsm, returning its (unencrypted) response
+    fn send_raw(connection: &MockConnection, message: impl Into<Message>) -> response::Message {
+        let response = connection
+            .send_message(uuid::new_v4(), message.into())
+            .unwrap();
+
+        response::Message::parse(response).unwrap()
+    }
+
+    /// Send a raw message to the MockHsm, returning the error it responds with
+    fn send_raw_error(
+        connection: &MockConnection,
+        message: impl Into<Message>,
+    ) -> device::ErrorKind {
+        let response = send_raw(connection, message);
+        device::ErrorKind::from_response_message(&response).expect("expected error response")
+    }
+
+    /// Open an authenticated session with a new MockHsm
+    fn open_session() -> (MockConnection, SecureChannel) {
+        let mockhsm = MockHsm::new();
+        let connection = MockConnection::new(&mockhsm);
+
+        let mut channel =
+            SecureChannel::open(&Connector::from(mockhsm), &Credentials::default()).unwrap();
+
+        let command = channel.authenticate_session().unwrap();
+        let response = send_raw(&connection, command);
+        channel.finish_authenticate_session(&response).unwrap();
+
+        (connection, channel)
+    }
+
+    /// Send a command within a session, returning the decrypted response
+    fn send_session_command(
+        connection: &MockConnection,
+        channel: &mut SecureChannel,
+        command_type: Code,
+        command_data: &[u8],
+    ) -> response::Message {
+        let command = command::Message::create(command_type, command_data.to_vec()).unwrap();
+        let encrypted_command = channel.encrypt_command(command).unwrap();
+        let response = send_raw(connection, encrypted_command);
+        channel.decrypt_response(response).unwrap()
+    }
+
+    #[test]
+    fn unsupported_command_test() {
+        let (connection, _) = open_session();
+
+        // Unknown command code
+        assert_eq!(
+            send_raw_error(&connection, vec![0xee, 0x00, 0x00]),
+            device::ErrorKind::InvalidCommand
+        );
+
+        // Too short to have a command code and length
+        assert_eq!(
+            send_raw_error(&connection, vec![0x06]),
+            device::ErrorKind::InvalidCommand
+        );
+
+        // Commands which are only supported within a session
+        assert_eq!(
+            send_raw_error(&connection, vec![0x51, 0x00, 0x02, 0x00, 0x10]),
+            device::ErrorKind::InvalidCommand
+        );
+    }
+
+    #[test]
+    fn truncated_command_test() {
+        let (connection, mut channel) = open_session();
+
+        // Length field doesn't match the command data
+        assert_eq!(
+            send_raw_error(&connection, vec![0x06, 0x00, 0x05, 0x00]),
+            device::ErrorKind::WrongLength
+        );
+
+        // Session ID without a MAC
+        assert_eq!(
+            send_raw_error(&connection, vec![0x05, 0x00, 0x01, channel.id().to_u8()]),
+            device::ErrorKind::WrongLength
+        );
+
+        // Unencrypted command data which is too short
+        assert_eq!(
+            send_raw_error(&connection, vec![0x03, 0x00, 0x01, 0x00]),
+            device::ErrorKind::WrongLength
+        );
+
+        // Encrypted command data which is too short
+        let response =
+            send_session_command(&connection, &mut channel, Code::GetObjectInfo, &[0x00]);
+        assert_eq!(
+            device::ErrorKind::from_response_message(&response),
+            Some(device::ErrorKind::WrongLength)
+        );
+    }
+
+    #[test]
+    fn oversized_command_test() {
+        let (connection, mut channel) = open_session();
+
+        // Unencrypted command data followed by an extra byte
+        let mut message = vec![0x03, 0x00, 0x0b, 0x00, 0x01];
+        message.extend_from_slice(&[0x42; 9]);
+        assert_eq!(
+            send_raw_error(&connection, message),
+            device::ErrorKind::WrongLength
+        );
+
+        // Encrypted command data followed by an extra byte
+        let response = send_session_command(
+            &connection,
+            &mut channel,
+            Code::GetObjectInfo,
+            &[0x00, 0x01, 0x02, 0x00],
+        );
+        assert_eq!(
+            device::ErrorKind::from_response_message(&response),
+            Some(device::ErrorKind::WrongLength)
+        );
+    }
+
+    #[test]
+    fn invalid_command_data_test() {
+        let (connection, mut channel) = open_session();
+
+        // Object ID followed by an invalid object type
+        let response = send_session_command(
+            &connection,
+            &mut channel,
+            Code::GetObjectInfo,
+            &[0x00, 0x01, 0xee],
+        );
+        assert_eq!(
+            device::ErrorKind::from_response_message(&response),
+            Some(device::ErrorKind::InvalidData)
+        );
+
+        // The session is still usable
+        let response = send_session_command(&connection, &mut channel, Code::Echo, b"hello");
+        assert_eq!(response.data, b"hello");
+    }
+
+    #[test]
+    fn invalid_session_test() {
+        let (connection, _) = open_session();
+
+        // Session message without a session ID
+        assert_eq!(
+            send_raw_error(&connection, vec![0x05, 0x00, 0x00]),
+            device::ErrorKind::InvalidSession
+        );
+
+        // Session which doesn't exist
+        let mut message = vec![0x05, 0x00, 0x19, 0x0f];
+        message.extend_from_slice(&[0x42; 24]);
+        assert_eq!(
+            send_raw_error(&connection, message),
+            device::ErrorKind::InvalidSession
+        );
+    }
+
+    #[test]
+    fn bad_mac_test() {
+        let (connection, mut channel) = open_session();
+
+        let mut message = vec![0x05, 0x00, 0x19, channel.id().to_u8()];
+        message.extend_from_slice(&[0x42; 24]);
+        assert_eq!(
+            send_raw_error(&connection, message),
+            device::ErrorKind::AuthenticationFailed
+        );
+
+        // The session was closed
+        let command = command::Message::create(Code::Echo, b"hello".to_vec()).unwrap();
+        let encrypted_command = channel.encrypt_command(command).unwrap();
+        assert_eq!(
+            send_raw_error(&connection, encrypted_command),
+            device::ErrorKind::InvalidSession
+        );
     }
 }
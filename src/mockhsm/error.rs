@@ -1,6 +1,10 @@
 //! MockHSM errors
 
-use crate::error::{BoxError, Context};
+use crate::{
+    device,
+    error::{BoxError, Context},
+};
+use std::io;
 use thiserror::Error;
 
 /// `MockHsm`-related errors
@@ -17,9 +21,33 @@ pub enum ErrorKind {
     #[error("crypto error")]
     CryptoError,
 
+    /// Input/output error
+    #[error("I/O error")]
+    IoError,
+
+    /// Malformed or unsupported object data
+    #[error("invalid data")]
+    InvalidData,
+
+    /// Object already exists
+    #[error("object exists")]
+    ObjectExists,
+
     /// Object does not exist
     #[error("object not found")]
     ObjectNotFound,
+
+    /// Error parsing a snapshot of the `MockHsm` state
+    #[error("parse error")]
+    ParseError,
+
+    /// No storage space left for a new object
+    #[error("storage full")]
+    StorageFull,
+
+    /// Object data has the wrong length
+    #[error("wrong length")]
+    WrongLength,
 }
 
 impl ErrorKind {
@@ -28,3 +56,25 @@ impl ErrorKind {
         Context::new(self, Some(source.into()))
     }
 }
+
+impl From<ErrorKind> for device::ErrorKind {
+    /// Map `MockHsm` errors to the codes the `YubiHSM 2` responds with
+    fn from(kind: ErrorKind) -> device::ErrorKind {
+        match kind {
+            ErrorKind::AccessDenied => device::ErrorKind::InsufficientPermissions,
+            ErrorKind::CryptoError => device::ErrorKind::InvalidData,
+            ErrorKind::IoError | ErrorKind::ParseError => device::ErrorKind::GenericError,
+            ErrorKind::InvalidData => device::ErrorKind::InvalidData,
+            ErrorKind::ObjectExists => device::ErrorKind::ObjectExists,
+            ErrorKind::ObjectNotFound => device::ErrorKind::ObjectNotFound,
+            ErrorKind::StorageFull => device::ErrorKind::StorageFailed,
+            ErrorKind::WrongLength => device::ErrorKind::WrongLength,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        ErrorKind::IoError.context(err).into()
+    }
+}
@@ -0,0 +1,149 @@
+//! Fault injection for testing how clients handle misbehaving devices
+
+use super::clock::Clock;
+use crate::{
+    command,
+    connector::{self, ErrorKind::ConnectionFailed, ErrorKind::IoError},
+    device,
+};
+use std::time::Duration;
+
+/// Faults which can be injected into the `MockHsm`'s responses
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Fault {
+    /// Perform the command, but fail to deliver its response, as if the
+    /// connection to the device was lost
+    DropResponse,
+
+    /// Perform the command, but flip a bit in the last byte of its response
+    /// (i.e. the response MAC of encrypted session messages)
+    CorruptMac,
+
+    /// Respond with the given device error instead of performing the command.
+    ///
+    /// Faults triggered by the code of a command sent within an encrypted
+    /// session respond with an encrypted error, as a device does when the
+    /// command itself fails. All other faults respond with an unencrypted
+    /// error, as a device does when it rejects the message or session.
+    DeviceError(device::ErrorKind),
+
+    /// Wait for the given duration before responding. A `MockHsm` driven
+    /// by a [`ManualClock`][`super::ManualClock`] advances it instead.
+    Delay(Duration),
+}
+
+/// Conditions under which a fault is injected
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Trigger {
+    /// Every command with the given code, including commands sent within
+    /// an encrypted session
+    Command(command::Code),
+
+    /// The Nth message sent to the `MockHsm`, counting from 1
+    Message(usize),
+}
+
+/// Plan of faults to inject into a `MockHsm`'s connections and responses
+#[derive(Clone, Debug, Default)]
+pub struct FaultPlan {
+    /// Faults to inject, along with their triggers. The first matching
+    /// fault is injected.
+    faults: Vec<(Trigger, Fault)>,
+
+    /// Number of upcoming connection attempts which will fail
+    failed_connects: usize,
+
+    /// Number of messages sent to the `MockHsm` so far
+    message_count: usize,
+}
+
+impl FaultPlan {
+    /// Create a new fault plan which injects no faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject a fault into the response to every command with the given code
+    pub fn on_command(mut self, command_type: command::Code, fault: Fault) -> Self {
+        self.faults.push((Trigger::Command(command_type), fault));
+        self
+    }
+
+    /// Inject a fault into the response to the Nth message (counting from 1)
+    /// sent to the `MockHsm` after the plan is set
+    pub fn on_message(mut self, message_number: usize, fault: Fault) -> Self {
+        self.faults.push((Trigger::Message(message_number), fault));
+        self
+    }
+
+    /// Fail the given number of upcoming connection attempts
+    pub fn fail_connect(mut self, attempts: usize) -> Self {
+        self.failed_connects = attempts;
+        self
+    }
+
+    /// Should the current connection attempt fail?
+    pub(super) fn connect_failed(&mut self) -> bool {
+        if self.failed_connects > 0 {
+            self.failed_connects -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Count a message received by the `MockHsm`, returning the fault to
+    /// inject into its response (if any)
+    pub(super) fn message_fault(&mut self, command_type: command::Code) -> Option<Fault> {
+        self.message_count += 1;
+        let message_number = self.message_count;
+
+        self.find(|trigger| {
+            trigger == Trigger::Message(message_number) || trigger == Trigger::Command(command_type)
+        })
+    }
+
+    /// Get the fault to inject into the response to a command sent within
+    /// an encrypted session (if any)
+    pub(super) fn session_command_fault(&self, command_type: command::Code) -> Option<Fault> {
+        self.find(|trigger| trigger == Trigger::Command(command_type))
+    }
+
+    /// Find the first fault whose trigger matches the given predicate
+    fn find(&self, predicate: impl Fn(Trigger) -> bool) -> Option<Fault> {
+        self.faults
+            .iter()
+            .find(|(trigger, _)| predicate(*trigger))
+            .map(|(_, fault)| *fault)
+    }
+}
+
+/// Inject a fault (other than a device error, which replaces the response
+/// before the command is performed) into a serialized response
+pub(super) fn inject(
+    fault: Option<Fault>,
+    mut response: Vec<u8>,
+    clock: &dyn Clock,
+) -> Result<Vec<u8>, connector::Error> {
+    match fault {
+        Some(Fault::DropResponse) => fail!(IoError, "MockHsm response dropped (injected fault)"),
+        Some(Fault::CorruptMac) => {
+            if let Some(byte) = response.last_mut() {
+                *byte ^= 0x01;
+            }
+        }
+        Some(Fault::Delay(duration)) => clock.sleep(duration),
+        Some(Fault::DeviceError(_)) | None => (),
+    }
+
+    Ok(response)
+}
+
+/// Error for connection attempts which failed due to an injected fault
+pub(super) fn connect_error() -> connector::Error {
+    format_err!(
+        ConnectionFailed,
+        "MockHsm connection failed (injected fault)"
+    )
+    .into()
+}
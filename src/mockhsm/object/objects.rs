@@ -3,14 +3,28 @@
 use super::{Object, Payload, WrappedObject, DEFAULT_AUTHENTICATION_KEY_LABEL};
 use crate::{
     authentication::{self, DEFAULT_AUTHENTICATION_KEY_ID},
+    device::StorageInfo,
     mockhsm::{Error, ErrorKind},
     object::{Handle, Id, Info, Label, Origin, Type},
+    rsa::oaep::DigestAlgorithm,
     serialization::{deserialize, serialize},
-    wrap, Algorithm, Capability, Domain,
+    symmetric, wrap, Algorithm, Capability, Domain,
 };
 use aes::cipher::consts::{U13, U16};
 use ccm::aead::{AeadInPlace, KeyInit};
-use std::collections::{btree_map::Iter as MapIter, BTreeMap as Map};
+use std::{
+    cmp,
+    collections::{btree_map::Iter as MapIter, BTreeMap as Map},
+};
+
+/// Total number of object records in the MockHsm
+const TOTAL_RECORDS: u16 = 256;
+
+/// Total number of storage pages in the MockHsm
+const TOTAL_PAGES: u16 = 1024;
+
+/// Size of a storage page in bytes
+const PAGE_SIZE: u16 = 126;
 
 /// AES-CCM with a 128-bit key
 pub(crate) type Aes128Ccm = ccm::Ccm<aes::Aes128, U16, U13>;
@@ -127,6 +141,20 @@ impl Default for Objects {
     }
 }
 
+impl FromIterator<Object> for Objects {
+    fn from_iter<I: IntoIterator<Item = Object>>(iter: I) -> Self {
+        Objects(
+            iter.into_iter()
+                .map(|obj| {
+                    let handle =
+                        Handle::new(obj.object_info.object_id, obj.object_info.object_type);
+                    (handle, obj)
+                })
+                .collect(),
+        )
+    }
+}
+
 impl Objects {
     /// Generate a new object in the MockHsm
     pub fn generate(
@@ -138,14 +166,39 @@ impl Objects {
         capabilities: Capability,
         delegated_capabilities: Capability,
         domains: Domain,
-    ) {
-        let payload = Payload::generate(algorithm);
+    ) -> Result<(), Error> {
+        self.insert_generated(
+            object_id,
+            object_type,
+            label,
+            capabilities,
+            delegated_capabilities,
+            domains,
+            Payload::generate(algorithm)?,
+        )
+    }
+
+    /// Insert a newly generated payload as an object in the MockHsm
+    pub fn insert_generated(
+        &mut self,
+        object_id: Id,
+        object_type: Type,
+        label: Label,
+        capabilities: Capability,
+        delegated_capabilities: Capability,
+        domains: Domain,
+        payload: Payload,
+    ) -> Result<(), Error> {
+        let handle = Handle::new(object_id, object_type);
+        self.ensure_not_exists(&handle)?;
+
         let length = payload.len();
+        self.ensure_free_storage(length)?;
 
         let object_info = Info {
             object_id,
             object_type,
-            algorithm,
+            algorithm: payload.algorithm(),
             capabilities,
             delegated_capabilities,
             domains,
@@ -155,14 +208,13 @@ impl Objects {
             label,
         };
 
-        let handle = Handle::new(object_id, object_type);
-
         let object = Object {
             object_info,
             payload,
         };
 
-        assert!(self.0.insert(handle, object).is_none());
+        self.0.insert(handle, object);
+        Ok(())
     }
 
     /// Get an object
@@ -170,6 +222,11 @@ impl Objects {
         self.0.get(&Handle::new(object_id, object_type))
     }
 
+    /// Get a mutable reference to an object
+    pub fn get_mut(&mut self, object_id: Id, object_type: Type) -> Option<&mut Object> {
+        self.0.get_mut(&Handle::new(object_id, object_type))
+    }
+
     /// Put a new object in the MockHsm
     pub fn put(
         &mut self,
@@ -181,9 +238,13 @@ impl Objects {
         delegated_capabilities: Capability,
         domains: Domain,
         data: &[u8],
-    ) {
-        let payload = Payload::new(algorithm, data);
+    ) -> Result<(), Error> {
+        let handle = Handle::new(object_id, object_type);
+        self.ensure_not_exists(&handle)?;
+
+        let payload = Payload::from_object_data(object_type, algorithm, data)?;
         let length = payload.len();
+        self.ensure_free_storage(length)?;
 
         let object_info = Info {
             object_id,
@@ -198,14 +259,13 @@ impl Objects {
             label,
         };
 
-        let handle = Handle::new(object_id, object_type);
-
         let object = Object {
             object_info,
             payload,
         };
 
-        assert!(self.0.insert(handle, object).is_none());
+        self.0.insert(handle, object);
+        Ok(())
     }
 
     /// Remove an object
@@ -222,7 +282,182 @@ impl Objects {
         nonce: &wrap::Nonce,
     ) -> Result<Vec<u8>, Error> {
         let wrap_key = self.get_wrap_key(wrap_key_id)?;
+        let (object_info, data) = self.export_obj(object_id, object_type)?;
 
+        let mut wrapped_object = serialize(&WrappedObject {
+            alg_id: wrap_key.algorithm(),
+            object_info,
+            data,
+        })
+        .unwrap();
+
+        wrap_key
+            .encrypt_in_place(nonce, b"", &mut wrapped_object)
+            .unwrap();
+
+        Ok(wrapped_object)
+    }
+
+    /// Encrypt an object under an RSA public wrap key, using an ephemeral
+    /// AES key of the given algorithm
+    pub fn wrap_obj_rsa<D: DigestAlgorithm>(
+        &mut self,
+        wrap_key_id: Id,
+        object_id: Id,
+        object_type: Type,
+        aes_algorithm: symmetric::Algorithm,
+    ) -> Result<wrap::RsaMessage, Error> {
+        let public_key = match self
+            .get(wrap_key_id, Type::PublicWrapKey)
+            .and_then(|obj| obj.payload.public_wrap_key())
+        {
+            Some(k) => k.clone(),
+            None => fail!(
+                ErrorKind::ObjectNotFound,
+                "no such public wrap key: {:?}",
+                wrap_key_id
+            ),
+        };
+
+        let (object_info, data) = self.export_obj(object_id, object_type)?;
+
+        wrap::RsaPlaintext { object_info, data }
+            .encrypt::<D>(&public_key, aes_algorithm)
+            .map_err(|e| format_err!(ErrorKind::CryptoError, "{}", e).into())
+    }
+
+    /// Encrypt arbitrary data under a wrap key
+    pub fn wrap_data(
+        &self,
+        wrap_key_id: Id,
+        nonce: &wrap::Nonce,
+        mut data: Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        self.get_wrap_key(wrap_key_id)?
+            .encrypt_in_place(nonce, b"", &mut data)?;
+
+        Ok(data)
+    }
+
+    /// Decrypt data which was encrypted under a wrap key
+    pub fn unwrap_data(
+        &self,
+        wrap_key_id: Id,
+        nonce: &wrap::Nonce,
+        mut ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        self.get_wrap_key(wrap_key_id)?
+            .decrypt_in_place(nonce, b"", &mut ciphertext)?;
+
+        Ok(ciphertext)
+    }
+
+    /// Deserialize an encrypted object and insert it into the HSM, provided
+    /// it's within the given domains of the importing session
+    pub fn unwrap_obj<V: Into<Vec<u8>>>(
+        &mut self,
+        wrap_key_id: Id,
+        domains: Domain,
+        nonce: &wrap::Nonce,
+        ciphertext: V,
+    ) -> Result<Handle, Error> {
+        let wrap_key = self.get_wrap_key(wrap_key_id)?;
+        let mut wrapped_data: Vec<u8> = ciphertext.into();
+        wrap_key.decrypt_in_place(nonce, b"", &mut wrapped_data)?;
+
+        let unwrapped_object: WrappedObject = deserialize(&wrapped_data)
+            .map_err(|e| format_err!(ErrorKind::InvalidData, "malformed wrapped object: {}", e))?;
+
+        // Objects can't be imported with capabilities beyond those delegated
+        // to the wrap key
+        let delegated_capabilities = self
+            .get(wrap_key_id, Type::WrapKey)
+            .unwrap()
+            .object_info
+            .delegated_capabilities;
+
+        ensure!(
+            delegated_capabilities.contains(unwrapped_object.object_info.capabilities),
+            ErrorKind::AccessDenied,
+            "capabilities {:?} not delegated to wrap key {:?}",
+            unwrapped_object.object_info.capabilities - delegated_capabilities,
+            wrap_key_id
+        );
+
+        check_unwrapped_domains(&unwrapped_object.object_info, domains)?;
+        self.import_obj(unwrapped_object.object_info, &unwrapped_object.data)
+    }
+
+    /// Decrypt an object encrypted under an RSA public wrap key using the
+    /// corresponding RSA private key, and insert it into the HSM
+    pub fn unwrap_obj_rsa<D: DigestAlgorithm>(
+        &mut self,
+        unwrap_key_id: Id,
+        domains: Domain,
+        message: &wrap::RsaMessage,
+    ) -> Result<Handle, Error> {
+        let private_key = match self
+            .get(unwrap_key_id, Type::WrapKey)
+            .and_then(|obj| obj.payload.rsa_key())
+        {
+            Some(k) => k,
+            None => fail!(
+                ErrorKind::ObjectNotFound,
+                "no such RSA wrap key: {:?}",
+                unwrap_key_id
+            ),
+        };
+
+        let plaintext = message
+            .decrypt::<D>(private_key)
+            .map_err(|e| format_err!(ErrorKind::CryptoError, "{}", e))?;
+
+        // Objects can't be imported with capabilities beyond those delegated
+        // to the unwrap key
+        let delegated_capabilities = self
+            .get(unwrap_key_id, Type::WrapKey)
+            .unwrap()
+            .object_info
+            .delegated_capabilities;
+
+        ensure!(
+            delegated_capabilities.contains(plaintext.object_info.capabilities),
+            ErrorKind::AccessDenied,
+            "capabilities {:?} not delegated to unwrap key {:?}",
+            plaintext.object_info.capabilities - delegated_capabilities,
+            unwrap_key_id
+        );
+
+        check_unwrapped_domains(&plaintext.object_info, domains)?;
+        self.import_obj(plaintext.object_info.clone(), &plaintext.data)
+    }
+
+    /// Iterate over the objects
+    pub fn iter(&self) -> Iter<'_> {
+        self.0.iter()
+    }
+
+    /// Get the current storage usage, accounting one record and the pages
+    /// needed to hold its data for each object
+    pub fn storage_info(&self) -> StorageInfo {
+        let used_pages: usize = self
+            .0
+            .values()
+            .map(|obj| storage_pages(obj.object_info.length))
+            .sum();
+
+        StorageInfo {
+            total_records: TOTAL_RECORDS,
+            free_records: TOTAL_RECORDS.saturating_sub(self.0.len() as u16),
+            total_pages: TOTAL_PAGES,
+            free_pages: TOTAL_PAGES.saturating_sub(used_pages as u16),
+            page_size: PAGE_SIZE,
+        }
+    }
+
+    /// Get the `wrap::Info` and serialized payload of an object to be
+    /// exported under wrap
+    fn export_obj(&self, object_id: Id, object_type: Type) -> Result<(wrap::Info, Vec<u8>), Error> {
         let object_to_wrap = match self.get(object_id, object_type) {
             Some(o) => o,
             None => fail!(
@@ -254,56 +489,62 @@ impl Objects {
             Origin::WrappedGenerated | Origin::WrappedImported => (),
         }
 
-        let mut wrapped_object = serialize(&WrappedObject {
-            alg_id: wrap_key.algorithm(),
-            object_info: object_info.into(),
-            data: object_to_wrap.payload.to_bytes(),
-        })
-        .unwrap();
-
-        wrap_key
-            .encrypt_in_place(nonce, b"", &mut wrapped_object)
-            .unwrap();
-
-        Ok(wrapped_object)
+        Ok((object_info.into(), object_to_wrap.payload.to_bytes()))
     }
 
-    /// Deserialize an encrypted object and insert it into the HSM
-    pub fn unwrap_obj<V: Into<Vec<u8>>>(
-        &mut self,
-        wrap_key_id: Id,
-        nonce: &wrap::Nonce,
-        ciphertext: V,
-    ) -> Result<Handle, Error> {
-        let wrap_key = self.get_wrap_key(wrap_key_id)?;
-        let mut wrapped_data: Vec<u8> = ciphertext.into();
-        wrap_key.decrypt_in_place(nonce, b"", &mut wrapped_data)?;
-
-        let unwrapped_object: WrappedObject = deserialize(&wrapped_data).unwrap();
-
-        let payload = Payload::new(
-            unwrapped_object.object_info.algorithm,
-            &unwrapped_object.data,
-        );
+    /// Insert an object decrypted from a wrapped export
+    fn import_obj(&mut self, object_info: wrap::Info, data: &[u8]) -> Result<Handle, Error> {
+        let object_key = Handle::new(object_info.object_id, object_info.object_type);
+        self.ensure_not_exists(&object_key)?;
 
-        let object_key = Handle::new(
-            unwrapped_object.object_info.object_id,
-            unwrapped_object.object_info.object_type,
-        );
+        let payload =
+            Payload::from_object_data(object_info.object_type, object_info.algorithm, data)?;
+        self.ensure_free_storage(payload.len())?;
 
         let object = Object {
-            object_info: unwrapped_object.object_info.into(),
+            object_info: object_info.into(),
             payload,
         };
 
-        assert!(self.0.insert(object_key.clone(), object).is_none());
-
+        self.0.insert(object_key.clone(), object);
         Ok(object_key)
     }
 
-    /// Iterate over the objects
-    pub fn iter(&self) -> Iter<'_> {
-        self.0.iter()
+    /// Ensure there's no existing object with the given handle
+    fn ensure_not_exists(&self, handle: &Handle) -> Result<(), Error> {
+        ensure!(
+            !self.0.contains_key(handle),
+            ErrorKind::ObjectExists,
+            "{:?} object {:?} already exists",
+            handle.object_type,
+            handle.object_id
+        );
+
+        Ok(())
+    }
+
+    /// Ensure there's a free record and enough free pages to store an
+    /// object of the given length
+    fn ensure_free_storage(&self, length: u16) -> Result<(), Error> {
+        let storage_info = self.storage_info();
+
+        ensure!(
+            storage_info.free_records > 0,
+            ErrorKind::StorageFull,
+            "all {} object records are in use",
+            TOTAL_RECORDS
+        );
+
+        ensure!(
+            usize::from(storage_info.free_pages) >= storage_pages(length),
+            ErrorKind::StorageFull,
+            "{} pages free, but {} needed for a {}-byte object",
+            storage_info.free_pages,
+            storage_pages(length),
+            length
+        );
+
+        Ok(())
     }
 
     /// Get a wrapping key
@@ -331,5 +572,24 @@ impl Objects {
     }
 }
 
+/// Ensure an unwrapped object is within the domains of the importing session
+fn check_unwrapped_domains(object_info: &wrap::Info, domains: Domain) -> Result<(), Error> {
+    ensure!(
+        domains.contains(object_info.domains),
+        ErrorKind::AccessDenied,
+        "domains {:?} not a subset of the session's domains",
+        object_info.domains
+    );
+
+    Ok(())
+}
+
+/// Number of storage pages used by an object with the given length
+fn storage_pages(length: u16) -> usize {
+    let length = cmp::max(usize::from(length), 1);
+    let page_size = usize::from(PAGE_SIZE);
+    (length + page_size - 1) / page_size
+}
+
 /// Iterator over objects
 pub(crate) type Iter<'a> = MapIter<'a, Handle, Object>;
@@ -1,10 +1,18 @@
 //! Object "payloads" in the MockHsm are instances of software implementations
 //! of supported cryptographic primitives, already initialized with a private key
 
-use crate::{algorithm::Algorithm, asymmetric, authentication, hmac, opaque, wrap};
-use ecdsa::elliptic_curve::sec1::ToEncodedPoint;
+mod rsa_key;
+
+use crate::{
+    algorithm::Algorithm,
+    asymmetric, authentication, hmac,
+    mockhsm::{Error, ErrorKind},
+    object, opaque, otp, symmetric, template, wrap,
+};
+use ecdsa::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
 use ed25519_dalek as ed25519;
 use rand_core::{OsRng, RngCore};
+use rsa::{RsaPrivateKey, RsaPublicKey};
 
 /// Loaded instances of a cryptographic primitives in the MockHsm
 #[derive(Debug)]
@@ -12,9 +20,19 @@ pub(crate) enum Payload {
     /// Authentication key
     AuthenticationKey(authentication::Key),
 
+    /// Asymmetric (EC P-256) authentication public key
+    AuthenticationPublicKey(p256::PublicKey),
+
     /// ECDSA/P-256 signing key
     EcdsaNistP256(p256::SecretKey),
 
+    /// ECDSA/P-384 signing key
+    EcdsaNistP384(p384::SecretKey),
+
+    /// ECDSA/P-521 signing key
+    #[cfg(feature = "nistp521")]
+    EcdsaNistP521(p521::SecretKey),
+
     /// ECDSA/secp256k1 signing key,
     EcdsaSecp256k1(k256::SecretKey),
 
@@ -27,44 +45,148 @@ pub(crate) enum Payload {
     /// Opaque data
     Opaque(opaque::Algorithm, Vec<u8>),
 
+    /// Yubico OTP AEAD key, along with the nonce ID of the AEADs it creates
+    OtpAeadKey(otp::Algorithm, otp::Nonce, Vec<u8>),
+
+    /// RSA public wrap key
+    PublicWrapKey(asymmetric::Algorithm, RsaPublicKey),
+
+    /// RSA private key
+    RsaKey(asymmetric::Algorithm, RsaPrivateKey),
+
+    /// Symmetric (AES) encryption key
+    SymmetricKey(symmetric::Algorithm, Vec<u8>),
+
+    /// Certificate template (i.e. for SSH CA)
+    Template(template::Algorithm, Vec<u8>),
+
     /// Wrapping (i.e. symmetric encryption keys)
     WrapKey(wrap::Algorithm, Vec<u8>),
 }
 
 impl Payload {
     /// Create a new payload from the given algorithm and data
-    pub fn new(algorithm: Algorithm, data: &[u8]) -> Self {
-        match algorithm {
-            Algorithm::Wrap(alg) => Payload::WrapKey(alg, data.into()),
+    pub fn new(algorithm: Algorithm, data: &[u8]) -> Result<Self, Error> {
+        Ok(match algorithm {
+            Algorithm::Wrap(alg) => {
+                ensure_len(data, alg.key_len())?;
+                Payload::WrapKey(alg, data.into())
+            }
             Algorithm::Asymmetric(asymmetric_alg) => match asymmetric_alg {
                 asymmetric::Algorithm::EcP256 => {
-                    assert_eq!(data.len(), 32);
-                    Payload::EcdsaNistP256(p256::SecretKey::from_slice(data).unwrap())
+                    ensure_len(data, 32)?;
+                    Payload::EcdsaNistP256(p256::SecretKey::from_slice(data).map_err(invalid_key)?)
+                }
+                asymmetric::Algorithm::EcP384 => {
+                    ensure_len(data, 48)?;
+                    Payload::EcdsaNistP384(p384::SecretKey::from_slice(data).map_err(invalid_key)?)
+                }
+                #[cfg(feature = "nistp521")]
+                asymmetric::Algorithm::EcP521 => {
+                    ensure_len(data, 66)?;
+                    Payload::EcdsaNistP521(p521::SecretKey::from_slice(data).map_err(invalid_key)?)
                 }
                 asymmetric::Algorithm::EcK256 => {
-                    assert_eq!(data.len(), 32);
-                    Payload::EcdsaSecp256k1(k256::SecretKey::from_slice(data).unwrap())
+                    ensure_len(data, 32)?;
+                    Payload::EcdsaSecp256k1(k256::SecretKey::from_slice(data).map_err(invalid_key)?)
                 }
                 asymmetric::Algorithm::Ed25519 => {
-                    assert_eq!(data.len(), ed25519::SECRET_KEY_LENGTH);
-                    Payload::Ed25519Key(ed25519::SigningKey::try_from(data).unwrap())
+                    ensure_len(data, ed25519::SECRET_KEY_LENGTH)?;
+                    Payload::Ed25519Key(ed25519::SigningKey::try_from(data).map_err(invalid_key)?)
                 }
-                _ => {
-                    panic!("MockHsm doesn't support this asymmetric algorithm: {asymmetric_alg:?}")
+                asymmetric::Algorithm::Rsa2048
+                | asymmetric::Algorithm::Rsa3072
+                | asymmetric::Algorithm::Rsa4096 => {
+                    Payload::RsaKey(asymmetric_alg, rsa_key::from_bytes(asymmetric_alg, data)?)
                 }
+                _ => fail!(
+                    ErrorKind::InvalidData,
+                    "MockHsm doesn't support this asymmetric algorithm: {:?}",
+                    asymmetric_alg
+                ),
             },
             Algorithm::Hmac(alg) => Payload::HmacKey(alg, data.into()),
             Algorithm::Opaque(alg) => Payload::Opaque(alg, data.into()),
+            Algorithm::YubicoOtp(alg) => {
+                // Keys are stored (and exported under wrap) as `nonce_id || key`
+                ensure_len(data, otp::nonce::SIZE + alg.key_len())?;
+                let (nonce_id, key) = data.split_at(otp::nonce::SIZE);
+                Payload::OtpAeadKey(alg, otp::Nonce(nonce_id.try_into().unwrap()), key.into())
+            }
+            Algorithm::Symmetric(alg) => {
+                ensure_len(data, alg.key_len())?;
+                Payload::SymmetricKey(alg, data.into())
+            }
+            Algorithm::Template(alg) => Payload::Template(alg, data.into()),
+            Algorithm::Authentication(authentication::Algorithm::EcP256) => {
+                ensure_len(data, authentication::Algorithm::EcP256.key_len())?;
+                let point = p256::EncodedPoint::from_untagged_bytes(data.into());
+                let public_key = Option::from(p256::PublicKey::from_encoded_point(&point))
+                    .ok_or_else(|| format_err!(ErrorKind::InvalidData, "invalid EC point"))?;
+                Payload::AuthenticationPublicKey(public_key)
+            }
             Algorithm::Authentication(_) => {
+                ensure_len(data, authentication::key::SIZE)?;
                 Payload::AuthenticationKey(authentication::Key::from_slice(data).unwrap())
             }
-            _ => panic!("MockHsm does not support putting {algorithm:?} objects"),
+            _ => fail!(
+                ErrorKind::InvalidData,
+                "MockHsm does not support putting {:?} objects",
+                algorithm
+            ),
+        })
+    }
+
+    /// Create a new payload for an object of the given type
+    pub fn from_object_data(
+        object_type: object::Type,
+        algorithm: Algorithm,
+        data: &[u8],
+    ) -> Result<Self, Error> {
+        // RSA public wrap keys share their algorithms with RSA private keys
+        if object_type == object::Type::PublicWrapKey {
+            return Payload::new_public_wrap_key(algorithm, data);
         }
+
+        let payload = Payload::new(algorithm, data)?;
+
+        // RSA private wrap keys share their payload with RSA private keys
+        let is_rsa_wrap_key =
+            object_type == object::Type::WrapKey && matches!(payload, Payload::RsaKey(..));
+
+        ensure!(
+            is_rsa_wrap_key || payload.object_type() == object_type,
+            ErrorKind::InvalidData,
+            "{:?} is not a valid algorithm for {:?} objects",
+            algorithm,
+            object_type
+        );
+
+        Ok(payload)
     }
 
-    /// Generate a new key with the given algorithm
-    pub fn generate(algorithm: Algorithm) -> Self {
+    /// Create a new RSA public wrap key payload from the given modulus
+    pub fn new_public_wrap_key(algorithm: Algorithm, modulus: &[u8]) -> Result<Self, Error> {
         match algorithm {
+            Algorithm::Asymmetric(
+                asymmetric_alg @ (asymmetric::Algorithm::Rsa2048
+                | asymmetric::Algorithm::Rsa3072
+                | asymmetric::Algorithm::Rsa4096),
+            ) => {
+                let public_key = rsa_key::public_key_from_modulus(asymmetric_alg, modulus)?;
+                Ok(Payload::PublicWrapKey(asymmetric_alg, public_key))
+            }
+            _ => fail!(
+                ErrorKind::InvalidData,
+                "MockHsm does not support {:?} public wrap keys",
+                algorithm
+            ),
+        }
+    }
+
+    /// Generate a new key with the given algorithm
+    pub fn generate(algorithm: Algorithm) -> Result<Self, Error> {
+        Ok(match algorithm {
             Algorithm::Wrap(wrap_alg) => {
                 let mut bytes = vec![0u8; wrap_alg.key_len()];
                 OsRng.fill_bytes(&mut bytes);
@@ -74,23 +196,53 @@ impl Payload {
                 asymmetric::Algorithm::EcP256 => {
                     Payload::EcdsaNistP256(p256::SecretKey::random(&mut OsRng))
                 }
+                asymmetric::Algorithm::EcP384 => {
+                    Payload::EcdsaNistP384(p384::SecretKey::random(&mut OsRng))
+                }
+                #[cfg(feature = "nistp521")]
+                asymmetric::Algorithm::EcP521 => {
+                    Payload::EcdsaNistP521(p521::SecretKey::random(&mut OsRng))
+                }
                 asymmetric::Algorithm::EcK256 => {
                     Payload::EcdsaSecp256k1(k256::SecretKey::random(&mut OsRng))
                 }
                 asymmetric::Algorithm::Ed25519 => {
                     Payload::Ed25519Key(ed25519::SigningKey::generate(&mut OsRng))
                 }
-                _ => {
-                    panic!("MockHsm doesn't support this asymmetric algorithm: {asymmetric_alg:?}")
+                asymmetric::Algorithm::Rsa2048
+                | asymmetric::Algorithm::Rsa3072
+                | asymmetric::Algorithm::Rsa4096 => {
+                    Payload::RsaKey(asymmetric_alg, rsa_key::generate(asymmetric_alg))
                 }
+                _ => fail!(
+                    ErrorKind::InvalidData,
+                    "MockHsm doesn't support this asymmetric algorithm: {:?}",
+                    asymmetric_alg
+                ),
             },
             Algorithm::Hmac(hmac_alg) => {
                 let mut bytes = vec![0u8; hmac_alg.key_len()];
                 OsRng.fill_bytes(&mut bytes);
                 Payload::HmacKey(hmac_alg, bytes)
             }
-            _ => panic!("MockHsm does not support generating {algorithm:?} objects"),
-        }
+            Algorithm::Symmetric(symmetric_alg) => {
+                let mut bytes = vec![0u8; symmetric_alg.key_len()];
+                OsRng.fill_bytes(&mut bytes);
+                Payload::SymmetricKey(symmetric_alg, bytes)
+            }
+            _ => fail!(
+                ErrorKind::InvalidData,
+                "MockHsm does not support generating {:?} objects",
+                algorithm
+            ),
+        })
+    }
+
+    /// Generate a new OTP AEAD key with the given algorithm and nonce ID
+    pub fn generate_otp_aead_key(algorithm: otp::Algorithm, nonce_id: otp::Nonce) -> Self {
+        let mut bytes = vec![0u8; algorithm.key_len()];
+        OsRng.fill_bytes(&mut bytes);
+        Payload::OtpAeadKey(algorithm, nonce_id, bytes)
     }
 
     /// Get the algorithm type for this payload
@@ -99,23 +251,60 @@ impl Payload {
             Payload::AuthenticationKey(_) => {
                 Algorithm::Authentication(authentication::Algorithm::YubicoAes)
             }
+            Payload::AuthenticationPublicKey(_) => {
+                Algorithm::Authentication(authentication::Algorithm::EcP256)
+            }
             Payload::EcdsaNistP256(_) => Algorithm::Asymmetric(asymmetric::Algorithm::EcP256),
+            Payload::EcdsaNistP384(_) => Algorithm::Asymmetric(asymmetric::Algorithm::EcP384),
+            #[cfg(feature = "nistp521")]
+            Payload::EcdsaNistP521(_) => Algorithm::Asymmetric(asymmetric::Algorithm::EcP521),
             Payload::EcdsaSecp256k1(_) => Algorithm::Asymmetric(asymmetric::Algorithm::EcK256),
             Payload::Ed25519Key(_) => Algorithm::Asymmetric(asymmetric::Algorithm::Ed25519),
             Payload::HmacKey(alg, _) => alg.into(),
             Payload::Opaque(alg, _) => alg.into(),
+            Payload::OtpAeadKey(alg, _, _) => alg.into(),
+            Payload::PublicWrapKey(alg, _) => alg.into(),
+            Payload::RsaKey(alg, _) => alg.into(),
+            Payload::SymmetricKey(alg, _) => alg.into(),
+            Payload::Template(alg, _) => alg.into(),
             Payload::WrapKey(alg, _) => alg.into(),
         }
     }
 
+    /// Get the type of object this payload can be stored as
+    pub fn object_type(&self) -> object::Type {
+        match self {
+            Payload::AuthenticationKey(_) | Payload::AuthenticationPublicKey(_) => {
+                object::Type::AuthenticationKey
+            }
+            Payload::HmacKey(..) => object::Type::HmacKey,
+            Payload::Opaque(..) => object::Type::Opaque,
+            Payload::OtpAeadKey(..) => object::Type::OtpAeadKey,
+            Payload::PublicWrapKey(..) => object::Type::PublicWrapKey,
+            Payload::SymmetricKey(..) => object::Type::SymmetricKey,
+            Payload::Template(..) => object::Type::Template,
+            Payload::WrapKey(..) => object::Type::WrapKey,
+            _ => object::Type::AsymmetricKey,
+        }
+    }
+
     /// Get the length of the object
     pub fn len(&self) -> u16 {
         let l = match self {
             Payload::AuthenticationKey(_) => authentication::key::SIZE,
+            Payload::AuthenticationPublicKey(_) => authentication::Algorithm::EcP256.key_len(),
             Payload::EcdsaNistP256(_) | Payload::EcdsaSecp256k1(_) => 32,
+            Payload::EcdsaNistP384(_) => 48,
+            #[cfg(feature = "nistp521")]
+            Payload::EcdsaNistP521(_) => 66,
             Payload::Ed25519Key(_) => ed25519::SECRET_KEY_LENGTH,
             Payload::HmacKey(_, ref data) => data.len(),
             Payload::Opaque(_, ref data) => data.len(),
+            Payload::OtpAeadKey(_, _, ref key) => otp::nonce::SIZE + key.len(),
+            Payload::PublicWrapKey(alg, _) => alg.key_len(),
+            Payload::RsaKey(alg, _) => rsa_key::serialized_len(*alg),
+            Payload::SymmetricKey(_, ref data) => data.len(),
+            Payload::Template(_, ref data) => data.len(),
             Payload::WrapKey(_, ref data) => data.len(),
         };
         l as u16
@@ -127,10 +316,21 @@ impl Payload {
             Payload::EcdsaNistP256(secret_key) => {
                 Some(secret_key.public_key().to_encoded_point(false).as_bytes()[1..].into())
             }
+            Payload::EcdsaNistP384(secret_key) => {
+                Some(secret_key.public_key().to_encoded_point(false).as_bytes()[1..].into())
+            }
+            #[cfg(feature = "nistp521")]
+            Payload::EcdsaNistP521(secret_key) => {
+                Some(secret_key.public_key().to_encoded_point(false).as_bytes()[1..].into())
+            }
             Payload::EcdsaSecp256k1(secret_key) => {
                 Some(secret_key.public_key().to_encoded_point(false).as_bytes()[1..].into())
             }
             Payload::Ed25519Key(signing_key) => Some(signing_key.verifying_key().to_bytes().into()),
+            Payload::PublicWrapKey(alg, public_key) => {
+                Some(rsa_key::modulus_bytes(*alg, public_key))
+            }
+            Payload::RsaKey(alg, private_key) => Some(rsa_key::modulus_bytes(*alg, private_key)),
             _ => None,
         }
     }
@@ -143,16 +343,67 @@ impl Payload {
         }
     }
 
+    /// If this payload is an asymmetric auth key, return a reference to it
+    pub fn asymmetric_authentication_key(&self) -> Option<&p256::PublicKey> {
+        match *self {
+            Payload::AuthenticationPublicKey(ref k) => Some(k),
+            _ => None,
+        }
+    }
+
     /// Serialize this payload as a byte vector
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
             Payload::AuthenticationKey(k) => k.0.as_ref().into(),
+            Payload::AuthenticationPublicKey(k) => k.to_encoded_point(false).as_bytes()[1..].into(),
             Payload::EcdsaNistP256(k) => k.to_bytes().to_vec(),
+            Payload::EcdsaNistP384(k) => k.to_bytes().to_vec(),
+            #[cfg(feature = "nistp521")]
+            Payload::EcdsaNistP521(k) => k.to_bytes().to_vec(),
             Payload::EcdsaSecp256k1(k) => k.to_bytes().to_vec(),
-            Payload::Ed25519Key(k) => k.verifying_key().to_bytes().into(),
+            Payload::Ed25519Key(k) => k.to_bytes().into(),
             Payload::HmacKey(_, data) => data.clone(),
             Payload::Opaque(_, data) => data.clone(),
+            Payload::OtpAeadKey(_, nonce_id, key) => [nonce_id.as_ref(), key].concat(),
+            Payload::PublicWrapKey(alg, k) => rsa_key::modulus_bytes(*alg, k),
+            Payload::RsaKey(alg, k) => rsa_key::to_bytes(*alg, k),
+            Payload::SymmetricKey(_, data) => data.clone(),
+            Payload::Template(_, data) => data.clone(),
             Payload::WrapKey(_, data) => data.clone(),
         }
     }
+
+    /// If this payload is an RSA key, return a reference to it
+    pub fn rsa_key(&self) -> Option<&RsaPrivateKey> {
+        match *self {
+            Payload::RsaKey(_, ref k) => Some(k),
+            _ => None,
+        }
+    }
+
+    /// If this payload is an RSA public wrap key, return a reference to it
+    pub fn public_wrap_key(&self) -> Option<&RsaPublicKey> {
+        match *self {
+            Payload::PublicWrapKey(_, ref k) => Some(k),
+            _ => None,
+        }
+    }
+}
+
+/// Ensure key data has the expected length
+fn ensure_len(data: &[u8], expected_len: usize) -> Result<(), Error> {
+    ensure!(
+        data.len() == expected_len,
+        ErrorKind::WrongLength,
+        "expected {}-byte key (got {})",
+        expected_len,
+        data.len()
+    );
+
+    Ok(())
+}
+
+/// Error for key material which is malformed (e.g. out of range)
+fn invalid_key(e: impl std::fmt::Display) -> Error {
+    format_err!(ErrorKind::InvalidData, "invalid key: {}", e).into()
 }
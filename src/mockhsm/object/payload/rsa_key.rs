@@ -0,0 +1,126 @@
+//! RSA key payloads in the MockHsm, serialized in the formats used by the
+//! `YubiHSM 2` to put and wrap them
+
+use super::invalid_key;
+use crate::{
+    asymmetric,
+    mockhsm::{Error, ErrorKind},
+};
+use num_bigint::traits::ModInverse;
+use rand_core::OsRng;
+use rsa::{
+    traits::{PrivateKeyParts, PublicKeyParts},
+    BigUint, RsaPrivateKey, RsaPublicKey,
+};
+
+/// RSA public exponent used by the YubiHSM 2
+const EXPONENT: u32 = 65537;
+
+/// Generate a new RSA private key
+pub(super) fn generate(alg: asymmetric::Algorithm) -> RsaPrivateKey {
+    RsaPrivateKey::new(&mut OsRng, alg.key_len() * 8).unwrap()
+}
+
+/// Parse an RSA private key.
+///
+/// Keys are put as `p || q`, and imported under wrap in the format
+/// serialized by [`to_bytes`], which begins the same.
+pub(super) fn from_bytes(alg: asymmetric::Algorithm, data: &[u8]) -> Result<RsaPrivateKey, Error> {
+    ensure!(
+        data.len() == alg.key_len() || data.len() == serialized_len(alg),
+        ErrorKind::WrongLength,
+        "invalid {:?} key length: {}",
+        alg,
+        data.len()
+    );
+
+    let component_size = alg.key_len() / 2;
+    let (p, rest) = data.split_at(component_size);
+    let q = &rest[..component_size];
+
+    from_primes(BigUint::from_bytes_be(p), BigUint::from_bytes_be(q))
+}
+
+/// Parse an RSA public key from its modulus
+pub(super) fn public_key_from_modulus(
+    alg: asymmetric::Algorithm,
+    modulus: &[u8],
+) -> Result<RsaPublicKey, Error> {
+    ensure!(
+        modulus.len() == alg.key_len(),
+        ErrorKind::WrongLength,
+        "expected {}-byte modulus (got {})",
+        alg.key_len(),
+        modulus.len()
+    );
+
+    RsaPublicKey::new(BigUint::from_bytes_be(modulus), BigUint::from(EXPONENT)).map_err(invalid_key)
+}
+
+/// Serialize the modulus of an RSA key, which is how the `YubiHSM 2`
+/// represents RSA public keys
+pub(super) fn modulus_bytes(alg: asymmetric::Algorithm, key: &impl PublicKeyParts) -> Vec<u8> {
+    to_be_bytes_padded(key.n(), alg.key_len())
+}
+
+/// Size of an RSA key serialized as `p || q || dp || dq || qinv || n`
+pub(super) fn serialized_len(alg: asymmetric::Algorithm) -> usize {
+    let modulus_size = alg.key_len();
+    (modulus_size / 2) * 5 + modulus_size
+}
+
+/// Serialize an RSA key in the YubiHSM's wrapped object format:
+/// `p || q || dp || dq || qinv || n`
+pub(super) fn to_bytes(alg: asymmetric::Algorithm, key: &RsaPrivateKey) -> Vec<u8> {
+    let modulus_size = alg.key_len();
+    let component_size = modulus_size / 2;
+    let primes = key.primes();
+    let qinv = key
+        .crt_coefficient()
+        .expect("RSA key missing CRT coefficient");
+
+    let mut bytes = Vec::with_capacity(serialized_len(alg));
+
+    for component in [
+        &primes[0],
+        &primes[1],
+        key.dp().expect("RSA key missing dP"),
+        key.dq().expect("RSA key missing dQ"),
+        &qinv,
+    ] {
+        bytes.extend_from_slice(&to_be_bytes_padded(component, component_size));
+    }
+
+    bytes.extend_from_slice(&modulus_bytes(alg, key));
+    bytes
+}
+
+/// Reconstruct an RSA private key from its prime factors
+fn from_primes(p: BigUint, q: BigUint) -> Result<RsaPrivateKey, Error> {
+    let one = BigUint::from(1u32);
+    ensure!(
+        p > one && q > one,
+        ErrorKind::InvalidData,
+        "invalid RSA primes"
+    );
+
+    let e = BigUint::from(EXPONENT);
+    let n = &p * &q;
+    let d = e
+        .clone()
+        .mod_inverse((&p - &one) * (&q - &one))
+        .and_then(|d| d.to_biguint())
+        .ok_or_else(|| format_err!(ErrorKind::InvalidData, "invalid RSA primes"))?;
+
+    RsaPrivateKey::from_components(n, e, d, vec![p, q]).map_err(invalid_key)
+}
+
+/// Serialize a big integer as big endian bytes, left-padded with zeroes
+fn to_be_bytes_padded(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    assert!(bytes.len() <= len, "integer too large: {}", bytes.len());
+
+    let mut result = vec![0u8; len - bytes.len()];
+    result.extend_from_slice(&bytes);
+    result
+}
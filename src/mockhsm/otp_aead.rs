@@ -0,0 +1,174 @@
+//! Yubico OTP AEADs created and decrypted by the MockHsm.
+//!
+//! AEADs contain a YubiKey's OTP key and private ID encrypted with AES-CCM
+//! (with an 8-byte MAC) under an OTP AEAD key. The CCM nonce is the key's
+//! nonce ID followed by the AEAD's random 6-byte nonce, padded with zeroes.
+
+use super::object::Payload;
+use crate::{device, otp};
+use aes::cipher::{
+    consts::{U13, U16, U8},
+    generic_array::GenericArray,
+    BlockCipher, BlockDecrypt, BlockEncrypt, BlockSizeUser, KeyInit,
+};
+use ccm::aead::AeadInPlace;
+use rand_core::{OsRng, RngCore};
+
+/// Size of the random nonce at the beginning of an AEAD
+const NONCE_SIZE: usize = 6;
+
+/// Size of the MAC at the end of an AEAD
+const MAC_SIZE: usize = 8;
+
+/// Residue of the CRC-16 of a valid OTP, including its own CRC
+const OTP_CRC_RESIDUE: u16 = 0xf0b8;
+
+/// Create an AEAD containing the given OTP key and private ID
+pub(super) fn create_aead(
+    key: &Payload,
+    otp_key: &[u8; otp::KEY_SIZE],
+    private_id: &otp::PrivateId,
+) -> Result<otp::Aead, device::ErrorKind> {
+    let (algorithm, nonce_id, key_bytes) = otp_aead_key(key)?;
+
+    let mut aead = [0u8; otp::aead::SIZE];
+    OsRng.fill_bytes(&mut aead[..NONCE_SIZE]);
+
+    let (nonce, rest) = aead.split_at_mut(NONCE_SIZE);
+    let (buffer, mac) = rest.split_at_mut(otp::KEY_SIZE + otp::private_id::SIZE);
+    buffer[..otp::KEY_SIZE].copy_from_slice(otp_key);
+    buffer[otp::KEY_SIZE..].copy_from_slice(private_id.as_ref());
+
+    let ccm_nonce = ccm_nonce(nonce_id, nonce);
+    let tag = match algorithm {
+        otp::Algorithm::Aes128 => encrypt::<aes::Aes128>(key_bytes, &ccm_nonce, buffer),
+        otp::Algorithm::Aes192 => encrypt::<aes::Aes192>(key_bytes, &ccm_nonce, buffer),
+        otp::Algorithm::Aes256 => encrypt::<aes::Aes256>(key_bytes, &ccm_nonce, buffer),
+    };
+
+    mac.copy_from_slice(&tag);
+    Ok(otp::Aead(aead))
+}
+
+/// Decrypt an AEAD, returning the OTP key and private ID it contains
+pub(super) fn decrypt_aead(
+    key: &Payload,
+    aead: &otp::Aead,
+) -> Result<([u8; otp::KEY_SIZE], otp::PrivateId), device::ErrorKind> {
+    let (algorithm, nonce_id, key_bytes) = otp_aead_key(key)?;
+
+    let (nonce, rest) = aead.0.split_at(NONCE_SIZE);
+    let (ciphertext, mac) = rest.split_at(rest.len() - MAC_SIZE);
+    let mut buffer = ciphertext.to_vec();
+
+    let ccm_nonce = ccm_nonce(nonce_id, nonce);
+    let result = match algorithm {
+        otp::Algorithm::Aes128 => decrypt::<aes::Aes128>(key_bytes, &ccm_nonce, &mut buffer, mac),
+        otp::Algorithm::Aes192 => decrypt::<aes::Aes192>(key_bytes, &ccm_nonce, &mut buffer, mac),
+        otp::Algorithm::Aes256 => decrypt::<aes::Aes256>(key_bytes, &ccm_nonce, &mut buffer, mac),
+    };
+
+    if !result {
+        debug!("OTP AEAD MAC mismatch");
+        return Err(device::ErrorKind::InvalidData);
+    }
+
+    let mut otp_key = [0u8; otp::KEY_SIZE];
+    let mut private_id = [0u8; otp::private_id::SIZE];
+    otp_key.copy_from_slice(&buffer[..otp::KEY_SIZE]);
+    private_id.copy_from_slice(&buffer[otp::KEY_SIZE..]);
+
+    Ok((otp_key, private_id.into()))
+}
+
+/// Decrypt an OTP using the OTP key and private ID contained in an AEAD
+pub(super) fn decrypt_otp(
+    key: &Payload,
+    aead: &otp::Aead,
+    otp: &[u8; otp::OTP_SIZE],
+) -> Result<otp::DecryptedOtp, device::ErrorKind> {
+    let (otp_key, private_id) = decrypt_aead(key, aead)?;
+
+    let mut token = GenericArray::clone_from_slice(otp);
+    aes::Aes128::new(&otp_key.into()).decrypt_block(&mut token);
+
+    if crc16(&token) != OTP_CRC_RESIDUE {
+        debug!("OTP CRC mismatch");
+        return Err(device::ErrorKind::InvalidOtp);
+    }
+
+    if token[..otp::private_id::SIZE] != private_id.0 {
+        debug!("OTP private ID doesn't match AEAD");
+        return Err(device::ErrorKind::InvalidOtp);
+    }
+
+    Ok(otp::DecryptedOtp {
+        use_counter: u16::from_le_bytes([token[6], token[7]]),
+        session_counter: token[11],
+        timestamp: u32::from(token[10]) << 16 | u32::from(u16::from_le_bytes([token[8], token[9]])),
+    })
+}
+
+/// Get the algorithm, nonce ID, and key of an OTP AEAD key
+fn otp_aead_key(key: &Payload) -> Result<(otp::Algorithm, &otp::Nonce, &[u8]), device::ErrorKind> {
+    match key {
+        Payload::OtpAeadKey(algorithm, nonce_id, key_bytes) => {
+            Ok((*algorithm, nonce_id, key_bytes))
+        }
+        _ => {
+            debug!("not an OTP AEAD key: {:?}", key.algorithm());
+            Err(device::ErrorKind::InvalidCommand)
+        }
+    }
+}
+
+/// Compute the CCM nonce for an AEAD
+fn ccm_nonce(nonce_id: &otp::Nonce, nonce: &[u8]) -> GenericArray<u8, U13> {
+    let mut ccm_nonce = GenericArray::default();
+    ccm_nonce[..otp::nonce::SIZE].copy_from_slice(nonce_id.as_ref());
+    ccm_nonce[otp::nonce::SIZE..otp::nonce::SIZE + NONCE_SIZE].copy_from_slice(nonce);
+    ccm_nonce
+}
+
+/// Encrypt the contents of an AEAD in-place, returning its MAC
+fn encrypt<C>(key: &[u8], nonce: &GenericArray<u8, U13>, buffer: &mut [u8]) -> GenericArray<u8, U8>
+where
+    C: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+{
+    ccm::Ccm::<C, U8, U13>::new_from_slice(key)
+        .unwrap()
+        .encrypt_in_place_detached(nonce, b"", buffer)
+        .expect("OTP AEAD encryption failure!")
+}
+
+/// Decrypt the contents of an AEAD in-place, returning whether its MAC
+/// is valid
+fn decrypt<C>(key: &[u8], nonce: &GenericArray<u8, U13>, buffer: &mut [u8], mac: &[u8]) -> bool
+where
+    C: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+{
+    ccm::Ccm::<C, U8, U13>::new_from_slice(key)
+        .unwrap()
+        .decrypt_in_place_detached(nonce, b"", buffer, GenericArray::from_slice(mac))
+        .is_ok()
+}
+
+/// CRC-16 (ISO 13239) as used by YubiKey OTPs
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+
+    for byte in data {
+        crc ^= u16::from(*byte);
+
+        for _ in 0..8 {
+            let carry = crc & 1;
+            crc >>= 1;
+
+            if carry != 0 {
+                crc ^= 0x8408;
+            }
+        }
+    }
+
+    crc
+}
@@ -1,13 +1,13 @@
 //! Sessions with the `MockHsm`
 
-use std::fmt::{self, Debug};
+use std::{
+    fmt::{self, Debug},
+    time::Instant,
+};
 
 use crate::{
-    command, response,
-    session::{
-        securechannel::{Challenge, Cryptogram, SecureChannel},
-        Id,
-    },
+    command, object, response,
+    session::{self, securechannel::SecureChannel, Id},
 };
 
 /// Session with the `MockHsm`
@@ -15,36 +15,38 @@ pub(crate) struct HsmSession {
     /// ID of the session
     pub id: Id,
 
-    /// Card challenge for this session
-    pub card_challenge: Challenge,
+    /// ID of the authentication key used to establish this session
+    pub authentication_key_id: object::Id,
 
     /// Encrypted channel
     pub channel: SecureChannel,
+
+    /// Time of the last message received in this session
+    pub last_active: Instant,
 }
 
 impl HsmSession {
     /// Create a new session
-    pub fn new(id: Id, card_challenge: Challenge, channel: SecureChannel) -> Self {
+    pub fn new(
+        id: Id,
+        authentication_key_id: object::Id,
+        channel: SecureChannel,
+        now: Instant,
+    ) -> Self {
         Self {
             id,
-            card_challenge,
+            authentication_key_id,
             channel,
+            last_active: now,
         }
     }
 
-    /// Get the card challenge for this session
-    pub fn card_challenge(&self) -> &Challenge {
-        &self.card_challenge
-    }
-
-    /// Get the card cryptogram for this session
-    pub fn card_cryptogram(&self) -> Cryptogram {
-        self.channel.card_cryptogram()
-    }
-
     /// Decrypt an incoming command
-    pub fn decrypt_command(&mut self, command: command::Message) -> command::Message {
-        self.channel.decrypt_command(command).unwrap()
+    pub fn decrypt_command(
+        &mut self,
+        command: command::Message,
+    ) -> Result<command::Message, session::Error> {
+        self.channel.decrypt_command(command)
     }
 
     /// Encrypt an outgoing response
@@ -0,0 +1,337 @@
+//! Snapshots of the persistent state of the `MockHsm`, i.e. its objects,
+//! device-wide options and device identity (serial number, firmware version
+//! and the device's own keys), which can be saved to and loaded from disk.
+//!
+//! Snapshots begin with a magic string and a format version, followed by a
+//! sequence of tag-length-value records (with a big endian 16-bit length),
+//! similar to the option records used by the `SetOption` command.
+
+use super::{
+    audit::CommandAuditOptions,
+    object::{Object, Objects, Payload},
+    state::State,
+    Error, ErrorKind,
+};
+use crate::{
+    algorithm::Algorithm,
+    audit::{AuditCommand, AuditOption},
+    command::Code,
+    device::SerialNumber,
+    object,
+    serialization::{deserialize, serialize},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Magic string identifying `MockHsm` snapshots
+const MAGIC: &[u8] = b"MOCKHSM\0";
+
+/// Version of the snapshot format
+const VERSION: u8 = 1;
+
+/// Record containing the force audit setting
+const FORCE_AUDIT_TAG: u8 = 0x01;
+
+/// Record containing the per-command audit settings
+const COMMAND_AUDIT_TAG: u8 = 0x02;
+
+/// Record containing the FIPS mode setting
+const FIPS_TAG: u8 = 0x03;
+
+/// Record containing an object
+const OBJECT_TAG: u8 = 0x04;
+
+/// Record containing the serial number of the device
+const SERIAL_NUMBER_TAG: u8 = 0x05;
+
+/// Record containing the firmware version of the device
+const FIRMWARE_VERSION_TAG: u8 = 0x06;
+
+/// Record containing the device private key
+const DEVICE_KEY_TAG: u8 = 0x07;
+
+/// Record containing the device attestation key, prefixed by its algorithm
+const ATTESTATION_KEY_TAG: u8 = 0x08;
+
+/// Record containing the certificate for the device attestation key
+const ATTESTATION_CERTIFICATE_TAG: u8 = 0x09;
+
+/// Persistent state of the `MockHsm`
+#[derive(Debug)]
+pub(crate) struct Snapshot {
+    /// Command-specific audit options
+    pub command_audit_options: CommandAuditOptions,
+
+    /// Force audit setting
+    pub force_audit: AuditOption,
+
+    /// FIPS mode setting
+    pub fips: AuditOption,
+
+    /// Objects within the `MockHsm`
+    pub objects: Objects,
+
+    /// Serial number of the device
+    pub serial_number: SerialNumber,
+
+    /// Firmware version of the device (major, minor, build)
+    pub firmware_version: (u8, u8, u8),
+
+    /// Device private key, used for asymmetric authentication
+    pub device_key: p256::SecretKey,
+
+    /// Device attestation key
+    pub attestation_key: Payload,
+
+    /// Self-signed certificate for the device attestation key
+    pub attestation_certificate: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Parse a serialized snapshot
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        ensure!(
+            bytes.len() > MAGIC.len() && bytes.starts_with(MAGIC),
+            ErrorKind::ParseError,
+            "not a MockHsm snapshot"
+        );
+
+        let version = bytes[MAGIC.len()];
+
+        ensure!(
+            version == VERSION,
+            ErrorKind::ParseError,
+            "unsupported snapshot version: {} (expected {})",
+            version,
+            VERSION
+        );
+
+        let mut command_audit_options = CommandAuditOptions::default();
+        let mut force_audit = AuditOption::Off;
+        let mut fips = AuditOption::Off;
+        let mut objects = vec![];
+        let mut serial_number = None;
+        let mut firmware_version = None;
+        let mut device_key = None;
+        let mut attestation_key = None;
+        let mut attestation_certificate = None;
+        let mut remaining = &bytes[MAGIC.len() + 1..];
+
+        while !remaining.is_empty() {
+            ensure!(
+                remaining.len() >= 3,
+                ErrorKind::ParseError,
+                "truncated snapshot record"
+            );
+
+            let tag = remaining[0];
+            let length = usize::from(u16::from_be_bytes([remaining[1], remaining[2]]));
+
+            ensure!(
+                remaining.len() >= 3 + length,
+                ErrorKind::ParseError,
+                "truncated snapshot record: tag 0x{:02x}",
+                tag
+            );
+
+            let value = &remaining[3..3 + length];
+            remaining = &remaining[3 + length..];
+
+            match tag {
+                FORCE_AUDIT_TAG => force_audit = parse_audit_option(value)?,
+                COMMAND_AUDIT_TAG => {
+                    let audit_commands: Vec<AuditCommand> = deserialize(value)
+                        .map_err(|e| format_err!(ErrorKind::ParseError, "{}", e))?;
+
+                    for audit_command in audit_commands {
+                        command_audit_options
+                            .put(audit_command.command_type(), audit_command.audit_option());
+                    }
+                }
+                FIPS_TAG => fips = parse_audit_option(value)?,
+                OBJECT_TAG => {
+                    let StoredObject { object_info, data } = deserialize(value)
+                        .map_err(|e| format_err!(ErrorKind::ParseError, "{}", e))?;
+
+                    let payload = Payload::from_object_data(
+                        object_info.object_type,
+                        object_info.algorithm,
+                        &data,
+                    )?;
+
+                    objects.push(Object {
+                        object_info,
+                        payload,
+                    });
+                }
+                SERIAL_NUMBER_TAG => {
+                    serial_number = Some(
+                        deserialize(value)
+                            .map_err(|e| format_err!(ErrorKind::ParseError, "{}", e))?,
+                    );
+                }
+                FIRMWARE_VERSION_TAG => match *value {
+                    [major, minor, build] => firmware_version = Some((major, minor, build)),
+                    _ => fail!(
+                        ErrorKind::ParseError,
+                        "invalid firmware version length: {}",
+                        value.len()
+                    ),
+                },
+                DEVICE_KEY_TAG => {
+                    device_key = Some(
+                        p256::SecretKey::from_slice(value)
+                            .map_err(|e| format_err!(ErrorKind::ParseError, "{}", e))?,
+                    );
+                }
+                ATTESTATION_KEY_TAG => {
+                    ensure!(
+                        !value.is_empty(),
+                        ErrorKind::ParseError,
+                        "empty attestation key record"
+                    );
+
+                    let algorithm = Algorithm::from_u8(value[0])
+                        .map_err(|e| format_err!(ErrorKind::ParseError, "{}", e))?;
+
+                    attestation_key = Some(Payload::new(algorithm, &value[1..])?);
+                }
+                ATTESTATION_CERTIFICATE_TAG => attestation_certificate = Some(value.to_vec()),
+                _ => fail!(
+                    ErrorKind::ParseError,
+                    "invalid snapshot record tag: 0x{:02x}",
+                    tag
+                ),
+            }
+        }
+
+        Ok(Snapshot {
+            command_audit_options,
+            force_audit,
+            fips,
+            objects: objects.into_iter().collect(),
+            serial_number: required(serial_number, "serial number")?,
+            firmware_version: required(firmware_version, "firmware version")?,
+            device_key: required(device_key, "device key")?,
+            attestation_key: required(attestation_key, "attestation key")?,
+            attestation_certificate: required(attestation_certificate, "attestation certificate")?,
+        })
+    }
+}
+
+/// Save a snapshot of the given state to the given path.
+///
+/// The snapshot is written to a temporary file which is then renamed over
+/// the given path, so readers never observe a partially written snapshot.
+pub(crate) fn write(state: &State, path: &Path) -> Result<(), Error> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    fs::write(&tmp_path, to_bytes(state))?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Does the given command (when successful) modify the state saved in
+/// snapshots?
+pub(crate) fn is_modified_by(command_type: Code) -> bool {
+    matches!(
+        command_type,
+        Code::ChangeAuthenticationKey
+            | Code::DeleteObject
+            | Code::GenerateAsymmetricKey
+            | Code::GenerateHmacKey
+            | Code::GenerateOtpAead
+            | Code::GenerateSymmetricKey
+            | Code::GenerateWrapKey
+            | Code::ImportWrapped
+            | Code::ImportWrappedRsa
+            | Code::PutAsymmetricKey
+            | Code::PutAuthenticationKey
+            | Code::PutHmacKey
+            | Code::PutOpaqueObject
+            | Code::PutOtpAead
+            | Code::PutPublicWrapKey
+            | Code::PutSymmetricKey
+            | Code::PutTemplate
+            | Code::PutWrapKey
+            | Code::ResetDevice
+            | Code::SetOption
+    )
+}
+
+/// Serialize a snapshot of the persistent parts of the given state
+pub(crate) fn to_bytes(state: &State) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+
+    push_record(&mut bytes, FORCE_AUDIT_TAG, &[state.force_audit.to_u8()]);
+    push_record(
+        &mut bytes,
+        COMMAND_AUDIT_TAG,
+        &state.command_audit_options.serialize(),
+    );
+    push_record(&mut bytes, FIPS_TAG, &[state.fips.to_u8()]);
+    push_record(
+        &mut bytes,
+        SERIAL_NUMBER_TAG,
+        &serialize(&state.serial_number).unwrap(),
+    );
+
+    let (major, minor, build) = state.firmware_version;
+    push_record(&mut bytes, FIRMWARE_VERSION_TAG, &[major, minor, build]);
+    push_record(&mut bytes, DEVICE_KEY_TAG, &state.device_key.to_bytes());
+
+    let mut attestation_key = vec![state.attestation_key.algorithm().to_u8()];
+    attestation_key.extend_from_slice(&state.attestation_key.to_bytes());
+    push_record(&mut bytes, ATTESTATION_KEY_TAG, &attestation_key);
+    push_record(
+        &mut bytes,
+        ATTESTATION_CERTIFICATE_TAG,
+        &state.attestation_certificate,
+    );
+
+    for (_, object) in state.objects.iter() {
+        let stored_object = StoredObject {
+            object_info: object.object_info.clone(),
+            data: object.payload.to_bytes(),
+        };
+
+        push_record(&mut bytes, OBJECT_TAG, &serialize(&stored_object).unwrap());
+    }
+
+    bytes
+}
+
+/// An object as stored in a snapshot
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredObject {
+    object_info: object::Info,
+    data: Vec<u8>,
+}
+
+/// Append a tag-length-value record to a snapshot
+fn push_record(bytes: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    let length = u16::try_from(value.len()).expect("snapshot record too long");
+
+    bytes.push(tag);
+    bytes.extend_from_slice(&length.to_be_bytes());
+    bytes.extend_from_slice(value);
+}
+
+/// Ensure a record which must be present in every snapshot was found
+fn required<T>(value: Option<T>, name: &str) -> Result<T, Error> {
+    value.ok_or_else(|| format_err!(ErrorKind::ParseError, "snapshot missing {}", name).into())
+}
+
+/// Parse a single-byte audit option record
+fn parse_audit_option(value: &[u8]) -> Result<AuditOption, Error> {
+    ensure!(
+        value.len() == 1,
+        ErrorKind::ParseError,
+        "invalid audit option length: {}",
+        value.len()
+    );
+
+    AuditOption::from_u8(value[0]).map_err(|e| format_err!(ErrorKind::ParseError, "{}", e).into())
+}
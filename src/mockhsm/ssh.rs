@@ -0,0 +1,416 @@
+//! SSH certificates issued by the MockHsm.
+//!
+//! Certificate templates are a sequence of tag-length-value records (with a
+//! big endian 16-bit length). They contain the RSA "timestamp key" which
+//! certificate requests must be signed with, the IDs of the CA keys allowed
+//! to sign certificates, how far certificates may be valid before and after
+//! the request's timestamp, and the principals which may not be certified.
+//!
+//! Requests consist of a RSASSA-PKCS#1v1.5 (SHA-256) signature over the
+//! timestamp and certificate made with the timestamp key, followed by the
+//! certificate in the OpenSSH format, minus its trailing signature:
+//!
+//! <https://github.com/openssh/openssh-portable/blob/master/PROTOCOL.certkeys>
+
+use super::object::Payload;
+use crate::{algorithm::Algorithm, asymmetric, device, ecdsa, object, rsa};
+use ::ecdsa::elliptic_curve::sec1::ToEncodedPoint;
+use ::rsa::{traits::PublicKeyParts, BigUint, Pkcs1v15Sign, RsaPublicKey};
+use sha2::{Digest, Sha256, Sha512};
+use signature::Signer;
+
+/// Record containing the algorithm of the timestamp key
+const TIMESTAMP_KEY_ALGORITHM_TAG: u8 = 0x01;
+
+/// Record containing the modulus of the timestamp key
+const TIMESTAMP_KEY_TAG: u8 = 0x02;
+
+/// Record containing the IDs of the CA keys allowed to sign certificates
+const CA_KEY_IDS_TAG: u8 = 0x03;
+
+/// Record containing how many seconds before the request's timestamp
+/// certificates may become valid
+const NOT_BEFORE_TAG: u8 = 0x04;
+
+/// Record containing how many seconds after the request's timestamp
+/// certificates may remain valid
+const NOT_AFTER_TAG: u8 = 0x05;
+
+/// Record containing NUL-separated principals which may not be certified
+const PRINCIPALS_BLOCKLIST_TAG: u8 = 0x06;
+
+/// RSA public exponent of timestamp keys
+const RSA_EXPONENT: u32 = 65537;
+
+/// Sign an SSH certificate request with the given CA key, after checking
+/// the request against the given template
+pub(super) fn sign_certificate(
+    template: &[u8],
+    key_id: object::Id,
+    key: &Payload,
+    algorithm: Algorithm,
+    timestamp: u32,
+    signed_request: &[u8],
+) -> Result<Vec<u8>, device::ErrorKind> {
+    let template = Template::parse(template).ok_or_else(|| {
+        debug!("invalid SSH certificate template");
+        device::ErrorKind::InvalidData
+    })?;
+
+    let signature_len = template.timestamp_key.size();
+
+    if signed_request.len() <= signature_len {
+        debug!(
+            "SSH certificate request too short: {} bytes",
+            signed_request.len()
+        );
+        return Err(device::ErrorKind::InvalidData);
+    }
+
+    let (timestamp_signature, request) = signed_request.split_at(signature_len);
+    let signed_data = [&timestamp.to_be_bytes()[..], request].concat();
+
+    if template
+        .timestamp_key
+        .verify(
+            Pkcs1v15Sign::new::<Sha256>(),
+            &Sha256::digest(&signed_data),
+            timestamp_signature,
+        )
+        .is_err()
+    {
+        debug!("invalid SSH certificate request timestamp signature");
+        return Err(device::ErrorKind::SshCaConstraintViolation);
+    }
+
+    if !template.ca_key_ids.contains(&key_id) {
+        debug!("key not allowed by SSH certificate template: {:?}", key_id);
+        return Err(device::ErrorKind::SshCaConstraintViolation);
+    }
+
+    let fields = Request::parse(request).ok_or_else(|| {
+        debug!("invalid SSH certificate request");
+        device::ErrorKind::InvalidData
+    })?;
+
+    if public_key_blob(key).as_deref() != Some(fields.signature_key) {
+        debug!(
+            "SSH certificate signature key doesn't match key: {:?}",
+            key_id
+        );
+        return Err(device::ErrorKind::SshCaConstraintViolation);
+    }
+
+    let not_before = u64::from(timestamp.saturating_sub(template.not_before));
+    let not_after = u64::from(timestamp) + u64::from(template.not_after);
+
+    if fields.valid_after < not_before || fields.valid_before > not_after {
+        debug!(
+            "SSH certificate validity ({}..{}) exceeds template's ({}..{})",
+            fields.valid_after, fields.valid_before, not_before, not_after
+        );
+        return Err(device::ErrorKind::SshCaConstraintViolation);
+    }
+
+    if let Some(principal) = fields
+        .principals
+        .iter()
+        .find(|principal| template.principals_blocklist.contains(principal))
+    {
+        debug!(
+            "SSH certificate principal not allowed: {}",
+            String::from_utf8_lossy(principal)
+        );
+        return Err(device::ErrorKind::SshCaConstraintViolation);
+    }
+
+    let signature = sign(key, algorithm, request).ok_or_else(|| {
+        debug!(
+            "can't sign SSH certificates with {:?} keys using {:?}",
+            key.algorithm(),
+            algorithm
+        );
+        device::ErrorKind::InvalidCommand
+    })?;
+
+    let mut certificate = request.to_vec();
+    put_string(&mut certificate, &signature);
+    Ok(certificate)
+}
+
+/// Parsed SSH certificate template
+struct Template<'a> {
+    /// Key which certificate requests must be signed with
+    timestamp_key: RsaPublicKey,
+
+    /// IDs of the CA keys allowed to sign certificates
+    ca_key_ids: Vec<object::Id>,
+
+    /// Seconds before the request's timestamp certificates may become valid
+    not_before: u32,
+
+    /// Seconds after the request's timestamp certificates may remain valid
+    not_after: u32,
+
+    /// Principals which may not be certified
+    principals_blocklist: Vec<&'a [u8]>,
+}
+
+impl<'a> Template<'a> {
+    /// Parse a serialized template
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let mut timestamp_key_algorithm = None;
+        let mut timestamp_key = None;
+        let mut ca_key_ids = vec![];
+        let mut not_before = 0;
+        let mut not_after = 0;
+        let mut principals_blocklist = vec![];
+        let mut reader = Reader(bytes);
+
+        while !reader.0.is_empty() {
+            let tag = reader.bytes(1)?[0];
+            let length = reader.bytes(2)?;
+            let value = reader.bytes(usize::from(u16::from_be_bytes([length[0], length[1]])))?;
+
+            match tag {
+                TIMESTAMP_KEY_ALGORITHM_TAG if value.len() == 1 => {
+                    timestamp_key_algorithm = asymmetric::Algorithm::from_u8(value[0]).ok()
+                }
+                TIMESTAMP_KEY_TAG => timestamp_key = Some(value),
+                CA_KEY_IDS_TAG if value.len() % 2 == 0 => {
+                    ca_key_ids = value
+                        .chunks(2)
+                        .map(|id| u16::from_be_bytes([id[0], id[1]]))
+                        .collect()
+                }
+                NOT_BEFORE_TAG => not_before = u32::from_be_bytes(value.try_into().ok()?),
+                NOT_AFTER_TAG => not_after = u32::from_be_bytes(value.try_into().ok()?),
+                PRINCIPALS_BLOCKLIST_TAG => {
+                    principals_blocklist = value
+                        .split(|&byte| byte == 0)
+                        .filter(|principal| !principal.is_empty())
+                        .collect()
+                }
+                _ => return None,
+            }
+        }
+
+        let modulus = match (timestamp_key_algorithm?, timestamp_key?) {
+            (
+                alg @ (asymmetric::Algorithm::Rsa2048
+                | asymmetric::Algorithm::Rsa3072
+                | asymmetric::Algorithm::Rsa4096),
+                modulus,
+            ) if modulus.len() == alg.key_len() => modulus,
+            _ => return None,
+        };
+
+        Some(Template {
+            timestamp_key: RsaPublicKey::new(
+                BigUint::from_bytes_be(modulus),
+                BigUint::from(RSA_EXPONENT),
+            )
+            .ok()?,
+            ca_key_ids,
+            not_before,
+            not_after,
+            principals_blocklist,
+        })
+    }
+}
+
+/// Fields of a certificate request which are checked against the template
+struct Request<'a> {
+    /// Principals the certificate is valid for
+    principals: Vec<&'a [u8]>,
+
+    /// Start of the certificate's validity period (in seconds since the epoch)
+    valid_after: u64,
+
+    /// End of the certificate's validity period (in seconds since the epoch)
+    valid_before: u64,
+
+    /// Public key of the CA which signs the certificate
+    signature_key: &'a [u8],
+}
+
+impl<'a> Request<'a> {
+    /// Parse a certificate request (i.e. an OpenSSH certificate without its
+    /// trailing signature)
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+
+        let public_key_fields = match reader.string()? {
+            b"ssh-ed25519-cert-v01@openssh.com" => 1,
+            b"ssh-rsa-cert-v01@openssh.com"
+            | b"ecdsa-sha2-nistp256-cert-v01@openssh.com"
+            | b"ecdsa-sha2-nistp384-cert-v01@openssh.com"
+            | b"ecdsa-sha2-nistp521-cert-v01@openssh.com" => 2,
+            _ => return None,
+        };
+
+        // Nonce and the certified public key
+        for _ in 0..=public_key_fields {
+            reader.string()?;
+        }
+
+        // Serial number, certificate type, and key ID
+        reader.bytes(8 + 4)?;
+        reader.string()?;
+
+        let mut principals_reader = Reader(reader.string()?);
+        let mut principals = vec![];
+
+        while !principals_reader.0.is_empty() {
+            principals.push(principals_reader.string()?);
+        }
+
+        let valid_after = reader.u64()?;
+        let valid_before = reader.u64()?;
+
+        // Critical options, extensions, and the reserved field
+        for _ in 0..3 {
+            reader.string()?;
+        }
+
+        let signature_key = reader.string()?;
+
+        if !reader.0.is_empty() {
+            return None;
+        }
+
+        Some(Request {
+            principals,
+            valid_after,
+            valid_before,
+            signature_key,
+        })
+    }
+}
+
+/// Reader for the SSH wire format
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Read the given number of bytes
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    /// Read a big endian 64-bit integer
+    fn u64(&mut self) -> Option<u64> {
+        self.bytes(8)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Read a length-prefixed string
+    fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self.bytes(4)?;
+        self.bytes(u32::from_be_bytes(len.try_into().unwrap()) as usize)
+    }
+}
+
+/// Public key of the given CA key, serialized in the SSH wire format
+fn public_key_blob(key: &Payload) -> Option<Vec<u8>> {
+    let mut blob = vec![];
+
+    match key {
+        Payload::EcdsaNistP256(k) => {
+            put_string(&mut blob, b"ecdsa-sha2-nistp256");
+            put_string(&mut blob, b"nistp256");
+            put_string(&mut blob, k.public_key().to_encoded_point(false).as_bytes());
+        }
+        Payload::EcdsaNistP384(k) => {
+            put_string(&mut blob, b"ecdsa-sha2-nistp384");
+            put_string(&mut blob, b"nistp384");
+            put_string(&mut blob, k.public_key().to_encoded_point(false).as_bytes());
+        }
+        Payload::Ed25519Key(k) => {
+            put_string(&mut blob, b"ssh-ed25519");
+            put_string(&mut blob, k.verifying_key().as_bytes());
+        }
+        Payload::RsaKey(_, k) => {
+            put_string(&mut blob, b"ssh-rsa");
+            put_mpint(&mut blob, k.e());
+            put_mpint(&mut blob, k.n());
+        }
+        _ => return None,
+    }
+
+    Some(blob)
+}
+
+/// Sign a certificate with the given CA key, returning the signature
+/// serialized in the SSH wire format
+fn sign(key: &Payload, algorithm: Algorithm, data: &[u8]) -> Option<Vec<u8>> {
+    let (signature_type, signature) = match (key, algorithm) {
+        (Payload::EcdsaNistP256(k), Algorithm::Ecdsa(ecdsa::Algorithm::Sha256)) => {
+            let signature: p256::ecdsa::Signature = p256::ecdsa::SigningKey::from(k).sign(data);
+            let (r, s) = signature.split_bytes();
+            ("ecdsa-sha2-nistp256", ecdsa_signature_blob(&r, &s))
+        }
+        (Payload::EcdsaNistP384(k), Algorithm::Ecdsa(ecdsa::Algorithm::Sha384)) => {
+            let signature: p384::ecdsa::Signature = p384::ecdsa::SigningKey::from(k).sign(data);
+            let (r, s) = signature.split_bytes();
+            ("ecdsa-sha2-nistp384", ecdsa_signature_blob(&r, &s))
+        }
+        (Payload::Ed25519Key(k), Algorithm::Asymmetric(asymmetric::Algorithm::Ed25519)) => {
+            ("ssh-ed25519", k.sign(data).to_bytes().to_vec())
+        }
+        (
+            Payload::RsaKey(_, k),
+            Algorithm::Rsa(rsa::Algorithm::Pkcs1(rsa::pkcs1::Algorithm::Sha256)),
+        ) => (
+            "rsa-sha2-256",
+            k.sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(data))
+                .expect("RSA signing failure!"),
+        ),
+        (
+            Payload::RsaKey(_, k),
+            Algorithm::Rsa(rsa::Algorithm::Pkcs1(rsa::pkcs1::Algorithm::Sha512)),
+        ) => (
+            "rsa-sha2-512",
+            k.sign(Pkcs1v15Sign::new::<Sha512>(), &Sha512::digest(data))
+                .expect("RSA signing failure!"),
+        ),
+        _ => return None,
+    };
+
+    let mut blob = vec![];
+    put_string(&mut blob, signature_type.as_bytes());
+    put_string(&mut blob, &signature);
+    Some(blob)
+}
+
+/// Serialize the `r` and `s` components of an ECDSA signature
+fn ecdsa_signature_blob(r: &[u8], s: &[u8]) -> Vec<u8> {
+    let mut blob = vec![];
+    put_mpint(&mut blob, &BigUint::from_bytes_be(r));
+    put_mpint(&mut blob, &BigUint::from_bytes_be(s));
+    blob
+}
+
+/// Append a length-prefixed string
+fn put_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Append a multiple precision integer (i.e. a two's complement string)
+fn put_mpint(buf: &mut Vec<u8>, n: &BigUint) {
+    let mut bytes = n.to_bytes_be();
+
+    if bytes == [0] {
+        bytes.clear();
+    } else if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+
+    put_string(buf, &bytes);
+}
@@ -1,16 +1,38 @@
 //! `MockHsm` presents a thread-safe API by locking interior mutable state,
 //! contained in the `State` struct defined in this module.
 
-use super::{audit::CommandAuditOptions, object::Objects, session::HsmSession};
+use super::{
+    attestation,
+    audit::{AuditLog, CommandAuditOptions},
+    clock::Clock,
+    fault::FaultPlan,
+    object::{Objects, Payload},
+    session::HsmSession,
+    snapshot::{self, Snapshot},
+    Error,
+};
 use crate::{
     audit::AuditOption,
-    connector, object,
+    command,
+    device::{self, SerialNumber},
+    object, response,
     session::{
         self,
-        securechannel::{Challenge, SecureChannel},
+        securechannel::{
+            asymmetric::{EphemeralPublicKey, Receipt, SessionKeys},
+            Challenge, SecureChannel,
+        },
     },
 };
-use std::collections::BTreeMap;
+use rand_core::OsRng;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Maximum number of concurrent sessions
+const MAX_SESSIONS: u8 = 16;
 
 /// Mutable interior state of the `MockHsm`
 #[derive(Debug)]
@@ -25,22 +47,67 @@ pub(crate) struct State {
     /// Fips mode
     pub(super) fips: AuditOption,
 
+    /// Audit log of commands performed by the MockHsm
+    pub(super) audit_log: AuditLog,
+
     /// Active sessions with the MockHsm
     sessions: BTreeMap<session::Id, HsmSession>,
 
     /// Objects within the MockHsm (i.e. keys)
     pub(super) objects: Objects,
+
+    /// Device private key, used for asymmetric authentication
+    pub(super) device_key: p256::SecretKey,
+
+    /// Device attestation key, used when no attestation key is specified
+    pub(super) attestation_key: Payload,
+
+    /// Self-signed certificate for the device attestation key
+    pub(super) attestation_certificate: Vec<u8>,
+
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
+
+    /// Serial number of the device
+    pub(super) serial_number: SerialNumber,
+
+    /// Firmware version of the device (major, minor, build)
+    pub(super) firmware_version: (u8, u8, u8),
+
+    /// Faults to inject into connections and responses
+    pub(super) fault_plan: FaultPlan,
+
+    /// Path to save a snapshot of the state to after every command which
+    /// modifies it
+    pub(super) persist_path: Option<PathBuf>,
 }
 
 impl State {
     /// Create a new instance of the server's mutable interior state
-    pub fn new() -> Self {
+    pub fn new(
+        clock: Arc<dyn Clock>,
+        serial_number: SerialNumber,
+        firmware_version: (u8, u8, u8),
+    ) -> Self {
+        let attestation_key = Payload::EcdsaNistP256(p256::SecretKey::random(&mut OsRng));
+        let attestation_certificate =
+            attestation::device_certificate(&attestation_key, serial_number);
+
         Self {
             command_audit_options: CommandAuditOptions::default(),
             force_audit: AuditOption::Off,
             fips: AuditOption::Off,
+            audit_log: AuditLog::new(clock.now()),
             sessions: BTreeMap::new(),
             objects: Objects::default(),
+            device_key: p256::SecretKey::random(&mut OsRng),
+            attestation_key,
+            attestation_certificate,
+            clock,
+            serial_number,
+            firmware_version,
+            fault_plan: FaultPlan::default(),
+            persist_path: None,
         }
     }
 
@@ -49,63 +116,243 @@ impl State {
         &mut self,
         authentication_key_id: object::Id,
         host_challenge: Challenge,
-    ) -> &HsmSession {
-        // Generate a random card challenge to send back to the client
-        let card_challenge = Challenge::new();
-
-        let session_id = self
-            .sessions
-            .keys()
-            .max()
-            .map(|id| id.succ().expect("session count exceeded"))
-            .unwrap_or_else(|| session::Id::from_u8(0).unwrap());
-
-        let channel = {
-            let authentication_key_obj = self
-                .objects
-                .get(authentication_key_id, object::Type::AuthenticationKey)
-                .unwrap_or_else(|| {
-                    panic!("MockHsm has no authentication::Key in slot {authentication_key_id:?}")
-                });
-
-            SecureChannel::new(
-                session_id,
-                authentication_key_obj
-                    .payload
-                    .authentication_key()
-                    .expect("auth key payload"),
-                host_challenge,
-                card_challenge,
-            )
+        card_challenge: Challenge,
+    ) -> Result<&HsmSession, device::ErrorKind> {
+        let session_id = self.next_session_id()?;
+
+        let authentication_key = match self
+            .objects
+            .get(authentication_key_id, object::Type::AuthenticationKey)
+        {
+            Some(obj) => match obj.payload.authentication_key() {
+                Some(key) => key,
+                None => {
+                    debug!(
+                        "not a symmetric authentication key: {:?}",
+                        authentication_key_id
+                    );
+                    return Err(device::ErrorKind::AuthenticationFailed);
+                }
+            },
+            None => {
+                debug!("no such authentication key: {:?}", authentication_key_id);
+                return Err(device::ErrorKind::ObjectNotFound);
+            }
+        };
+
+        let channel = SecureChannel::new(
+            session_id,
+            authentication_key,
+            host_challenge,
+            card_challenge,
+        );
+
+        self.insert_session(session_id, authentication_key_id, channel)
+    }
+
+    /// Create a new session with the MockHsm using an asymmetric (EC P-256)
+    /// authentication key, returning the card's ephemeral public key and
+    /// receipt along with the session
+    pub fn create_asymmetric_session(
+        &mut self,
+        authentication_key_id: object::Id,
+        host_public_key: &EphemeralPublicKey,
+    ) -> Result<(&HsmSession, EphemeralPublicKey, Receipt), device::ErrorKind> {
+        let session_id = self.next_session_id()?;
+
+        // Generate an ephemeral key pair for the card
+        let card_secret_key = p256::SecretKey::random(&mut OsRng);
+        let card_public_key = EphemeralPublicKey::from(&card_secret_key.public_key());
+
+        let authentication_key = match self
+            .objects
+            .get(authentication_key_id, object::Type::AuthenticationKey)
+        {
+            Some(obj) => match obj.payload.asymmetric_authentication_key() {
+                Some(key) => key,
+                None => {
+                    debug!(
+                        "not an asymmetric authentication key: {:?}",
+                        authentication_key_id
+                    );
+                    return Err(device::ErrorKind::AuthenticationFailed);
+                }
+            },
+            None => {
+                debug!("no such authentication key: {:?}", authentication_key_id);
+                return Err(device::ErrorKind::ObjectNotFound);
+            }
+        };
+
+        let host_ephemeral_key = match host_public_key.to_public_key() {
+            Ok(key) => key,
+            Err(e) => {
+                debug!("invalid host ephemeral public key: {}", e);
+                return Err(device::ErrorKind::InvalidData);
+            }
         };
 
-        let session = HsmSession::new(session_id, card_challenge, channel);
-        assert!(self.sessions.insert(session_id, session).is_none());
+        let session_keys = SessionKeys::derive(
+            &card_secret_key,
+            &self.device_key,
+            &host_ephemeral_key,
+            authentication_key,
+        );
+
+        let receipt = session_keys.receipt(&card_public_key, host_public_key);
+        let channel = SecureChannel::new_asymmetric(session_id, &session_keys, &receipt);
+        let session = self.insert_session(session_id, authentication_key_id, channel)?;
+
+        Ok((session, card_public_key, receipt))
+    }
+
+    /// Obtain an active session by its ID, marking it as active
+    pub fn get_session(&mut self, id: session::Id) -> Result<&mut HsmSession, device::ErrorKind> {
+        self.expire_sessions();
+        let now = self.clock.now();
+
+        match self.sessions.get_mut(&id) {
+            Some(session) => {
+                session.last_active = now;
+                Ok(session)
+            }
+            None => {
+                debug!("invalid session ID: {:?}", id);
+                Err(device::ErrorKind::InvalidSession)
+            }
+        }
+    }
 
-        self.get_session(session_id).unwrap()
+    /// Get the device public key, used for asymmetric authentication
+    pub fn device_public_key(&self) -> p256::PublicKey {
+        self.device_key.public_key()
     }
 
-    /// Obtain the channel for a session by its ID
-    pub fn get_session(&mut self, id: session::Id) -> Result<&mut HsmSession, connector::Error> {
-        self.sessions.get_mut(&id).ok_or_else(|| {
-            format_err!(
-                connector::ErrorKind::RequestError,
-                "invalid session ID: {:?}",
-                id
-            )
-            .into()
-        })
+    /// Get the clock driving the MockHsm
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        Arc::clone(&self.clock)
     }
 
     /// Close an active session
-    pub fn close_session(&mut self, id: session::Id) {
-        assert!(self.sessions.remove(&id).is_some());
+    pub fn close_session(&mut self, id: session::Id) -> Result<(), device::ErrorKind> {
+        if self.sessions.remove(&id).is_none() {
+            debug!("no such session to close: {:?}", id);
+            return Err(device::ErrorKind::ObjectNotFound);
+        }
+
+        Ok(())
     }
 
     /// Reset the internal HSM state, closing all connections
     pub fn reset(&mut self) {
         self.command_audit_options = CommandAuditOptions::default();
+        self.audit_log = AuditLog::new(self.clock.now());
         self.sessions = BTreeMap::new();
         self.objects = Objects::default();
     }
+
+    /// Replace the persistent parts of the state with the given snapshot
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.command_audit_options = snapshot.command_audit_options;
+        self.force_audit = snapshot.force_audit;
+        self.fips = snapshot.fips;
+        self.objects = snapshot.objects;
+        self.serial_number = snapshot.serial_number;
+        self.firmware_version = snapshot.firmware_version;
+        self.device_key = snapshot.device_key;
+        self.attestation_key = snapshot.attestation_key;
+        self.attestation_certificate = snapshot.attestation_certificate;
+    }
+
+    /// Save a snapshot of the state, if auto-persist is enabled
+    pub fn persist(&self) -> Result<(), Error> {
+        if let Some(path) = &self.persist_path {
+            snapshot::write(self, path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Must the given command be refused because it is audited, but the
+    /// audit log is full and auditing is being forced?
+    pub fn audit_log_full(&self, command_type: command::Code) -> bool {
+        self.force_audit != AuditOption::Off
+            && self.command_audit_options.is_audited(command_type)
+            && self.audit_log.is_full()
+    }
+
+    /// Record a command in the audit log, if auditing is enabled for it
+    pub fn audit(
+        &mut self,
+        command: &command::Message,
+        session_key: object::Id,
+        result: response::Code,
+    ) {
+        if !self.command_audit_options.is_audited(command.command_type) {
+            return;
+        }
+
+        if self.audit_log_full(command.command_type) {
+            // Sessions can still be established when the log is full, so
+            // the log can be consumed
+            if matches!(
+                command.command_type,
+                command::Code::CreateSession | command::Code::AuthenticateSession
+            ) {
+                self.audit_log.record_unlogged_auth_event();
+            }
+
+            return;
+        }
+
+        self.audit_log
+            .record(command, session_key, result, self.clock.now());
+    }
+
+    /// Get the ID to use for the next session
+    fn next_session_id(&mut self) -> Result<session::Id, device::ErrorKind> {
+        self.expire_sessions();
+
+        (0..MAX_SESSIONS)
+            .map(|id| session::Id::from_u8(id).unwrap())
+            .find(|id| !self.sessions.contains_key(id))
+            .ok_or_else(|| {
+                debug!("all {} sessions are in use", MAX_SESSIONS);
+                device::ErrorKind::SessionsFull
+            })
+    }
+
+    /// Close sessions which have been inactive for longer than the timeout
+    fn expire_sessions(&mut self) {
+        let now = self.clock.now();
+        let timeout = session::Timeout::default().duration();
+
+        self.sessions.retain(|id, session| {
+            let active = now.duration_since(session.last_active) < timeout;
+
+            if !active {
+                debug!("session {} timed out", id);
+            }
+
+            active
+        });
+    }
+
+    /// Add a session with the given ID and channel
+    fn insert_session(
+        &mut self,
+        session_id: session::Id,
+        authentication_key_id: object::Id,
+        channel: SecureChannel,
+    ) -> Result<&HsmSession, device::ErrorKind> {
+        let session = HsmSession::new(session_id, authentication_key_id, channel, self.clock.now());
+
+        match self.sessions.entry(session_id) {
+            Entry::Vacant(entry) => Ok(entry.insert(session)),
+            Entry::Occupied(_) => {
+                debug!("session ID already in use: {:?}", session_id);
+                Err(device::ErrorKind::InvalidData)
+            }
+        }
+    }
 }
@@ -27,6 +27,12 @@ pub enum Type {
 
     /// Yubikey-AES OTP encryption/decryption key
     OtpAeadKey = 0x07,
+
+    /// Symmetric (AES) encryption key
+    SymmetricKey = 0x08,
+
+    /// RSA public key used to export (wrap) objects
+    PublicWrapKey = 0x09,
 }
 
 impl Type {
@@ -40,6 +46,8 @@ impl Type {
             0x05 => Type::HmacKey,
             0x06 => Type::Template,
             0x07 => Type::OtpAeadKey,
+            0x08 => Type::SymmetricKey,
+            0x09 => Type::PublicWrapKey,
             _ => fail!(ErrorKind::TypeInvalid, "invalid object type: {}", byte),
         })
     }
@@ -60,6 +68,8 @@ impl fmt::Display for Type {
             Type::HmacKey => "hmac-key",
             Type::Template => "template",
             Type::OtpAeadKey => "otp-aead-key",
+            Type::SymmetricKey => "symmetric-key",
+            Type::PublicWrapKey => "public-wrap-key",
         })
     }
 }
@@ -76,6 +86,8 @@ impl FromStr for Type {
             "hmac-key" => Type::HmacKey,
             "template" => Type::Template,
             "otp-aead-key" => Type::OtpAeadKey,
+            "symmetric-key" => Type::SymmetricKey,
+            "public-wrap-key" => Type::PublicWrapKey,
             _ => return Err(()),
         })
     }
@@ -95,7 +107,7 @@ impl<'de> Deserialize<'de> for Type {
             type Value = Type;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-                formatter.write_str("an unsigned byte between 0x01 and 0x07")
+                formatter.write_str("an unsigned byte between 0x01 and 0x09")
             }
 
             fn visit_u8<E: de::Error>(self, value: u8) -> Result<Type, E> {
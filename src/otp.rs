@@ -1,6 +1,24 @@
 //! Yubico One Time Password (OTP) functionality
 
+pub mod aead;
 mod algorithm;
 pub(crate) mod commands;
+mod decrypted;
+mod error;
+pub mod nonce;
+pub mod private_id;
 
-pub use self::algorithm::Algorithm;
+pub use self::{
+    aead::Aead,
+    algorithm::Algorithm,
+    decrypted::DecryptedOtp,
+    error::{Error, ErrorKind},
+    nonce::Nonce,
+    private_id::PrivateId,
+};
+
+/// Size of a Yubico OTP key (AES-128) in bytes
+pub const KEY_SIZE: usize = 16;
+
+/// Size of a binary Yubico OTP (i.e. after modhex decoding) in bytes
+pub const OTP_SIZE: usize = 16;
@@ -0,0 +1,51 @@
+//! Yubico OTP AEADs: encrypted YubiKey OTP keys and private IDs
+
+use super::{Error, ErrorKind};
+
+/// Size of an OTP AEAD produced by the `YubiHSM 2`: a 6-byte nonce, followed
+/// by the encrypted 16-byte OTP key and 6-byte private ID, and an 8-byte MAC
+pub const SIZE: usize = 36;
+
+/// Yubico OTP AEAD: a YubiKey's OTP key and private ID, encrypted under an
+/// OTP AEAD key stored in the HSM
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Aead(pub [u8; SIZE]);
+
+impl Aead {
+    /// Get slice of the inner byte array
+    pub fn as_slice(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl AsRef<[u8]> for Aead {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; SIZE]> for Aead {
+    fn from(bytes: [u8; SIZE]) -> Aead {
+        Aead(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Aead {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Aead, Error> {
+        ensure!(
+            bytes.len() == SIZE,
+            ErrorKind::SizeInvalid,
+            "AEAD must be exactly {} bytes (got {})",
+            SIZE,
+            bytes.len()
+        );
+
+        let mut aead = [0u8; SIZE];
+        aead.copy_from_slice(bytes);
+        Ok(Aead(aead))
+    }
+}
+
+impl_array_serializers!(Aead, SIZE);
@@ -1,5 +1,12 @@
 //! Yubico OTP commands
 
+mod create_aead;
+mod decrypt;
+mod generate_key;
 mod put;
+mod randomize_aead;
+mod rewrap_aead;
 
-pub(crate) use self::put::*;
+pub(crate) use self::{
+    create_aead::*, decrypt::*, generate_key::*, put::*, randomize_aead::*, rewrap_aead::*,
+};
@@ -0,0 +1,71 @@
+//! Decrypt a Yubico OTP using an AEAD and the OTP AEAD key it was created with
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Otp.html>
+
+use crate::{
+    command::{self, Command},
+    object, otp,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::decrypt_otp`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptOtpCommand {
+    /// ID of the OTP AEAD key the AEAD was created with
+    pub key_id: object::Id,
+
+    /// AEAD containing the credential's OTP key and private ID
+    pub aead: otp::Aead,
+
+    /// Binary (i.e. modhex-decoded) OTP
+    pub otp: [u8; otp::OTP_SIZE],
+}
+
+impl Command for DecryptOtpCommand {
+    type ResponseType = DecryptOtpResponse;
+}
+
+/// Response from `command::decrypt_otp`.
+///
+/// Counters are little endian, as they appear in the OTP itself.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptOtpResponse {
+    /// Usage counter
+    pub use_counter: [u8; 2],
+
+    /// Session counter
+    pub session_counter: u8,
+
+    /// High byte of the timestamp
+    pub timestamp_high: u8,
+
+    /// Low bytes of the timestamp
+    pub timestamp_low: [u8; 2],
+}
+
+impl Response for DecryptOtpResponse {
+    const COMMAND_CODE: command::Code = command::Code::DecryptOtp;
+}
+
+impl From<DecryptOtpResponse> for otp::DecryptedOtp {
+    fn from(response: DecryptOtpResponse) -> otp::DecryptedOtp {
+        otp::DecryptedOtp {
+            use_counter: u16::from_le_bytes(response.use_counter),
+            session_counter: response.session_counter,
+            timestamp: u32::from(response.timestamp_high) << 16
+                | u32::from(u16::from_le_bytes(response.timestamp_low)),
+        }
+    }
+}
+
+impl From<otp::DecryptedOtp> for DecryptOtpResponse {
+    fn from(otp: otp::DecryptedOtp) -> DecryptOtpResponse {
+        DecryptOtpResponse {
+            use_counter: otp.use_counter.to_le_bytes(),
+            session_counter: otp.session_counter,
+            timestamp_high: (otp.timestamp >> 16) as u8,
+            timestamp_low: (otp.timestamp as u16).to_le_bytes(),
+        }
+    }
+}
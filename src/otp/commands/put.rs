@@ -4,7 +4,7 @@
 
 use crate::{
     command::{self, Command},
-    object,
+    object, otp,
     response::Response,
 };
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,9 @@ pub(crate) struct PutOtpAeadKeyCommand {
     /// Common parameters to all put object commands
     pub params: object::put::Params,
 
+    /// Nonce ID used for AEADs created with this key
+    pub nonce_id: otp::Nonce,
+
     /// Serialized object
     pub data: Vec<u8>,
 }
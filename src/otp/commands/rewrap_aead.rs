@@ -0,0 +1,35 @@
+//! Re-encrypt a Yubico OTP AEAD from one OTP AEAD key to another
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Rewrap_Otp_Aead.html>
+
+use crate::{
+    command::{self, Command},
+    object, otp,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::rewrap_otp_aead`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct RewrapOtpAeadCommand {
+    /// ID of the OTP AEAD key the AEAD is currently encrypted under
+    pub from_key_id: object::Id,
+
+    /// ID of the OTP AEAD key to re-encrypt the AEAD under
+    pub to_key_id: object::Id,
+
+    /// AEAD to re-encrypt
+    pub aead: otp::Aead,
+}
+
+impl Command for RewrapOtpAeadCommand {
+    type ResponseType = RewrapOtpAeadResponse;
+}
+
+/// Response from `command::rewrap_otp_aead`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct RewrapOtpAeadResponse(pub(crate) otp::Aead);
+
+impl Response for RewrapOtpAeadResponse {
+    const COMMAND_CODE: command::Code = command::Code::RewrapOtpAead;
+}
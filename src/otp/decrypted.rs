@@ -0,0 +1,18 @@
+//! Decrypted Yubico OTPs
+
+/// Counter and timestamp fields of an OTP decrypted by the HSM.
+///
+/// The HSM checks the OTP's private ID against the one stored in the AEAD
+/// before returning these values. Callers must still check the counters are
+/// larger than the last ones seen for the same credential to prevent replays.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DecryptedOtp {
+    /// Usage counter: incremented each time the YubiKey is powered up
+    pub use_counter: u16,
+
+    /// Session counter: incremented for each OTP generated while powered up
+    pub session_counter: u8,
+
+    /// 24-bit timestamp (8Hz ticks since the YubiKey was powered up)
+    pub timestamp: u32,
+}
@@ -0,0 +1,22 @@
+//! Yubico OTP errors
+
+use crate::error::{BoxError, Context};
+use thiserror::Error;
+
+/// Yubico OTP errors
+pub type Error = crate::Error<ErrorKind>;
+
+/// Kinds of Yubico OTP errors
+#[derive(Copy, Clone, Debug, Eq, Error, PartialEq)]
+pub enum ErrorKind {
+    /// Size is invalid
+    #[error("invalid size")]
+    SizeInvalid,
+}
+
+impl ErrorKind {
+    /// Create an error context from this error
+    pub fn context(self, source: impl Into<BoxError>) -> Context<ErrorKind> {
+        Context::new(self, Some(source.into()))
+    }
+}
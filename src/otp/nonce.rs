@@ -0,0 +1,36 @@
+//! Nonce IDs used by OTP AEAD keys
+
+use rand_core::{OsRng, RngCore};
+
+/// Number of bytes in an OTP AEAD key's nonce ID
+pub const SIZE: usize = 4;
+
+/// Nonce ID associated with an OTP AEAD key.
+///
+/// The HSM prefixes the nonces of all AEADs created under a given OTP AEAD
+/// key with this value, so it should be unique across the keys in use.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Nonce(pub [u8; SIZE]);
+
+impl Nonce {
+    /// Generate a random `otp::Nonce`
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; SIZE];
+        OsRng.fill_bytes(&mut bytes);
+        Nonce(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; SIZE]> for Nonce {
+    fn from(bytes: [u8; SIZE]) -> Nonce {
+        Nonce(bytes)
+    }
+}
+
+impl_array_serializers!(Nonce, SIZE);
@@ -0,0 +1,36 @@
+//! Yubico OTP private IDs
+
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Number of bytes in a Yubico OTP private ID
+pub const SIZE: usize = 6;
+
+/// Private ID of a YubiKey OTP credential.
+///
+/// This value is embedded in every OTP the YubiKey emits. The HSM compares it
+/// against the private ID stored in the AEAD when decrypting an OTP, and
+/// refuses to decrypt OTPs which don't match.
+#[derive(Clone, Eq, PartialEq, Zeroize)]
+#[zeroize(drop)]
+pub struct PrivateId(pub [u8; SIZE]);
+
+impl AsRef<[u8]> for PrivateId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for PrivateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "yubihsm::otp::PrivateId(...)")
+    }
+}
+
+impl From<[u8; SIZE]> for PrivateId {
+    fn from(bytes: [u8; SIZE]) -> PrivateId {
+        PrivateId(bytes)
+    }
+}
+
+impl_array_serializers!(PrivateId, SIZE);
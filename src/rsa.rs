@@ -13,5 +13,9 @@ pub mod mgf;
 pub mod oaep;
 pub mod pkcs1;
 pub mod pss;
+#[cfg(feature = "sha2")]
+mod signature_algorithm;
 
 pub use self::algorithm::*;
+#[cfg(feature = "sha2")]
+pub use self::signature_algorithm::SignatureAlgorithm;
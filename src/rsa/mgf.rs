@@ -1,6 +1,8 @@
 //! Mask generating functions for use with RSASSA-PSS signatures
 
 use crate::algorithm;
+#[cfg(any(feature = "rsa-oaep", feature = "mockhsm"))]
+use sha2::Digest;
 
 /// Mask generating functions for RSASSA-PSS
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -42,3 +44,19 @@ impl Algorithm {
 }
 
 impl_algorithm_serializers!(Algorithm);
+
+/// XOR the MGF1 mask generated from `seed` using the digest `D` into `out`,
+/// as described in RFC 8017 appendix B.2.1
+#[cfg(any(feature = "rsa-oaep", feature = "mockhsm"))]
+pub(crate) fn mgf1_xor<D: Digest>(out: &mut [u8], seed: &[u8]) {
+    for (counter, chunk) in out.chunks_mut(<D as Digest>::output_size()).enumerate() {
+        let mask = D::new()
+            .chain_update(seed)
+            .chain_update((counter as u32).to_be_bytes())
+            .finalize();
+
+        for (byte, mask_byte) in chunk.iter_mut().zip(mask) {
+            *byte ^= mask_byte;
+        }
+    }
+}
@@ -1,8 +1,20 @@
 //! RSA encryption with Optimal Asymmetric Encryption Padding (OAEP)
+//!
+//! The `Encryptor` and `Decryptor` types require the `rsa-oaep` cargo feature.
 
 mod algorithm;
 pub(crate) mod commands;
 mod decrypted_data;
+#[cfg(feature = "rsa-oaep")]
+mod decryptor;
+#[cfg(feature = "sha2")]
+mod digest_algorithm;
+#[cfg(feature = "rsa-oaep")]
+mod encryptor;
 
 pub use self::algorithm::Algorithm;
 pub use self::decrypted_data::DecryptedData;
+#[cfg(feature = "sha2")]
+pub use self::digest_algorithm::DigestAlgorithm;
+#[cfg(feature = "rsa-oaep")]
+pub use self::{decryptor::Decryptor, encryptor::Encryptor};
@@ -0,0 +1,90 @@
+//! RSA-OAEP decryption using keys stored in the YubiHSM 2
+
+use super::{DecryptedData, DigestAlgorithm};
+use crate::{
+    asymmetric,
+    client::{Error, ErrorKind},
+    object, Client,
+};
+use std::marker::PhantomData;
+
+/// RSA-OAEP decryptor bound to an RSA key stored in the HSM, generic over
+/// the digest algorithm `D` used to hash the label and with MGF1.
+pub struct Decryptor<D: DigestAlgorithm> {
+    /// YubiHSM client
+    client: Client,
+
+    /// ID of an RSA key to decrypt with
+    decryption_key_id: object::Id,
+
+    /// Algorithm (i.e. size) of the RSA key
+    algorithm: asymmetric::Algorithm,
+
+    /// Digest algorithm
+    digest: PhantomData<D>,
+}
+
+impl<D: DigestAlgorithm> Decryptor<D> {
+    /// Create a new YubiHSM-backed RSA-OAEP decryptor
+    pub fn create(client: Client, decryption_key_id: object::Id) -> Result<Self, Error> {
+        let algorithm = client.get_public_key(decryption_key_id)?.algorithm;
+
+        ensure!(
+            is_rsa(algorithm),
+            ErrorKind::ProtocolError,
+            "key {} is not an RSA key: {:?}",
+            decryption_key_id,
+            algorithm
+        );
+
+        Ok(Self {
+            client,
+            decryption_key_id,
+            algorithm,
+            digest: PhantomData,
+        })
+    }
+
+    /// Get the algorithm (i.e. size) of the RSA key
+    pub fn algorithm(&self) -> asymmetric::Algorithm {
+        self.algorithm
+    }
+
+    /// Decrypt the given ciphertext, which was encrypted with an empty label
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<DecryptedData, Error> {
+        self.decrypt_with_label(ciphertext, b"")
+    }
+
+    /// Decrypt the given ciphertext, which was encrypted with the given label
+    pub fn decrypt_with_label(
+        &self,
+        ciphertext: &[u8],
+        label: &[u8],
+    ) -> Result<DecryptedData, Error> {
+        ensure!(
+            ciphertext.len() == self.algorithm.key_len(),
+            ErrorKind::ProtocolError,
+            "invalid ciphertext length for {:?}: {} (expected {})",
+            self.algorithm,
+            ciphertext.len(),
+            self.algorithm.key_len()
+        );
+
+        self.client.decrypt_oaep(
+            self.decryption_key_id,
+            D::MGF_ALGORITHM,
+            ciphertext,
+            D::digest(label).to_vec(),
+        )
+    }
+}
+
+/// Is the given algorithm an RSA key algorithm?
+pub(super) fn is_rsa(algorithm: asymmetric::Algorithm) -> bool {
+    matches!(
+        algorithm,
+        asymmetric::Algorithm::Rsa2048
+            | asymmetric::Algorithm::Rsa3072
+            | asymmetric::Algorithm::Rsa4096
+    )
+}
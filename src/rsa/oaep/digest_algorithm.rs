@@ -0,0 +1,32 @@
+//! Digest algorithms for RSA-OAEP encryption
+
+use crate::rsa::{mgf, oaep};
+use sha2::{
+    digest::{Digest, DynDigest},
+    Sha256, Sha384, Sha512,
+};
+
+/// Digest algorithms which can be used to hash RSA-OAEP labels and with
+/// MGF1, both for HSM-backed decryption and for RSA-wrapped objects
+pub trait DigestAlgorithm: Digest + DynDigest + Default + Send + Sync + 'static {
+    /// Hash algorithm to use with MGF1
+    const MGF_ALGORITHM: mgf::Algorithm;
+
+    /// RSA-OAEP algorithm using this digest
+    const OAEP_ALGORITHM: oaep::Algorithm;
+}
+
+impl DigestAlgorithm for Sha256 {
+    const MGF_ALGORITHM: mgf::Algorithm = mgf::Algorithm::Sha256;
+    const OAEP_ALGORITHM: oaep::Algorithm = oaep::Algorithm::Sha256;
+}
+
+impl DigestAlgorithm for Sha384 {
+    const MGF_ALGORITHM: mgf::Algorithm = mgf::Algorithm::Sha384;
+    const OAEP_ALGORITHM: oaep::Algorithm = oaep::Algorithm::Sha384;
+}
+
+impl DigestAlgorithm for Sha512 {
+    const MGF_ALGORITHM: mgf::Algorithm = mgf::Algorithm::Sha512;
+    const OAEP_ALGORITHM: oaep::Algorithm = oaep::Algorithm::Sha512;
+}
@@ -0,0 +1,113 @@
+//! RSA-OAEP encryption to the public key of an RSA key stored in the YubiHSM 2
+
+use super::{decryptor::is_rsa, DigestAlgorithm};
+use crate::rsa::mgf;
+use crate::{
+    asymmetric,
+    client::{Error, ErrorKind},
+    object, Client,
+};
+use ::rsa::{traits::PublicKeyParts, BigUint, RsaPublicKey};
+use rand_core::{OsRng, RngCore};
+use sha2::Digest;
+use std::marker::PhantomData;
+
+/// RSA-OAEP encryptor for the public key of an RSA key stored in the HSM.
+///
+/// Encryption happens locally. Ciphertexts can be decrypted by the HSM using
+/// an [`rsa::oaep::Decryptor`][`super::Decryptor`] with the same digest `D`.
+pub struct Encryptor<D: DigestAlgorithm> {
+    /// RSA public key
+    public_key: RsaPublicKey,
+
+    /// Algorithm (i.e. size) of the RSA key
+    algorithm: asymmetric::Algorithm,
+
+    /// Digest algorithm
+    digest: PhantomData<D>,
+}
+
+impl<D: DigestAlgorithm> Encryptor<D> {
+    /// Create an RSA-OAEP encryptor for the public key of the given key ID
+    pub fn create(client: &Client, key_id: object::Id) -> Result<Self, Error> {
+        let public_key = client.get_public_key(key_id)?;
+        let algorithm = public_key.algorithm;
+
+        ensure!(
+            is_rsa(algorithm),
+            ErrorKind::ProtocolError,
+            "key {} is not an RSA key: {:?}",
+            key_id,
+            algorithm
+        );
+
+        let public_key = public_key.rsa().ok_or_else(|| {
+            format_err!(
+                ErrorKind::ProtocolError,
+                "malformed RSA public key for key {}",
+                key_id
+            )
+        })?;
+
+        Ok(Self {
+            public_key,
+            algorithm,
+            digest: PhantomData,
+        })
+    }
+
+    /// Get the algorithm (i.e. size) of the RSA key
+    pub fn algorithm(&self) -> asymmetric::Algorithm {
+        self.algorithm
+    }
+
+    /// Get the RSA public key
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Encrypt the given plaintext with an empty label
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.encrypt_with_label(plaintext, b"")
+    }
+
+    /// Encrypt the given plaintext with the given label, as described in
+    /// RFC 8017 section 7.1.1. Labels are arbitrary bytes, which the `rsa`
+    /// crate's OAEP implementation doesn't support.
+    pub fn encrypt_with_label(&self, plaintext: &[u8], label: &[u8]) -> Result<Vec<u8>, Error> {
+        let modulus_size = self.public_key.size();
+        let hash_size = <D as Digest>::output_size();
+
+        ensure!(
+            plaintext.len() + 2 * hash_size + 2 <= modulus_size,
+            ErrorKind::ProtocolError,
+            "plaintext too long for {:?}: {} bytes (max {})",
+            self.algorithm,
+            plaintext.len(),
+            modulus_size.saturating_sub(2 * hash_size + 2)
+        );
+
+        // Encoded message: 0x00 || masked seed || masked data block
+        let mut encoded = vec![0u8; modulus_size];
+        let (seed, data_block) = encoded[1..].split_at_mut(hash_size);
+        OsRng.fill_bytes(seed);
+
+        // Data block: label hash || zero padding || 0x01 || message
+        let message_offset = data_block.len() - plaintext.len();
+        data_block[..hash_size].copy_from_slice(&D::digest(label));
+        data_block[message_offset - 1] = 0x01;
+        data_block[message_offset..].copy_from_slice(plaintext);
+
+        mgf::mgf1_xor::<D>(data_block, seed);
+        mgf::mgf1_xor::<D>(seed, data_block);
+
+        let ciphertext = BigUint::from_bytes_be(&encoded)
+            .modpow(self.public_key.e(), self.public_key.n())
+            .to_bytes_be();
+
+        // Left pad the ciphertext to the size of the modulus
+        let mut padded = vec![0u8; modulus_size - ciphertext.len()];
+        padded.extend_from_slice(&ciphertext);
+        Ok(padded)
+    }
+}
@@ -1,14 +1,18 @@
-//! RSASSA-PKCS#1v1.5 commands
+//! RSA PKCS#1v1.5: legacy signature and encryption algorithms
 //!
 //! Note: This is a legacy algorithm. Greenfield projects should consider
 //! non-RSA algorithms like Ed25519 or ECDSA, or RSA-PSS if RSA is required.
 
 mod algorithm;
-#[cfg(feature = "untested")]
 pub(crate) mod commands;
-#[cfg(feature = "untested")]
+mod decrypted_data;
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 mod signature;
-
-pub use self::algorithm::Algorithm;
 #[cfg(feature = "untested")]
+mod signer;
+
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 pub use self::signature::Signature;
+#[cfg(feature = "untested")]
+pub use self::signer::Signer;
+pub use self::{algorithm::Algorithm, decrypted_data::DecryptedData};
@@ -1,4 +1,4 @@
-//! RSASSA-PKCS#1v1.5 commands
+//! RSA PKCS#1v1.5 commands
 
 use crate::{
     command::{self, Command},
@@ -8,7 +8,36 @@ use crate::{
 };
 use serde::{Deserialize, Serialize};
 
+/// Request parameters for `command::decrypt_pkcs1v15`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptPkcs1Command {
+    /// ID of the decryption key
+    pub key_id: object::Id,
+
+    /// Data to be decrypted
+    pub data: Vec<u8>,
+}
+
+impl Command for DecryptPkcs1Command {
+    type ResponseType = DecryptPkcs1Response;
+}
+
+/// RSA PKCS#1v1.5 decrypted data
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DecryptPkcs1Response(pub(crate) rsa::pkcs1::DecryptedData);
+
+impl Response for DecryptPkcs1Response {
+    const COMMAND_CODE: command::Code = command::Code::DecryptPkcs1;
+}
+
+impl From<DecryptPkcs1Response> for rsa::pkcs1::DecryptedData {
+    fn from(response: DecryptPkcs1Response) -> rsa::pkcs1::DecryptedData {
+        response.0
+    }
+}
+
 /// Request parameters for `command::sign_rsa_pkcs1v15*`
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct SignPkcs1Command {
     /// ID of the key to perform the signature with
@@ -18,18 +47,22 @@ pub(crate) struct SignPkcs1Command {
     pub digest: Vec<u8>,
 }
 
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 impl Command for SignPkcs1Command {
     type ResponseType = SignPkcs1Response;
 }
 
 /// RSASSA-PKCS#1v1.5 signatures (ASN.1 DER encoded)
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct SignPkcs1Response(rsa::pkcs1::Signature);
+pub struct SignPkcs1Response(pub(crate) rsa::pkcs1::Signature);
 
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 impl Response for SignPkcs1Response {
     const COMMAND_CODE: command::Code = command::Code::SignPkcs1;
 }
 
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 impl From<SignPkcs1Response> for rsa::pkcs1::Signature {
     fn from(response: SignPkcs1Response) -> rsa::pkcs1::Signature {
         response.0
@@ -0,0 +1,50 @@
+//! RSA PKCS#1v1.5 decrypted data
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug};
+use zeroize::Zeroize;
+
+/// RSA PKCS#1v1.5 decrypted data.
+///
+/// This is typically secret key material (e.g. a TLS premaster secret), so
+/// it's zeroized on drop and omitted from debug output.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize, Zeroize)]
+#[zeroize(drop)]
+pub struct DecryptedData(pub Vec<u8>);
+
+#[allow(clippy::len_without_is_empty)]
+impl DecryptedData {
+    /// Unwrap inner byte vector
+    pub fn into_vec(mut self) -> Vec<u8> {
+        std::mem::take(&mut self.0)
+    }
+
+    /// Get length of the decrypted data
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Get slice of the inner byte vector
+    pub fn as_slice(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl AsRef<[u8]> for DecryptedData {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl Debug for DecryptedData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Avoid leaking secrets in debug messages
+        write!(f, "yubihsm::rsa::pkcs1::DecryptedData(...)")
+    }
+}
+
+impl Into<Vec<u8>> for DecryptedData {
+    fn into(self) -> Vec<u8> {
+        self.into_vec()
+    }
+}
@@ -0,0 +1,78 @@
+//! RSASSA-PKCS#1v1.5 signature provider for the YubiHSM 2
+
+use crate::{object, rsa::SignatureAlgorithm, Client};
+use ::rsa::{
+    pkcs1v15,
+    pkcs8::spki::{der::AnyRef, AlgorithmIdentifierRef, SignatureAlgorithmIdentifier},
+    RsaPublicKey,
+};
+use signature::{DigestSigner, Error, Keypair};
+
+/// RSASSA-PKCS#1v1.5 signature provider for yubihsm-client, generic over
+/// the digest algorithm `S` (SHA-256, SHA-384, or SHA-512)
+pub struct Signer<S: SignatureAlgorithm> {
+    /// YubiHSM client
+    client: Client,
+
+    /// ID of an RSA key to perform signatures with
+    signing_key_id: object::Id,
+
+    /// Verifying key which corresponds to this signer
+    verifying_key: pkcs1v15::VerifyingKey<S>,
+}
+
+impl<S: SignatureAlgorithm> Signer<S> {
+    /// Create a new YubiHSM-backed RSASSA-PKCS#1v1.5 signer
+    pub fn create(client: Client, signing_key_id: object::Id) -> Result<Self, Error> {
+        let public_key = client
+            .get_public_key(signing_key_id)?
+            .rsa()
+            .ok_or_else(Error::new)?;
+
+        Ok(Self {
+            client,
+            signing_key_id,
+            verifying_key: pkcs1v15::VerifyingKey::new(public_key),
+        })
+    }
+
+    /// Get the public key for the YubiHSM-backed RSA private key
+    pub fn public_key(&self) -> &RsaPublicKey {
+        self.verifying_key.as_ref()
+    }
+}
+
+impl<S: SignatureAlgorithm> Keypair for Signer<S> {
+    type VerifyingKey = pkcs1v15::VerifyingKey<S>;
+
+    fn verifying_key(&self) -> pkcs1v15::VerifyingKey<S> {
+        self.verifying_key.clone()
+    }
+}
+
+impl<S: SignatureAlgorithm> DigestSigner<S, pkcs1v15::Signature> for Signer<S> {
+    /// Compute an RSASSA-PKCS#1v1.5 signature of the given digest
+    fn try_sign_digest(&self, digest: S) -> Result<pkcs1v15::Signature, Error> {
+        let signature = self
+            .client
+            .sign_rsa_pkcs1v15_prehash(self.signing_key_id, &digest.finalize())?;
+
+        pkcs1v15::Signature::try_from(signature.as_slice())
+    }
+}
+
+impl<S: SignatureAlgorithm> signature::Signer<pkcs1v15::Signature> for Signer<S> {
+    fn try_sign(&self, msg: &[u8]) -> Result<pkcs1v15::Signature, Error> {
+        self.try_sign_digest(S::new_with_prefix(msg))
+    }
+}
+
+impl<S: SignatureAlgorithm> SignatureAlgorithmIdentifier for Signer<S> {
+    type Params = AnyRef<'static>;
+
+    const SIGNATURE_ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> =
+        AlgorithmIdentifierRef {
+            oid: S::PKCS1_OID,
+            parameters: Some(AnyRef::NULL),
+        };
+}
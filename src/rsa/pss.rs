@@ -2,15 +2,19 @@
 //! primitives with the EMSA-PSS encoding method.
 
 mod algorithm;
-#[cfg(feature = "untested")]
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 pub(crate) mod commands;
-#[cfg(feature = "untested")]
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 mod signature;
+#[cfg(feature = "untested")]
+mod signer;
 
 /// Maximum message size supported for RSASSA-PSS
 #[cfg(feature = "untested")]
 pub const MAX_MESSAGE_SIZE: usize = 0xFFFF;
 
 pub use self::algorithm::Algorithm;
-#[cfg(feature = "untested")]
+#[cfg(any(feature = "untested", feature = "mockhsm"))]
 pub use self::signature::Signature;
+#[cfg(feature = "untested")]
+pub use self::signer::Signer;
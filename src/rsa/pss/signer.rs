@@ -0,0 +1,91 @@
+//! RSASSA-PSS signature provider for the YubiHSM 2
+
+use crate::{object, rsa::SignatureAlgorithm, Client};
+use ::rsa::{
+    pkcs1::RsaPssParams,
+    pkcs8::spki::{
+        der::Any, AlgorithmIdentifierOwned, DynSignatureAlgorithmIdentifier, ObjectIdentifier,
+    },
+    pss, RsaPublicKey,
+};
+use sha2::Digest;
+use signature::{DigestSigner, Error, Keypair};
+
+/// Object identifier of the RSASSA-PSS signature algorithm (`id-RSASSA-PSS`)
+const RSASSA_PSS_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10");
+
+/// RSASSA-PSS signature provider for yubihsm-client, generic over the
+/// digest algorithm `S` (SHA-256, SHA-384, or SHA-512).
+///
+/// Signatures use MGF1 with the same digest, and a salt the length of the
+/// digest output.
+pub struct Signer<S: SignatureAlgorithm> {
+    /// YubiHSM client
+    client: Client,
+
+    /// ID of an RSA key to perform signatures with
+    signing_key_id: object::Id,
+
+    /// Verifying key which corresponds to this signer
+    verifying_key: pss::VerifyingKey<S>,
+}
+
+impl<S: SignatureAlgorithm> Signer<S> {
+    /// Create a new YubiHSM-backed RSASSA-PSS signer
+    pub fn create(client: Client, signing_key_id: object::Id) -> Result<Self, Error> {
+        let public_key = client
+            .get_public_key(signing_key_id)?
+            .rsa()
+            .ok_or_else(Error::new)?;
+
+        Ok(Self {
+            client,
+            signing_key_id,
+            verifying_key: pss::VerifyingKey::new(public_key),
+        })
+    }
+
+    /// Get the public key for the YubiHSM-backed RSA private key
+    pub fn public_key(&self) -> &RsaPublicKey {
+        self.verifying_key.as_ref()
+    }
+}
+
+impl<S: SignatureAlgorithm> Keypair for Signer<S> {
+    type VerifyingKey = pss::VerifyingKey<S>;
+
+    fn verifying_key(&self) -> pss::VerifyingKey<S> {
+        self.verifying_key.clone()
+    }
+}
+
+impl<S: SignatureAlgorithm> DigestSigner<S, pss::Signature> for Signer<S> {
+    /// Compute an RSASSA-PSS signature of the given digest
+    fn try_sign_digest(&self, digest: S) -> Result<pss::Signature, Error> {
+        let signature = self
+            .client
+            .sign_rsa_pss_prehash::<S>(self.signing_key_id, &digest.finalize())?;
+
+        pss::Signature::try_from(signature.as_slice())
+    }
+}
+
+impl<S: SignatureAlgorithm> signature::Signer<pss::Signature> for Signer<S> {
+    fn try_sign(&self, msg: &[u8]) -> Result<pss::Signature, Error> {
+        self.try_sign_digest(S::new_with_prefix(msg))
+    }
+}
+
+impl<S: SignatureAlgorithm> DynSignatureAlgorithmIdentifier for Signer<S> {
+    fn signature_algorithm_identifier(
+        &self,
+    ) -> ::rsa::pkcs8::spki::Result<AlgorithmIdentifierOwned> {
+        // Salts are the length of the digest output
+        let params = RsaPssParams::new::<S>(<S as Digest>::output_size() as u8);
+
+        Ok(AlgorithmIdentifierOwned {
+            oid: RSASSA_PSS_OID,
+            parameters: Some(Any::encode_from(&params)?),
+        })
+    }
+}
@@ -0,0 +1,34 @@
+//! Digest algorithms for RSA signatures computed by the HSM
+
+use super::mgf;
+use ::rsa::pkcs8::spki::ObjectIdentifier;
+use sha2::{
+    digest::{const_oid::AssociatedOid, Digest, FixedOutputReset},
+    Sha256, Sha384, Sha512,
+};
+
+/// Digest algorithms which can be used with HSM-backed RSASSA-PKCS#1v1.5
+/// and RSASSA-PSS signatures
+pub trait SignatureAlgorithm: AssociatedOid + Digest + FixedOutputReset + Default {
+    /// Hash algorithm to use with MGF1 when computing RSASSA-PSS signatures
+    const MGF_ALGORITHM: mgf::Algorithm;
+
+    /// Object identifier of the RSASSA-PKCS#1v1.5 signature algorithm
+    /// using this digest, e.g. `sha256WithRSAEncryption`
+    const PKCS1_OID: ObjectIdentifier;
+}
+
+impl SignatureAlgorithm for Sha256 {
+    const MGF_ALGORITHM: mgf::Algorithm = mgf::Algorithm::Sha256;
+    const PKCS1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+}
+
+impl SignatureAlgorithm for Sha384 {
+    const MGF_ALGORITHM: mgf::Algorithm = mgf::Algorithm::Sha384;
+    const PKCS1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12");
+}
+
+impl SignatureAlgorithm for Sha512 {
+    const MGF_ALGORITHM: mgf::Algorithm = mgf::Algorithm::Sha512;
+    const PKCS1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13");
+}
@@ -20,12 +20,40 @@ pub fn deserialize<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, Er
     serde::Deserialize::deserialize(&mut deserializer)
 }
 
+/// Deserialize a byte slice into an instance of `T`, which must consume all
+/// of its bytes
+#[cfg(feature = "mockhsm")]
+pub fn deserialize_exact<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    let mut cursor = Cursor::new(bytes);
+    let value = serde::Deserialize::deserialize(&mut de::Deserializer::new(&mut cursor))?;
+    let trailing = bytes.len() - cursor.position() as usize;
+
+    ensure!(
+        trailing == 0,
+        ErrorKind::TrailingData,
+        "{} bytes left over after deserializing",
+        trailing
+    );
+
+    Ok(value)
+}
+
 /// Implement serde serializers/deserializers for array newtypes
 macro_rules! impl_array_serializers {
     ($ty:ident, $size:expr) => {
         impl ::serde::Serialize for $ty {
             fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-                self.0.serialize(serializer)
+                use ::serde::ser::SerializeTuple;
+
+                // Serialize element-by-element, as serde only implements
+                // `Serialize` for arrays of up to 32 elements
+                let mut tuple = serializer.serialize_tuple($size)?;
+
+                for byte in self.0.iter() {
+                    tuple.serialize_element(byte)?;
+                }
+
+                tuple.end()
             }
         }
 
@@ -22,6 +22,10 @@ pub enum ErrorKind {
     /// Unexpected end-of-buffer/file
     #[error("unexpected end of buffer")]
     UnexpectedEof,
+
+    /// Bytes left over after deserializing a message
+    #[error("trailing data")]
+    TrailingData,
 }
 
 impl ErrorKind {
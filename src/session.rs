@@ -25,7 +25,7 @@ pub use self::{
 use self::{commands::CloseSessionCommand, securechannel::SecureChannel};
 use crate::{
     authentication::Credentials,
-    command::{self, Command, Code},
+    command::{self, Code, Command},
     connector::Connector,
     device, response,
     serialization::deserialize,
@@ -79,6 +79,7 @@ impl Session {
         );
 
         let channel = SecureChannel::open(&connector, credentials)?;
+        let channel_authenticated = channel.is_authenticated();
         let now = Instant::now();
 
         let mut session = Session {
@@ -90,7 +91,11 @@ impl Session {
             timeout,
         };
 
-        session.authenticate(credentials)?;
+        // Asymmetrically authenticated channels are already authenticated
+        // once the session has been created
+        if !channel_authenticated {
+            session.authenticate(credentials)?;
+        }
 
         Ok(session)
     }
@@ -163,7 +168,7 @@ impl Session {
             Code::PutOtpAead => Some("Put OTP AEAD"),
             Code::SetLogIndex => Some("Set Log index"),
             Code::ChangeAuthenticationKey => Some("Change Authentication Key"),
-            _ => None
+            _ => None,
         };
 
         if let Some(notification_content) = notification_option {
@@ -256,6 +261,17 @@ impl Session {
 
         if response.is_err() {
             session_error!(self, "uuid={} error={:?}", &uuid, response.code);
+
+            // The HSM only responds with an unencrypted error when it rejects
+            // a message outright (e.g. the session timed out, or the MAC was
+            // invalid) without advancing the session's counters, so they're
+            // now out of sync and the secure channel can't be used further
+            self.abort();
+
+            if let Some(kind) = device::ErrorKind::from_response_message(&response) {
+                return Err(kind.into());
+            }
+
             fail!(
                 ErrorKind::ResponseError,
                 "HSM error (session: {})",
@@ -272,7 +288,7 @@ impl Session {
             self,
             "command={:?} key={}",
             command::Code::AuthenticateSession,
-            credentials.authentication_key_id
+            credentials.authentication_key_id()
         );
 
         let command = self.secure_channel()?.authenticate_session()?;
@@ -286,17 +302,23 @@ impl Session {
                 self,
                 "failed={:?} key={} err={:?}",
                 command::Code::AuthenticateSession,
-                credentials.authentication_key_id,
+                credentials.authentication_key_id(),
                 e.to_string()
             );
 
             return Err(e);
         }
 
-        session_debug!(self, "auth=OK key={}", credentials.authentication_key_id);
+        session_debug!(self, "auth=OK key={}", credentials.authentication_key_id());
         Ok(())
     }
 
+    /// Set the number of messages sent during this session
+    #[cfg(all(test, feature = "mockhsm"))]
+    pub(crate) fn set_messages_sent(&mut self, messages_sent: u32) {
+        self.secure_channel().unwrap().set_counter(messages_sent);
+    }
+
     /// Get the underlying channel or return an error
     fn secure_channel(&mut self) -> Result<&mut SecureChannel, Error> {
         self.secure_channel
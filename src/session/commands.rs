@@ -2,6 +2,8 @@
 //!
 //! <https://developers.yubico.com/YubiHSM2/Commands/Create_Session.html>
 
+#[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+use super::securechannel::asymmetric::{EphemeralPublicKey, Receipt};
 use super::securechannel::{Challenge, Cryptogram};
 use crate::{
     command::{self, Command},
@@ -38,6 +40,40 @@ impl Response for CreateSessionResponse {
     const COMMAND_CODE: command::Code = command::Code::CreateSession;
 }
 
+/// Request parameters for `command::create_session` when authenticating
+/// with an asymmetric (EC P-256) authentication key
+#[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct CreateAsymmetricSessionCommand {
+    /// Authentication key ID to use
+    pub authentication_key_id: object::Id,
+
+    /// Ephemeral public key generated by the host
+    pub host_public_key: EphemeralPublicKey,
+}
+
+#[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+impl Command for CreateAsymmetricSessionCommand {
+    type ResponseType = CreateAsymmetricSessionResponse;
+}
+
+/// Response from `command::create_session` when authenticating with an
+/// asymmetric (EC P-256) authentication key
+#[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct CreateAsymmetricSessionResponse {
+    /// Ephemeral public key generated by the card
+    pub card_public_key: EphemeralPublicKey,
+
+    /// Receipt confirming the key agreement
+    pub receipt: Receipt,
+}
+
+#[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+impl Response for CreateAsymmetricSessionResponse {
+    const COMMAND_CODE: command::Code = command::Code::CreateSession;
+}
+
 /// Close the current session and release its resources for reuse
 ///
 /// <https://developers.yubico.com/YubiHSM2/Commands/Close_Session.html>
@@ -17,7 +17,13 @@
 //! For more information on the YubiHSM 2 command format, see:
 //!
 //! <https://developers.yubico.com/YubiHSM2/Commands/>
+//!
+//! Sessions authenticated with asymmetric (EC P-256) credentials use the
+//! same channel, but with session keys established via ECDH (see the
+//! `asymmetric` module) instead of the SCP03 challenge/response.
 
+#[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+pub(crate) mod asymmetric;
 mod challenge;
 mod context;
 mod cryptogram;
@@ -30,12 +36,19 @@ pub(crate) use self::{
     cryptogram::{Cryptogram, CRYPTOGRAM_SIZE},
     mac::Mac,
 };
+#[cfg(feature = "asymmetric-auth")]
+use super::commands::{CreateAsymmetricSessionCommand, CreateAsymmetricSessionResponse};
 use super::commands::{CreateSessionCommand, CreateSessionResponse};
 use crate::{
+    asymmetric::PublicKey,
     authentication::{self, Credentials},
     command,
     connector::Connector,
-    device, response,
+    device::{
+        self,
+        commands::{GetDevicePublicKeyCommand, GetDevicePublicKeyResponse},
+    },
+    object, response,
     serialization::deserialize,
     session::{self, ErrorKind},
 };
@@ -82,8 +95,9 @@ pub(crate) struct SecureChannel {
     // TODO(tarcieri): use session types to model the protocol state machine?
     security_level: SecurityLevel,
 
-    /// Context (card + host challenges)
-    context: Context,
+    /// Context (card + host challenges). Only present for symmetrically
+    /// authenticated (i.e. SCP03) sessions.
+    context: Option<Context>,
 
     /// Session encryption key (S-ENC)
     enc_key: [u8; KEY_SIZE],
@@ -104,14 +118,142 @@ impl SecureChannel {
     pub(crate) fn open(
         connector: &Connector,
         credentials: &Credentials,
+    ) -> Result<Self, session::Error> {
+        match credentials {
+            Credentials::Symmetric {
+                authentication_key_id,
+                authentication_key,
+            } => Self::open_symmetric(connector, *authentication_key_id, authentication_key),
+            #[cfg(feature = "asymmetric-auth")]
+            Credentials::Asymmetric {
+                authentication_key_id,
+                private_key,
+                device_public_key,
+            } => Self::open_asymmetric(
+                connector,
+                *authentication_key_id,
+                private_key,
+                device_public_key.as_ref(),
+            ),
+        }
+    }
+
+    /// Open a SecureChannel using a symmetric `authentication::Key`
+    fn open_symmetric(
+        connector: &Connector,
+        authentication_key_id: object::Id,
+        authentication_key: &authentication::Key,
     ) -> Result<Self, session::Error> {
         let host_challenge = Challenge::new();
 
         let command_message = command::Message::from(&CreateSessionCommand {
-            authentication_key_id: credentials.authentication_key_id,
+            authentication_key_id,
             host_challenge,
         });
 
+        let (id, response_message) =
+            Self::create_session(connector, authentication_key_id, command_message)?;
+
+        let session_response: CreateSessionResponse = deserialize(response_message.data.as_ref())?;
+
+        // Derive session keys from the combination of host and card challenges.
+        // If either of them are incorrect (indicating a key mismatch) it will
+        // result in a cryptogram verification failure.
+        let channel = Self::new(
+            id,
+            authentication_key,
+            host_challenge,
+            session_response.card_challenge,
+        );
+
+        if channel
+            .card_cryptogram()
+            .ct_eq(&session_response.card_cryptogram)
+            .unwrap_u8()
+            != 1
+        {
+            fail!(
+                ErrorKind::AuthenticationError,
+                "(session: {}) invalid credentials for authentication key #{} (cryptogram mismatch)",
+                channel.id().to_u8(),
+                authentication_key_id,
+            );
+        }
+
+        Ok(channel)
+    }
+
+    /// Open a SecureChannel using an asymmetric (EC P-256) private key,
+    /// performing ECDH against the device public key. The resulting channel
+    /// is already authenticated.
+    ///
+    /// The device public key is fetched with an unauthenticated command, so
+    /// it must match `expected_device_public_key` (if pinned).
+    #[cfg(feature = "asymmetric-auth")]
+    fn open_asymmetric(
+        connector: &Connector,
+        authentication_key_id: object::Id,
+        private_key: &p256::SecretKey,
+        expected_device_public_key: Option<&p256::PublicKey>,
+    ) -> Result<Self, session::Error> {
+        let device_public_key = get_device_public_key(connector)?
+            .ecdsa::<p256::NistP256>()
+            .and_then(|point| p256::PublicKey::from_sec1_bytes(point.as_bytes()).ok())
+            .ok_or_else(|| format_err!(ErrorKind::ProtocolError, "invalid device public key"))?;
+
+        if let Some(expected_device_public_key) = expected_device_public_key {
+            ensure!(
+                &device_public_key == expected_device_public_key,
+                ErrorKind::AuthenticationError,
+                "device public key mismatch for authentication key #{}",
+                authentication_key_id
+            );
+        }
+
+        let ephemeral_secret = p256::SecretKey::random(&mut rand_core::OsRng);
+        let host_public_key = asymmetric::EphemeralPublicKey::from(&ephemeral_secret.public_key());
+
+        let command_message = command::Message::from(&CreateAsymmetricSessionCommand {
+            authentication_key_id,
+            host_public_key,
+        });
+
+        let (id, response_message) =
+            Self::create_session(connector, authentication_key_id, command_message)?;
+
+        let session_response: CreateAsymmetricSessionResponse =
+            deserialize(response_message.data.as_ref())?;
+
+        let session_keys = asymmetric::SessionKeys::derive(
+            &ephemeral_secret,
+            private_key,
+            &session_response.card_public_key.to_public_key()?,
+            &device_public_key,
+        );
+
+        // The receipt can only be computed by a card which holds both the
+        // device private key and knows our authentication public key
+        let receipt = session_keys.receipt(&session_response.card_public_key, &host_public_key);
+
+        if receipt.ct_eq(&session_response.receipt).unwrap_u8() != 1 {
+            fail!(
+                ErrorKind::AuthenticationError,
+                "(session: {}) invalid credentials for authentication key #{} (receipt mismatch)",
+                id.to_u8(),
+                authentication_key_id,
+            );
+        }
+
+        Ok(Self::new_asymmetric(id, &session_keys, &receipt))
+    }
+
+    /// Send a `CreateSession` command message, returning the new session's ID
+    /// along with the response
+    fn create_session(
+        connector: &Connector,
+        authentication_key_id: object::Id,
+        command_message: command::Message,
+    ) -> Result<(session::Id, response::Message), session::Error> {
         let uuid = command_message.uuid;
         let response_body = connector.send_message(uuid, command_message.into())?;
         let response_message = response::Message::parse(response_body)?;
@@ -121,7 +263,7 @@ impl SecureChannel {
                 Some(device::ErrorKind::ObjectNotFound) => fail!(
                     ErrorKind::AuthenticationError,
                     "auth key not found: 0x{:04x}",
-                    credentials.authentication_key_id
+                    authentication_key_id
                 ),
                 Some(kind) => return Err(kind.into()),
                 None => fail!(
@@ -145,33 +287,7 @@ impl SecureChannel {
             .session_id
             .ok_or_else(|| format_err!(ErrorKind::CreateFailed, "no session ID in response"))?;
 
-        let session_response: CreateSessionResponse = deserialize(response_message.data.as_ref())?;
-
-        // Derive session keys from the combination of host and card challenges.
-        // If either of them are incorrect (indicating a key mismatch) it will
-        // result in a cryptogram verification failure.
-        let channel = Self::new(
-            id,
-            &credentials.authentication_key,
-            host_challenge,
-            session_response.card_challenge,
-        );
-
-        if channel
-            .card_cryptogram()
-            .ct_eq(&session_response.card_cryptogram)
-            .unwrap_u8()
-            != 1
-        {
-            fail!(
-                ErrorKind::AuthenticationError,
-                "(session: {}) invalid credentials for authentication key #{} (cryptogram mismatch)",
-                channel.id().to_u8(),
-                credentials.authentication_key_id,
-            );
-        }
-
-        Ok(channel)
+        Ok((id, response_message))
     }
 
     /// Create a new channel with the given ID, auth key, and host/card challenges
@@ -191,7 +307,7 @@ impl SecureChannel {
             id,
             counter: 0,
             security_level: SecurityLevel::None,
-            context,
+            context: Some(context),
             enc_key,
             mac_key,
             rmac_key,
@@ -199,6 +315,35 @@ impl SecureChannel {
         }
     }
 
+    /// Create a new, already authenticated channel from session keys
+    /// established using asymmetric authentication. The receipt is used as
+    /// the initial MAC chaining value.
+    #[cfg(any(feature = "asymmetric-auth", feature = "mockhsm"))]
+    pub(crate) fn new_asymmetric(
+        id: session::Id,
+        session_keys: &asymmetric::SessionKeys,
+        receipt: &asymmetric::Receipt,
+    ) -> Self {
+        let mut mac_chaining_value = [0u8; Mac::BYTE_SIZE * 2];
+        mac_chaining_value.copy_from_slice(receipt.as_slice());
+
+        Self {
+            id,
+            counter: 1,
+            security_level: SecurityLevel::Authenticated,
+            context: None,
+            enc_key: session_keys.enc_key,
+            mac_key: session_keys.mac_key,
+            rmac_key: session_keys.rmac_key,
+            mac_chaining_value,
+        }
+    }
+
+    /// Is this channel authenticated?
+    pub(crate) fn is_authenticated(&self) -> bool {
+        self.security_level == SecurityLevel::Authenticated
+    }
+
     /// Get the channel (i.e. session) ID
     pub fn id(&self) -> session::Id {
         self.id
@@ -207,17 +352,24 @@ impl SecureChannel {
     /// Calculate the card's cryptogram for this session
     pub fn card_cryptogram(&self) -> Cryptogram {
         let mut result_bytes = Zeroizing::new([0u8; CRYPTOGRAM_SIZE]);
-        kdf::derive(&self.mac_key, 0, &self.context, result_bytes.as_mut());
+        kdf::derive(&self.mac_key, 0, self.context(), result_bytes.as_mut());
         Cryptogram::from_slice(result_bytes.as_ref())
     }
 
     /// Calculate the host's cryptogram for this session
     pub fn host_cryptogram(&self) -> Cryptogram {
         let mut result_bytes = Zeroizing::new([0u8; CRYPTOGRAM_SIZE]);
-        kdf::derive(&self.mac_key, 1, &self.context, result_bytes.as_mut());
+        kdf::derive(&self.mac_key, 1, self.context(), result_bytes.as_mut());
         Cryptogram::from_slice(result_bytes.as_ref())
     }
 
+    /// Borrow the SCP03 derivation context for this session
+    fn context(&self) -> &Context {
+        self.context
+            .as_ref()
+            .expect("no SCP03 context for asymmetrically authenticated session")
+    }
+
     /// Compute a command message with a MAC value for this session
     pub fn command_with_mac(
         &mut self,
@@ -394,7 +546,11 @@ impl SecureChannel {
         &mut self,
         command: &command::Message,
     ) -> Result<response::Message, session::Error> {
-        assert_eq!(self.security_level, SecurityLevel::None);
+        if self.security_level != SecurityLevel::None {
+            self.terminate();
+            fail!(ErrorKind::ProtocolError, "session already authenticated");
+        }
+
         assert_eq!(self.mac_chaining_value, [0u8; Mac::BYTE_SIZE * 2]);
 
         if command.data.len() != CRYPTOGRAM_SIZE {
@@ -439,7 +595,10 @@ impl SecureChannel {
         &mut self,
         encrypted_command: command::Message,
     ) -> Result<command::Message, session::Error> {
-        assert_eq!(self.security_level, SecurityLevel::Authenticated);
+        if self.security_level != SecurityLevel::Authenticated {
+            self.terminate();
+            fail!(ErrorKind::ProtocolError, "session not authenticated");
+        }
 
         let cipher = Aes128::new_from_slice(&self.enc_key).unwrap();
         let icv = compute_icv(&cipher, self.counter);
@@ -493,9 +652,7 @@ impl SecureChannel {
         if command
             .mac
             .as_ref()
-            .expect("missing C-MAC tag!")
-            .verify(&tag)
-            .is_err()
+            .map_or(true, |mac| mac.verify(&tag).is_err())
         {
             self.terminate();
             fail!(ErrorKind::VerifyFailed, "C-MAC mismatch!");
@@ -572,6 +729,12 @@ impl SecureChannel {
         self.counter as usize
     }
 
+    /// Set the internal message counter
+    #[cfg(all(test, feature = "mockhsm"))]
+    pub(crate) fn set_counter(&mut self, counter: u32) {
+        self.counter = counter;
+    }
+
     /// Increment the internal message counter
     fn increment_counter(&mut self) {
         self.counter = self.counter.checked_add(1).unwrap_or_else(|| {
@@ -625,6 +788,38 @@ fn compute_icv(cipher: &Aes128, counter: u32) -> GenericArray<u8, U16> {
     icv
 }
 
+/// Get the device public key, used to establish asymmetrically authenticated
+/// sessions. This command is sent unauthenticated (i.e. outside of a session)
+pub(crate) fn get_device_public_key(connector: &Connector) -> Result<PublicKey, session::Error> {
+    let command_message = command::Message::from(&GetDevicePublicKeyCommand {});
+    let uuid = command_message.uuid;
+    let response_body = connector.send_message(uuid, command_message.into())?;
+    let response_message = response::Message::parse(response_body)?;
+
+    if response_message.is_err() {
+        match device::ErrorKind::from_response_message(&response_message) {
+            Some(kind) => return Err(kind.into()),
+            None => fail!(
+                ErrorKind::ResponseError,
+                "HSM error: {:?}",
+                response_message.code
+            ),
+        }
+    }
+
+    if response_message.command() != Some(command::Code::GetDevicePublicKey) {
+        fail!(
+            ErrorKind::ProtocolError,
+            "command type mismatch: expected {:?}, got {:?}",
+            command::Code::GetDevicePublicKey,
+            response_message.command()
+        );
+    }
+
+    let response: GetDevicePublicKeyResponse = deserialize(response_message.data.as_ref())?;
+    Ok(response.into())
+}
+
 #[cfg(all(test, feature = "mockhsm"))]
 mod tests {
     use super::*;
@@ -734,4 +929,19 @@ mod tests {
             "cryptographic verification failed: R-MAC mismatch!"
         );
     }
+
+    #[test]
+    fn command_limit_test() {
+        let (mut host_channel, _) = create_channel_pair();
+        host_channel.set_counter(MAX_COMMANDS_PER_SESSION);
+
+        let err = host_channel
+            .encrypt_command(
+                command::Message::create(COMMAND_CODE, Vec::from(COMMAND_DATA)).unwrap(),
+            )
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ErrorKind::CommandLimitExceeded);
+        assert_eq!(host_channel.security_level, SecurityLevel::Terminated);
+    }
 }
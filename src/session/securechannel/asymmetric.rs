@@ -0,0 +1,259 @@
+//! Asymmetric (EC P-256) authentication for establishing a secure channel.
+//!
+//! Rather than deriving session keys from a pre-shared symmetric key, the
+//! host and the card each contribute an ephemeral P-256 key in addition to
+//! their static keys. Session keys are derived from both ECDH shared secrets
+//! using the ANSI X9.63 KDF with SHA-256, and the card proves it holds the
+//! device private key by returning a "receipt": an AES-CMAC over both
+//! ephemeral public keys, keyed with an additional derived key.
+
+use super::KEY_SIZE;
+use crate::session::{self, ErrorKind};
+use aes::{cipher::KeyInit, Aes128};
+use cmac::{digest::Mac as _, Cmac};
+use p256::{
+    ecdh::diffie_hellman,
+    elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint},
+    EncodedPoint,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Size of an uncompressed SEC1-encoded P-256 public key
+pub const EPHEMERAL_PUBLIC_KEY_SIZE: usize = 65;
+
+/// Size of a receipt
+pub const RECEIPT_SIZE: usize = 16;
+
+/// X9.63 KDF "shared info": key usage, key type (AES) and key length
+const KDF_SHARED_INFO: [u8; 3] = [0x3c, 0x88, KEY_SIZE as u8];
+
+/// Ephemeral P-256 public key (uncompressed SEC1 encoding) sent by either
+/// the host or the card
+#[derive(Copy, Clone, Debug)]
+pub struct EphemeralPublicKey([u8; EPHEMERAL_PUBLIC_KEY_SIZE]);
+
+impl EphemeralPublicKey {
+    /// Decode this ephemeral key as a P-256 public key
+    pub fn to_public_key(self) -> Result<p256::PublicKey, session::Error> {
+        EncodedPoint::from_bytes(self.0)
+            .ok()
+            .and_then(|point| Option::from(p256::PublicKey::from_encoded_point(&point)))
+            .ok_or_else(|| {
+                format_err!(ErrorKind::ProtocolError, "invalid ephemeral public key").into()
+            })
+    }
+
+    /// Borrow the encoded public key as a slice
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<&p256::PublicKey> for EphemeralPublicKey {
+    fn from(public_key: &p256::PublicKey) -> Self {
+        let mut bytes = [0u8; EPHEMERAL_PUBLIC_KEY_SIZE];
+        bytes.copy_from_slice(public_key.to_encoded_point(false).as_bytes());
+        EphemeralPublicKey(bytes)
+    }
+}
+
+impl_array_serializers!(EphemeralPublicKey, EPHEMERAL_PUBLIC_KEY_SIZE);
+
+/// Receipt computed by the card to confirm the key agreement
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Receipt([u8; RECEIPT_SIZE]);
+
+impl Receipt {
+    /// Borrow the receipt value as a slice
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl ConstantTimeEq for Receipt {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+/// Keys derived from the asymmetric key agreement
+pub struct SessionKeys {
+    /// Key used to compute the receipt
+    receipt_key: [u8; KEY_SIZE],
+
+    /// Session encryption key (S-ENC)
+    pub(super) enc_key: [u8; KEY_SIZE],
+
+    /// Session Command MAC key (S-MAC)
+    pub(super) mac_key: [u8; KEY_SIZE],
+
+    /// Session Respose MAC key (S-RMAC)
+    pub(super) rmac_key: [u8; KEY_SIZE],
+}
+
+impl SessionKeys {
+    /// Derive session keys from our ephemeral and static secret keys and
+    /// the other party's ephemeral and static public keys
+    pub fn derive(
+        ephemeral_secret: &p256::SecretKey,
+        static_secret: &p256::SecretKey,
+        ephemeral_public: &p256::PublicKey,
+        static_public: &p256::PublicKey,
+    ) -> Self {
+        let ephemeral_shared = diffie_hellman(
+            ephemeral_secret.to_nonzero_scalar(),
+            ephemeral_public.as_affine(),
+        );
+        let static_shared =
+            diffie_hellman(static_secret.to_nonzero_scalar(), static_public.as_affine());
+
+        let mut shared_secret = Zeroizing::new(Vec::with_capacity(64));
+        shared_secret.extend_from_slice(ephemeral_shared.raw_secret_bytes());
+        shared_secret.extend_from_slice(static_shared.raw_secret_bytes());
+
+        let mut output = Zeroizing::new([0u8; KEY_SIZE * 4]);
+        x963_kdf(&shared_secret, &KDF_SHARED_INFO, output.as_mut());
+
+        let mut keys = Self {
+            receipt_key: [0u8; KEY_SIZE],
+            enc_key: [0u8; KEY_SIZE],
+            mac_key: [0u8; KEY_SIZE],
+            rmac_key: [0u8; KEY_SIZE],
+        };
+
+        keys.receipt_key.copy_from_slice(&output[..KEY_SIZE]);
+        keys.enc_key
+            .copy_from_slice(&output[KEY_SIZE..KEY_SIZE * 2]);
+        keys.mac_key
+            .copy_from_slice(&output[KEY_SIZE * 2..KEY_SIZE * 3]);
+        keys.rmac_key.copy_from_slice(&output[KEY_SIZE * 3..]);
+        keys
+    }
+
+    /// Compute the receipt over the card's and host's ephemeral public keys
+    pub fn receipt(
+        &self,
+        card_public_key: &EphemeralPublicKey,
+        host_public_key: &EphemeralPublicKey,
+    ) -> Receipt {
+        let mut mac = <Cmac<Aes128> as KeyInit>::new_from_slice(&self.receipt_key).unwrap();
+        mac.update(card_public_key.as_slice());
+        mac.update(host_public_key.as_slice());
+
+        let mut receipt = [0u8; RECEIPT_SIZE];
+        receipt.copy_from_slice(&mac.finalize().into_bytes());
+        Receipt(receipt)
+    }
+}
+
+impl Drop for SessionKeys {
+    fn drop(&mut self) {
+        self.receipt_key.zeroize();
+        self.enc_key.zeroize();
+        self.mac_key.zeroize();
+        self.rmac_key.zeroize();
+    }
+}
+
+/// ANSI X9.63 key derivation function using SHA-256
+fn x963_kdf(shared_secret: &[u8], shared_info: &[u8], output: &mut [u8]) {
+    for (i, chunk) in output.chunks_mut(Sha256::output_size()).enumerate() {
+        let counter = (i as u32) + 1;
+        let digest = Sha256::new()
+            .chain_update(shared_secret)
+            .chain_update(counter.to_be_bytes())
+            .chain_update(shared_info)
+            .finalize();
+
+        chunk.copy_from_slice(&digest[..chunk.len()]);
+    }
+}
+
+// Known-answer tests. The expected values were computed independently with
+// pyca/cryptography's `X963KDF` and `CMAC`, following python-yubihsm's
+// asymmetric session key derivation.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// X9.63 KDF output for the shared secret `00 01 .. 3f`
+    const KDF_OUTPUT: [u8; KEY_SIZE * 4] = [
+        0x78, 0xe6, 0xaf, 0xba, 0x79, 0x8e, 0x33, 0x8b, 0x0b, 0x61, 0x04, 0xdf, 0xc1, 0x8e, 0x5b,
+        0x9e, 0xfa, 0xab, 0xdf, 0x39, 0xc9, 0x91, 0xde, 0x68, 0x79, 0xd9, 0xc7, 0xa0, 0xc2, 0x1f,
+        0xf0, 0x22, 0x40, 0x99, 0x8c, 0xe3, 0x8b, 0x6d, 0x3d, 0xd3, 0xfd, 0x3f, 0xa9, 0xc7, 0xd9,
+        0x56, 0xb6, 0x73, 0x23, 0xd0, 0x69, 0xaf, 0x64, 0x57, 0x58, 0x66, 0x00, 0x43, 0x1b, 0x7e,
+        0xc8, 0x3d, 0x38, 0xc7,
+    ];
+
+    /// Session keys and receipt for the secret scalars `01 01 ..` (host
+    /// ephemeral), `02 02 ..` (host static), `03 03 ..` (card ephemeral)
+    /// and `04 04 ..` (device static)
+    const ENC_KEY: [u8; KEY_SIZE] = [
+        0x56, 0x54, 0x67, 0x96, 0x25, 0x46, 0x6f, 0x12, 0xba, 0xfc, 0x05, 0x18, 0x99, 0x1a, 0x39,
+        0xc1,
+    ];
+    const MAC_KEY: [u8; KEY_SIZE] = [
+        0xec, 0x96, 0x19, 0xec, 0xea, 0xda, 0x29, 0x6d, 0x95, 0x3e, 0xeb, 0x7c, 0x57, 0x84, 0x8c,
+        0xda,
+    ];
+    const RMAC_KEY: [u8; KEY_SIZE] = [
+        0xe0, 0x6f, 0xd2, 0x45, 0xa0, 0xb0, 0xf2, 0x3f, 0x5b, 0xb8, 0x41, 0x13, 0x9e, 0x0d, 0x7c,
+        0x1c,
+    ];
+    const RECEIPT: [u8; RECEIPT_SIZE] = [
+        0xca, 0x73, 0x70, 0x76, 0x82, 0x9a, 0x62, 0x16, 0x75, 0xae, 0x77, 0x0a, 0x78, 0xeb, 0x92,
+        0x93,
+    ];
+
+    fn secret_key(byte: u8) -> p256::SecretKey {
+        p256::SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn x963_kdf_test() {
+        let shared_secret = (0..64).collect::<Vec<u8>>();
+        let mut output = [0u8; KEY_SIZE * 4];
+        x963_kdf(&shared_secret, &KDF_SHARED_INFO, &mut output);
+        assert_eq!(output, KDF_OUTPUT);
+    }
+
+    #[test]
+    fn session_keys_and_receipt_test() {
+        let host_ephemeral = secret_key(1);
+        let host_static = secret_key(2);
+        let card_ephemeral = secret_key(3);
+        let device_static = secret_key(4);
+
+        let host_keys = SessionKeys::derive(
+            &host_ephemeral,
+            &host_static,
+            &card_ephemeral.public_key(),
+            &device_static.public_key(),
+        );
+
+        assert_eq!(host_keys.enc_key, ENC_KEY);
+        assert_eq!(host_keys.mac_key, MAC_KEY);
+        assert_eq!(host_keys.rmac_key, RMAC_KEY);
+
+        let receipt = host_keys.receipt(
+            &EphemeralPublicKey::from(&card_ephemeral.public_key()),
+            &EphemeralPublicKey::from(&host_ephemeral.public_key()),
+        );
+        assert_eq!(receipt.as_slice(), RECEIPT);
+
+        // The card derives the same keys from the other halves of each pair
+        let card_keys = SessionKeys::derive(
+            &card_ephemeral,
+            &device_static,
+            &host_ephemeral.public_key(),
+            &host_static.public_key(),
+        );
+
+        assert_eq!(card_keys.enc_key, ENC_KEY);
+        assert_eq!(card_keys.mac_key, MAC_KEY);
+        assert_eq!(card_keys.rmac_key, RMAC_KEY);
+    }
+}
@@ -64,17 +64,34 @@ impl Role {
 
     /// Create this role within the YubiHSM 2 device
     pub fn create(&self, client: &Client) -> Result<(), Error> {
-        client
-            .put_authentication_key(
-                self.credentials.authentication_key_id,
+        match &self.credentials {
+            Credentials::Symmetric {
+                authentication_key_id,
+                authentication_key,
+            } => client.put_authentication_key(
+                *authentication_key_id,
                 self.authentication_key_label.clone(),
                 self.domains,
                 self.capabilities,
                 self.delegated_capabilities,
                 Default::default(),
-                self.credentials.authentication_key.clone(),
-            )
-            .map_err(|e| format_err!(ErrorKind::SetupFailed, "error creating role: {}", e))?;
+                authentication_key.clone(),
+            ),
+            #[cfg(feature = "asymmetric-auth")]
+            Credentials::Asymmetric {
+                authentication_key_id,
+                private_key,
+                ..
+            } => client.put_asymmetric_authentication_key(
+                *authentication_key_id,
+                self.authentication_key_label.clone(),
+                self.domains,
+                self.capabilities,
+                self.delegated_capabilities,
+                &private_key.public_key(),
+            ),
+        }
+        .map_err(|e| format_err!(ErrorKind::SetupFailed, "error creating role: {}", e))?;
 
         Ok(())
     }
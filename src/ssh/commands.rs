@@ -29,10 +29,8 @@ pub(crate) struct SignSshCertificateCommand {
     /// Timestamp
     pub timestamp: u32,
 
-    /// Signature over the request and timestamp
-    pub signature: [u8; 32],
-
-    /// Data to be signed
+    /// Signature over the timestamp and request (made with the template's
+    /// timestamp key), followed by the request to be signed
     pub request: Vec<u8>,
 }
 
@@ -42,7 +40,7 @@ impl Command for SignSshCertificateCommand {
 
 /// Signed SSH certificates
 #[derive(Serialize, Deserialize, Debug)]
-pub struct SignSshCertificateResponse(ssh::Certificate);
+pub struct SignSshCertificateResponse(pub(crate) ssh::Certificate);
 
 impl Response for SignSshCertificateResponse {
     const COMMAND_CODE: command::Code = command::Code::SignSshCertificate;
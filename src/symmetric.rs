@@ -0,0 +1,13 @@
+//! Symmetric (AES) encryption keys and block cipher modes (firmware 2.3+)
+
+mod algorithm;
+pub(crate) mod commands;
+mod mode;
+
+pub use self::{algorithm::Algorithm, mode::Mode};
+
+/// AES block size in bytes. ECB and CBC inputs must be a multiple of this.
+pub const BLOCK_SIZE: usize = 16;
+
+/// Initialization vector for AES-CBC
+pub type Iv = [u8; BLOCK_SIZE];
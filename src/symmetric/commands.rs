@@ -0,0 +1,12 @@
+//! Symmetric key commands
+
+mod decrypt_cbc;
+mod decrypt_ecb;
+mod encrypt_cbc;
+mod encrypt_ecb;
+mod generate_key;
+mod put_key;
+
+pub(crate) use self::{
+    decrypt_cbc::*, decrypt_ecb::*, encrypt_cbc::*, encrypt_ecb::*, generate_key::*, put_key::*,
+};
@@ -0,0 +1,36 @@
+//! Decrypt data using a symmetric key in AES-CBC mode
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Cbc.html>
+
+use crate::{
+    command::{self, Command},
+    object,
+    response::Response,
+    symmetric,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::decrypt_cbc`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptCbcCommand {
+    /// ID of the symmetric key
+    pub key_id: object::Id,
+
+    /// Initialization vector
+    pub iv: symmetric::Iv,
+
+    /// Ciphertext to be decrypted (multiple of the block size)
+    pub data: Vec<u8>,
+}
+
+impl Command for DecryptCbcCommand {
+    type ResponseType = DecryptCbcResponse;
+}
+
+/// Response from `command::decrypt_cbc` containing the plaintext
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptCbcResponse(pub(crate) Vec<u8>);
+
+impl Response for DecryptCbcResponse {
+    const COMMAND_CODE: command::Code = command::Code::DecryptCbc;
+}
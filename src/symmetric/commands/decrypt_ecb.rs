@@ -0,0 +1,32 @@
+//! Decrypt data using a symmetric key in AES-ECB mode
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Decrypt_Ecb.html>
+
+use crate::{
+    command::{self, Command},
+    object,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::decrypt_ecb`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptEcbCommand {
+    /// ID of the symmetric key
+    pub key_id: object::Id,
+
+    /// Ciphertext to be decrypted (multiple of the block size)
+    pub data: Vec<u8>,
+}
+
+impl Command for DecryptEcbCommand {
+    type ResponseType = DecryptEcbResponse;
+}
+
+/// Response from `command::decrypt_ecb` containing the plaintext
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DecryptEcbResponse(pub(crate) Vec<u8>);
+
+impl Response for DecryptEcbResponse {
+    const COMMAND_CODE: command::Code = command::Code::DecryptEcb;
+}
@@ -0,0 +1,36 @@
+//! Encrypt data using a symmetric key in AES-CBC mode
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Encrypt_Cbc.html>
+
+use crate::{
+    command::{self, Command},
+    object,
+    response::Response,
+    symmetric,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::encrypt_cbc`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct EncryptCbcCommand {
+    /// ID of the symmetric key
+    pub key_id: object::Id,
+
+    /// Initialization vector
+    pub iv: symmetric::Iv,
+
+    /// Plaintext to be encrypted (multiple of the block size)
+    pub data: Vec<u8>,
+}
+
+impl Command for EncryptCbcCommand {
+    type ResponseType = EncryptCbcResponse;
+}
+
+/// Response from `command::encrypt_cbc` containing the ciphertext
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct EncryptCbcResponse(pub(crate) Vec<u8>);
+
+impl Response for EncryptCbcResponse {
+    const COMMAND_CODE: command::Code = command::Code::EncryptCbc;
+}
@@ -0,0 +1,32 @@
+//! Encrypt data using a symmetric key in AES-ECB mode
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Encrypt_Ecb.html>
+
+use crate::{
+    command::{self, Command},
+    object,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::encrypt_ecb`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct EncryptEcbCommand {
+    /// ID of the symmetric key
+    pub key_id: object::Id,
+
+    /// Plaintext to be encrypted (multiple of the block size)
+    pub data: Vec<u8>,
+}
+
+impl Command for EncryptEcbCommand {
+    type ResponseType = EncryptEcbResponse;
+}
+
+/// Response from `command::encrypt_ecb` containing the ciphertext
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct EncryptEcbResponse(pub(crate) Vec<u8>);
+
+impl Response for EncryptEcbResponse {
+    const COMMAND_CODE: command::Code = command::Code::EncryptEcb;
+}
@@ -0,0 +1,29 @@
+//! Generate a new symmetric key within the `YubiHSM 2`
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Generate_Symmetric_Key.html>
+
+use crate::{
+    command::{self, Command},
+    object::{self, generate},
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::generate_symmetric_key`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct GenSymmetricKeyCommand(pub(crate) generate::Params);
+
+impl Command for GenSymmetricKeyCommand {
+    type ResponseType = GenSymmetricKeyResponse;
+}
+
+/// Response from `command::generate_symmetric_key`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct GenSymmetricKeyResponse {
+    /// ID of the key
+    pub key_id: object::Id,
+}
+
+impl Response for GenSymmetricKeyResponse {
+    const COMMAND_CODE: command::Code = command::Code::GenerateSymmetricKey;
+}
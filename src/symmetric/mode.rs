@@ -0,0 +1,36 @@
+//! Block cipher modes of operation
+
+use crate::algorithm;
+
+/// Block cipher modes supported by symmetric keys
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Mode {
+    /// `aes-ecb`: Electronic Codebook
+    Ecb = 0x35,
+
+    /// `aes-cbc`: Cipher Block Chaining
+    Cbc = 0x36,
+}
+
+impl Mode {
+    /// Convert an unsigned byte tag into a `symmetric::Mode` (if valid)
+    pub fn from_u8(tag: u8) -> Result<Self, algorithm::Error> {
+        Ok(match tag {
+            0x35 => Mode::Ecb,
+            0x36 => Mode::Cbc,
+            _ => fail!(
+                algorithm::ErrorKind::TagInvalid,
+                "unknown block cipher mode ID: 0x{:02x}",
+                tag
+            ),
+        })
+    }
+
+    /// Serialize mode ID as a byte
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+}
+
+impl_algorithm_serializers!(Mode);
@@ -8,6 +8,7 @@ mod info;
 mod key;
 mod message;
 mod nonce;
+mod rsa_message;
 
 pub use self::{
     algorithm::Algorithm,
@@ -16,4 +17,5 @@ pub use self::{
     key::Key,
     message::Message,
     nonce::Nonce,
+    rsa_message::{RsaMessage, RsaPlaintext},
 };
@@ -1,10 +1,19 @@
 mod export;
+#[cfg(feature = "sha2")]
+mod export_rsa;
 mod generate_key;
 mod import;
+#[cfg(feature = "sha2")]
+mod import_rsa;
 mod put_key;
+#[cfg(feature = "sha2")]
+mod put_public_key;
 mod unwrap_data;
 mod wrap_data;
 
 pub(crate) use self::{
     export::*, generate_key::*, import::*, put_key::*, unwrap_data::*, wrap_data::*,
 };
+
+#[cfg(feature = "sha2")]
+pub(crate) use self::{export_rsa::*, import_rsa::*, put_public_key::*};
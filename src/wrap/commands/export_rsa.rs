@@ -0,0 +1,46 @@
+//! Export an object from the `YubiHSM 2`, encrypted under an RSA public wrap key
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Export_Wrapped_Rsa.html>
+
+use crate::{
+    command::{self, Command},
+    object,
+    response::Response,
+    rsa::{mgf, oaep},
+    symmetric, wrap,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::export_wrapped_rsa`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct ExportWrappedRsaCommand {
+    /// ID of the public wrap key to encrypt the object with
+    pub wrap_key_id: object::Id,
+
+    /// Type of object to be wrapped
+    pub object_type: object::Type,
+
+    /// Object ID of the object to be exported (in encrypted form)
+    pub object_id: object::Id,
+
+    /// Algorithm of the ephemeral AES key used to wrap the object
+    pub aes_algorithm: symmetric::Algorithm,
+
+    /// Hash algorithm to use for RSA-OAEP
+    pub oaep_algorithm: oaep::Algorithm,
+
+    /// Hash algorithm to use for MGF1
+    pub mgf1_algorithm: mgf::Algorithm,
+}
+
+impl Command for ExportWrappedRsaCommand {
+    type ResponseType = ExportWrappedRsaResponse;
+}
+
+/// Response from `command::export_wrapped_rsa`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct ExportWrappedRsaResponse(pub(crate) wrap::RsaMessage);
+
+impl Response for ExportWrappedRsaResponse {
+    const COMMAND_CODE: command::Code = command::Code::ExportWrappedRsa;
+}
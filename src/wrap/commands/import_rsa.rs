@@ -0,0 +1,45 @@
+//! Import an object encrypted under an RSA public wrap key into the `YubiHSM 2`
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Import_Wrapped_Rsa.html>
+
+use crate::{
+    command::{self, Command},
+    object,
+    response::Response,
+    rsa::{mgf, oaep},
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::import_wrapped_rsa`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct ImportWrappedRsaCommand {
+    /// ID of the RSA private key to decrypt the object with
+    pub unwrap_key_id: object::Id,
+
+    /// Hash algorithm to use for RSA-OAEP
+    pub oaep_algorithm: oaep::Algorithm,
+
+    /// Hash algorithm to use for MGF1
+    pub mgf1_algorithm: mgf::Algorithm,
+
+    /// Ciphertext of the encrypted object
+    pub ciphertext: Vec<u8>,
+}
+
+impl Command for ImportWrappedRsaCommand {
+    type ResponseType = ImportWrappedRsaResponse;
+}
+
+/// Response from `command::import_wrapped_rsa`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct ImportWrappedRsaResponse {
+    /// Type of object
+    pub object_type: object::Type,
+
+    /// ID of the decrypted object
+    pub object_id: object::Id,
+}
+
+impl Response for ImportWrappedRsaResponse {
+    const COMMAND_CODE: command::Code = command::Code::ImportWrappedRsa;
+}
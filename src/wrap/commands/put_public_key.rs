@@ -0,0 +1,39 @@
+//! Put an RSA public wrap key into the `YubiHSM 2`
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Put_Public_Wrap_Key.html>
+
+use crate::{
+    capability::Capability,
+    command::{self, Command},
+    object,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+/// Request parameters for `command::put_public_wrap_key`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct PutPublicWrapKeyCommand {
+    /// Common parameters to all put object commands
+    pub params: object::put::Params,
+
+    /// Delegated capabilities
+    pub delegated_capabilities: Capability,
+
+    /// RSA public modulus
+    pub public_key: Vec<u8>,
+}
+
+impl Command for PutPublicWrapKeyCommand {
+    type ResponseType = PutPublicWrapKeyResponse;
+}
+
+/// Response from `command::put_public_wrap_key`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct PutPublicWrapKeyResponse {
+    /// ID of the key
+    pub key_id: object::Id,
+}
+
+impl Response for PutPublicWrapKeyResponse {
+    const COMMAND_CODE: command::Code = command::Code::PutPublicWrapKey;
+}
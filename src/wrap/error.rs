@@ -12,6 +12,10 @@ pub enum ErrorKind {
     /// Wrap message is an invalid length
     #[error("invalid message length")]
     LengthInvalid,
+
+    /// Wrap message could not be decrypted
+    #[error("decryption failed")]
+    DecryptFailed,
 }
 
 impl ErrorKind {
@@ -116,46 +116,66 @@ impl Plaintext {
         C: PrimeCurve + CurveAlgorithm + ValidatePublicKey,
         FieldBytesSize<C>: ModulusSize + Unsigned,
     {
-        if let algorithm::Algorithm::Asymmetric(alg) = self.object_info.algorithm {
-            if C::asymmetric_algorithm() == alg {
-                let mut reader = SliceReader(&self.data);
-
-                SecretKey::<C>::from_slice(reader.read(FieldBytesSize::<C>::USIZE)?).ok()
-            } else {
-                None
-            }
+        ecdsa_key(&self.object_info, &self.data)
+    }
+
+    pub fn rsa(&self) -> Option<RsaPrivateKey> {
+        rsa_key(&self.object_info, &self.data)
+    }
+}
+
+/// Parse an ECDSA private key from a wrapped object's data
+pub(super) fn ecdsa_key<C>(object_info: &wrap::Info, data: &[u8]) -> Option<SecretKey<C>>
+where
+    C: PrimeCurve + CurveAlgorithm + ValidatePublicKey,
+    FieldBytesSize<C>: ModulusSize + Unsigned,
+{
+    if let algorithm::Algorithm::Asymmetric(alg) = object_info.algorithm {
+        if C::asymmetric_algorithm() == alg {
+            let mut reader = SliceReader(data);
+
+            SecretKey::<C>::from_slice(reader.read(FieldBytesSize::<C>::USIZE)?).ok()
         } else {
             None
         }
+    } else {
+        None
     }
+}
 
-    pub fn rsa(&self) -> Option<RsaPrivateKey> {
-        let (component_size, modulus_size) = match self.object_info.algorithm {
-            algorithm::Algorithm::Asymmetric(asymmetric::Algorithm::Rsa2048) => (128, 256),
-            algorithm::Algorithm::Asymmetric(asymmetric::Algorithm::Rsa3072) => (192, 384),
-            algorithm::Algorithm::Asymmetric(asymmetric::Algorithm::Rsa4096) => (256, 512),
-            _ => return None,
-        };
-
-        let mut reader = SliceReader(&self.data);
-
-        let p = BigUint::from_bytes_be(reader.read(component_size)?);
-        let q = BigUint::from_bytes_be(reader.read(component_size)?);
-        let dp = BigUint::from_bytes_be(reader.read(component_size)?);
-        let dq = BigUint::from_bytes_be(reader.read(component_size)?);
-        let _qinv = BigUint::from_bytes_be(reader.read(component_size)?);
-        let n = BigUint::from_bytes_be(reader.read(modulus_size)?);
-        const EXP: u64 = 65537;
-        let e = BigUint::from_u64(EXP).expect("invalid static exponent");
-
-        let d = e
-            .clone()
-            .mod_inverse((dp - BigUint::one()) * (dq - BigUint::one()))?
-            .to_biguint()?;
-
-        let private_key = RsaPrivateKey::from_components(n, e, d, vec![p, q]).ok()?;
-        Some(private_key)
+/// Parse an RSA private key from a wrapped object's data
+pub(super) fn rsa_key(object_info: &wrap::Info, data: &[u8]) -> Option<RsaPrivateKey> {
+    let (component_size, modulus_size) = match object_info.algorithm {
+        algorithm::Algorithm::Asymmetric(asymmetric::Algorithm::Rsa2048) => (128, 256),
+        algorithm::Algorithm::Asymmetric(asymmetric::Algorithm::Rsa3072) => (192, 384),
+        algorithm::Algorithm::Asymmetric(asymmetric::Algorithm::Rsa4096) => (256, 512),
+        _ => return None,
+    };
+
+    let mut reader = SliceReader(data);
+
+    let p = BigUint::from_bytes_be(reader.read(component_size)?);
+    let q = BigUint::from_bytes_be(reader.read(component_size)?);
+
+    // The CRT parameters `dp || dq || qinv` are recomputed from the primes
+    reader.read(component_size * 3)?;
+
+    let n = BigUint::from_bytes_be(reader.read(modulus_size)?);
+    const EXP: u64 = 65537;
+    let e = BigUint::from_u64(EXP).expect("invalid static exponent");
+
+    if p <= BigUint::one() || q <= BigUint::one() || n != &p * &q {
+        return None;
     }
+
+    // The private exponent is the inverse of `e` modulo `(p - 1)(q - 1)`
+    let d = e
+        .clone()
+        .mod_inverse((&p - BigUint::one()) * (&q - BigUint::one()))?
+        .to_biguint()?;
+
+    let private_key = RsaPrivateKey::from_components(n, e, d, vec![p, q]).ok()?;
+    Some(private_key)
 }
 
 /// Support structure to read from a slice like a reader
@@ -0,0 +1,163 @@
+//! Objects exported under an RSA public wrap key
+
+use super::message::{ecdsa_key, rsa_key};
+use crate::{ecdsa::algorithm::CurveAlgorithm, wrap};
+use aes::cipher::Unsigned;
+use ecdsa::{
+    elliptic_curve::{
+        sec1::{ModulusSize, ValidatePublicKey},
+        FieldBytesSize, SecretKey,
+    },
+    PrimeCurve,
+};
+use rsa::RsaPrivateKey;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(feature = "sha2")]
+use {
+    super::{Error, ErrorKind},
+    crate::{rsa::oaep::DigestAlgorithm, serialization::deserialize},
+    ::rsa::{traits::PublicKeyParts, Oaep},
+    aes_kw::{KekAes128, KekAes192, KekAes256},
+};
+
+#[cfg(all(feature = "sha2", feature = "mockhsm"))]
+use {
+    crate::{serialization::serialize, symmetric},
+    ::rsa::RsaPublicKey,
+    rand_core::{OsRng, RngCore},
+};
+
+/// Object exported under an RSA public wrap key: an ephemeral AES key
+/// encrypted using RSA-OAEP, followed by the object encrypted under the
+/// ephemeral key using AES key wrap with padding (AES-KWP, RFC 5649)
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct RsaMessage(pub Vec<u8>);
+
+impl RsaMessage {
+    /// Load an `RsaMessage` from a byte vector
+    pub fn from_vec(vec: Vec<u8>) -> Self {
+        RsaMessage(vec)
+    }
+
+    /// Convert this message into a byte vector
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Borrow the message as a byte slice
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Decrypt the [`RsaMessage`] with the RSA private key corresponding to
+    /// the public wrap key it was exported under, using RSA-OAEP with the
+    /// digest `D` (which must match the one used to export it)
+    #[cfg(feature = "sha2")]
+    pub fn decrypt<D: DigestAlgorithm>(&self, key: &RsaPrivateKey) -> Result<RsaPlaintext, Error> {
+        let modulus_size = key.size();
+
+        ensure!(
+            self.0.len() > modulus_size,
+            ErrorKind::LengthInvalid,
+            "message must be longer than {}-bytes",
+            modulus_size
+        );
+
+        let (encrypted_key, ciphertext) = self.0.split_at(modulus_size);
+
+        let ephemeral_key = key
+            .decrypt(Oaep::new::<D>(), encrypted_key)
+            .map_err(|e| format_err!(ErrorKind::DecryptFailed, "RSA-OAEP error: {}", e))?;
+
+        let plaintext = match ephemeral_key.len() {
+            16 => KekAes128::try_from(ephemeral_key.as_slice())
+                .and_then(|kek| kek.unwrap_with_padding_vec(ciphertext)),
+            24 => KekAes192::try_from(ephemeral_key.as_slice())
+                .and_then(|kek| kek.unwrap_with_padding_vec(ciphertext)),
+            32 => KekAes256::try_from(ephemeral_key.as_slice())
+                .and_then(|kek| kek.unwrap_with_padding_vec(ciphertext)),
+            other => fail!(
+                ErrorKind::DecryptFailed,
+                "invalid ephemeral key size: {}",
+                other
+            ),
+        }
+        .map_err(|e| format_err!(ErrorKind::DecryptFailed, "AES-KWP error: {}", e))?;
+
+        deserialize(&plaintext)
+            .map_err(|e| format_err!(ErrorKind::DecryptFailed, "invalid object: {}", e).into())
+    }
+}
+
+impl From<Vec<u8>> for RsaMessage {
+    fn from(vec: Vec<u8>) -> Self {
+        RsaMessage(vec)
+    }
+}
+
+impl AsRef<[u8]> for RsaMessage {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Object decrypted from an [`RsaMessage`]. The object data is zeroized on
+/// drop.
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct RsaPlaintext {
+    /// Information about the exported object
+    #[zeroize(skip)]
+    pub object_info: wrap::Info,
+
+    /// Object data (i.e. key material)
+    pub data: Vec<u8>,
+}
+
+impl RsaPlaintext {
+    /// Encrypt this object under the given RSA public wrap key, using an
+    /// ephemeral key of the given AES algorithm
+    #[cfg(all(feature = "sha2", feature = "mockhsm"))]
+    pub(crate) fn encrypt<D: DigestAlgorithm>(
+        &self,
+        public_key: &RsaPublicKey,
+        algorithm: symmetric::Algorithm,
+    ) -> Result<RsaMessage, Error> {
+        let mut ephemeral_key = vec![0u8; algorithm.key_len()];
+        OsRng.fill_bytes(&mut ephemeral_key);
+
+        let wire = serialize(self).unwrap();
+
+        let ciphertext = match algorithm {
+            symmetric::Algorithm::Aes128 => KekAes128::try_from(ephemeral_key.as_slice())
+                .and_then(|kek| kek.wrap_with_padding_vec(&wire)),
+            symmetric::Algorithm::Aes192 => KekAes192::try_from(ephemeral_key.as_slice())
+                .and_then(|kek| kek.wrap_with_padding_vec(&wire)),
+            symmetric::Algorithm::Aes256 => KekAes256::try_from(ephemeral_key.as_slice())
+                .and_then(|kek| kek.wrap_with_padding_vec(&wire)),
+        }
+        .unwrap();
+
+        let mut message = public_key
+            .encrypt(&mut OsRng, Oaep::new::<D>(), &ephemeral_key)
+            .unwrap();
+
+        message.extend_from_slice(&ciphertext);
+        Ok(RsaMessage(message))
+    }
+
+    /// Get the ECDSA private key of the given curve type, if applicable
+    pub fn ecdsa<C>(&self) -> Option<SecretKey<C>>
+    where
+        C: PrimeCurve + CurveAlgorithm + ValidatePublicKey,
+        FieldBytesSize<C>: ModulusSize + Unsigned,
+    {
+        ecdsa_key(&self.object_info, &self.data)
+    }
+
+    /// Get the RSA private key, if applicable
+    pub fn rsa(&self) -> Option<RsaPrivateKey> {
+        rsa_key(&self.object_info, &self.data)
+    }
+}
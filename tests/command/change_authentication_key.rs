@@ -0,0 +1,147 @@
+#[cfg(feature = "mockhsm")]
+use std::time::Duration;
+#[cfg(all(feature = "mockhsm", feature = "asymmetric-auth"))]
+use yubihsm::client;
+use yubihsm::{authentication, object, Capability, Client, Credentials};
+#[cfg(feature = "mockhsm")]
+use yubihsm::{
+    device,
+    mockhsm::{ManualClock, MockHsm},
+    Connector,
+};
+
+use crate::{clear_test_key_slot, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL, TEST_MESSAGE};
+
+/// Change an authentication key in-place and reauthenticate with it
+#[test]
+fn change_authentication_key() {
+    let client = crate::get_hsm_client();
+
+    clear_test_key_slot(&client, object::Type::AuthenticationKey);
+
+    let old_authentication_key =
+        authentication::Key::derive_from_password(TEST_KEY_LABEL.as_bytes());
+    let new_authentication_key = authentication::Key::derive_from_password(TEST_MESSAGE);
+
+    client
+        .put_authentication_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::CHANGE_AUTHENTICATION_KEY,
+            Capability::empty(),
+            authentication::Algorithm::YubicoAes,
+            old_authentication_key.clone(),
+        )
+        .unwrap_or_else(|err| panic!("error putting auth key: {err}"));
+
+    let test_client = Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new(TEST_KEY_ID, old_authentication_key.clone()),
+        true,
+    )
+    .unwrap_or_else(|err| panic!("error authenticating with old auth key: {err}"));
+
+    let key_id = test_client
+        .change_authentication_key(TEST_KEY_ID, new_authentication_key.clone())
+        .unwrap_or_else(|err| panic!("error changing auth key: {err}"));
+
+    assert_eq!(key_id, TEST_KEY_ID);
+
+    Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new(TEST_KEY_ID, new_authentication_key),
+        false,
+    )
+    .unwrap_or_else(|err| panic!("error authenticating with new auth key: {err}"));
+
+    assert!(Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new(TEST_KEY_ID, old_authentication_key),
+        false,
+    )
+    .is_err());
+}
+
+/// Asymmetric authentication keys can't be changed to symmetric ones
+#[cfg(all(feature = "mockhsm", feature = "asymmetric-auth"))]
+#[test]
+fn change_asymmetric_authentication_key() {
+    let private_key = p256::SecretKey::from_slice(&[0x42; 32]).unwrap();
+
+    let hsm = MockHsm::builder()
+        .asymmetric_authentication_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::CHANGE_AUTHENTICATION_KEY,
+            Capability::empty(),
+            &private_key.public_key(),
+        )
+        .build()
+        .unwrap_or_else(|err| panic!("error building MockHsm: {err}"));
+
+    let client = Client::open(
+        Connector::from(hsm),
+        Credentials::new_asymmetric_unpinned(TEST_KEY_ID, private_key),
+        true,
+    )
+    .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    let err = client
+        .change_authentication_key(
+            TEST_KEY_ID,
+            authentication::Key::derive_from_password(TEST_MESSAGE),
+        )
+        .expect_err("expected asymmetric key change to be rejected");
+
+    assert_eq!(*err.kind(), client::ErrorKind::ProtocolError);
+}
+
+/// Clones of a client reconnect with the new key once the session it was
+/// changed in is closed
+#[cfg(feature = "mockhsm")]
+#[test]
+fn reconnect_after_change_authentication_key() {
+    let clock = ManualClock::new();
+    let connector = Connector::from(MockHsm::with_clock(clock.clone()));
+
+    let old_authentication_key =
+        authentication::Key::derive_from_password(TEST_KEY_LABEL.as_bytes());
+    let new_authentication_key = authentication::Key::derive_from_password(TEST_MESSAGE);
+
+    Client::open(connector.clone(), Credentials::default(), false)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"))
+        .put_authentication_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::CHANGE_AUTHENTICATION_KEY,
+            Capability::empty(),
+            authentication::Algorithm::YubicoAes,
+            old_authentication_key.clone(),
+        )
+        .unwrap_or_else(|err| panic!("error putting auth key: {err}"));
+
+    let client = Client::open(
+        connector,
+        Credentials::new(TEST_KEY_ID, old_authentication_key),
+        true,
+    )
+    .unwrap_or_else(|err| panic!("error authenticating with old auth key: {err}"));
+
+    let cloned_client = client.clone();
+
+    client
+        .change_authentication_key(TEST_KEY_ID, new_authentication_key)
+        .unwrap_or_else(|err| panic!("error changing auth key: {err}"));
+
+    // Time out the session the key was changed in
+    clock.advance(Duration::from_secs(30));
+
+    let err = cloned_client.echo(b"hello").unwrap_err();
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidSession));
+
+    // Reconnecting only succeeds with the new key
+    assert_eq!(cloned_client.echo(b"hello").unwrap(), b"hello");
+}
@@ -0,0 +1,58 @@
+//! Session table limits and inactivity timeouts, driven by a `ManualClock`
+
+use std::time::Duration;
+use yubihsm::{
+    device,
+    mockhsm::{ManualClock, MockHsm},
+    Client, Connector, Credentials,
+};
+
+/// The MockHsm allows at most 16 concurrent sessions, and frees sessions
+/// which have timed out
+#[test]
+fn session_limit_test() {
+    let clock = ManualClock::new();
+    let connector = Connector::from(MockHsm::with_clock(clock.clone()));
+
+    let clients = (0..16)
+        .map(|_| Client::open(connector.clone(), Credentials::default(), false))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    let err = Client::open(connector.clone(), Credentials::default(), false)
+        .err()
+        .expect("expected all sessions to be in use");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::SessionsFull));
+
+    clock.advance(Duration::from_secs(30));
+
+    let client = Client::open(connector, Credentials::default(), false)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+    drop(clients);
+}
+
+/// Sessions idle for 30 seconds are closed, after which the client reconnects
+#[test]
+fn session_timeout_test() {
+    let clock = ManualClock::new();
+    let connector = Connector::from(MockHsm::with_clock(clock.clone()));
+
+    let client = Client::open(connector, Credentials::default(), true)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    // Activity keeps the session alive
+    for _ in 0..3 {
+        clock.advance(Duration::from_secs(29));
+        assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+    }
+
+    clock.advance(Duration::from_secs(30));
+
+    let err = client.echo(b"hello").unwrap_err();
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidSession));
+
+    // The timed out session was discarded, so a new one is opened
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+}
@@ -1,7 +1,13 @@
+#[cfg(feature = "rsa-oaep")]
+use crate::{clear_test_key_slot, TEST_DOMAINS, TEST_KEY_LABEL};
 use crate::{generate_asymmetric_key, TEST_KEY_ID};
 use rand_core;
+#[cfg(feature = "rsa-oaep")]
+use rsa::traits::PrivateKeyParts;
 use sha2::{self, Digest};
 use yubihsm::{asymmetric, Capability};
+#[cfg(feature = "rsa-oaep")]
+use yubihsm::{object, rsa::oaep};
 
 /// Test RSA OAEP decryption
 #[test]
@@ -47,3 +53,165 @@ fn rsa_decrypt_oaep_test() {
 
     assert_eq!(decrypted_data.as_slice(), plaintext);
 }
+
+/// RSA OAEP decryption fails if the label doesn't match the ciphertext's
+#[test]
+fn rsa_decrypt_oaep_wrong_label_test() {
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::Rsa2048,
+        Capability::DECRYPT_OAEP,
+    );
+
+    let raw_public_key = client
+        .get_public_key(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {}", err));
+
+    let rsa_modulus = rsa::BigUint::from_bytes_be(raw_public_key.as_slice());
+    let rsa_exponent = rsa::BigUint::parse_bytes(b"65537", 10).unwrap();
+    let rsa_public_key = rsa::RsaPublicKey::new(rsa_modulus, rsa_exponent).unwrap();
+
+    let ciphertext = rsa_public_key
+        .encrypt(
+            &mut rand_core::OsRng,
+            rsa::Oaep::new_with_label::<sha2::Sha256, _>("expected label"),
+            b"Secret message!",
+        )
+        .expect("Failed to encrypt");
+
+    let result = client.decrypt_oaep(
+        TEST_KEY_ID,
+        yubihsm::rsa::mgf::Algorithm::Sha256,
+        ciphertext,
+        sha2::Sha256::digest(b"other label").to_vec(),
+    );
+
+    assert!(result.is_err());
+}
+
+/// Test RSA OAEP round trip using `rsa::oaep::Encryptor` and `rsa::oaep::Decryptor`
+#[cfg(feature = "rsa-oaep")]
+#[test]
+fn rsa_oaep_encryptor_decryptor_test() {
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::Rsa2048,
+        Capability::DECRYPT_OAEP,
+    );
+
+    let plaintext = b"Secret message!";
+    // Labels needn't be valid UTF-8
+    let label = b"yubihsm.rs test label \xff";
+
+    let encryptor = oaep::Encryptor::<sha2::Sha384>::create(&client, TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error creating encryptor: {}", err));
+
+    assert_eq!(encryptor.algorithm(), asymmetric::Algorithm::Rsa2048);
+
+    let ciphertext = encryptor
+        .encrypt_with_label(plaintext, label)
+        .unwrap_or_else(|err| panic!("error encrypting: {}", err));
+
+    let decryptor = oaep::Decryptor::<sha2::Sha384>::create(client.clone(), TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error creating decryptor: {}", err));
+
+    let decrypted_data = decryptor.decrypt_with_label(&ciphertext, label).unwrap();
+
+    assert_eq!(decrypted_data.as_slice(), plaintext);
+
+    // Ciphertexts which don't match the key size are rejected before reaching the HSM
+    assert!(decryptor.decrypt(&ciphertext[1..]).is_err());
+}
+
+/// Test `rsa::oaep::Decryptor` against ciphertexts produced by the `rsa` crate
+#[cfg(feature = "rsa-oaep")]
+#[test]
+fn rsa_oaep_decryptor_interop_test() {
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::Rsa2048,
+        Capability::DECRYPT_OAEP,
+    );
+
+    let plaintext = b"Secret message!";
+    let label = "yubihsm.rs test label";
+
+    let encryptor = oaep::Encryptor::<sha2::Sha256>::create(&client, TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error creating encryptor: {}", err));
+
+    let ciphertext = encryptor
+        .public_key()
+        .encrypt(
+            &mut rand_core::OsRng,
+            rsa::Oaep::new_with_label::<sha2::Sha256, _>(label),
+            plaintext,
+        )
+        .expect("Failed to encrypt");
+
+    let decryptor = oaep::Decryptor::<sha2::Sha256>::create(client.clone(), TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error creating decryptor: {}", err));
+
+    let decrypted_data = decryptor
+        .decrypt_with_label(&ciphertext, label.as_bytes())
+        .unwrap();
+
+    assert_eq!(decrypted_data.as_slice(), plaintext);
+}
+
+/// Test `rsa::oaep::Encryptor` ciphertexts decrypt with the `rsa` crate
+#[cfg(feature = "rsa-oaep")]
+#[test]
+fn rsa_oaep_encryptor_interop_test() {
+    let client = crate::get_hsm_client();
+    let private_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+
+    let mut key_bytes = private_key.primes()[0].to_bytes_be();
+    key_bytes.extend_from_slice(&private_key.primes()[1].to_bytes_be());
+
+    clear_test_key_slot(&client, object::Type::AsymmetricKey);
+
+    client
+        .put_asymmetric_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::DECRYPT_OAEP,
+            asymmetric::Algorithm::Rsa2048,
+            key_bytes,
+        )
+        .unwrap_or_else(|err| panic!("error putting RSA key: {}", err));
+
+    let plaintext = b"Secret message!";
+    let label = "yubihsm.rs test label";
+
+    let ciphertext = oaep::Encryptor::<sha2::Sha256>::create(&client, TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error creating encryptor: {}", err))
+        .encrypt_with_label(plaintext, label.as_bytes())
+        .unwrap_or_else(|err| panic!("error encrypting: {}", err));
+
+    let decrypted_data = private_key
+        .decrypt(
+            rsa::Oaep::new_with_label::<sha2::Sha256, _>(label),
+            &ciphertext,
+        )
+        .expect("Failed to decrypt");
+
+    assert_eq!(decrypted_data, plaintext);
+
+    let ciphertext = oaep::Encryptor::<sha2::Sha384>::create(&client, TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error creating encryptor: {}", err))
+        .encrypt(plaintext)
+        .unwrap_or_else(|err| panic!("error encrypting: {}", err));
+
+    let decrypted_data = private_key
+        .decrypt(rsa::Oaep::new::<sha2::Sha384>(), &ciphertext)
+        .expect("Failed to decrypt");
+
+    assert_eq!(decrypted_data, plaintext);
+}
@@ -0,0 +1,267 @@
+use crate::{
+    clear_test_key_slot, TEST_DOMAINS, TEST_EXPORTED_KEY_ID, TEST_EXPORTED_KEY_LABEL, TEST_KEY_ID,
+    TEST_KEY_LABEL,
+};
+use aes::cipher::{BlockEncrypt, KeyInit};
+use yubihsm::{object, otp, Capability};
+
+/// YubiKey OTP key used by this test
+const OTP_KEY: [u8; otp::KEY_SIZE] = [
+    0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+];
+
+/// YubiKey private ID used by this test
+const PRIVATE_ID: [u8; otp::private_id::SIZE] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+/// Create an AEAD for a YubiKey credential and decrypt an OTP with it
+#[test]
+fn otp_aead_test() {
+    let client = crate::get_hsm_client();
+    let algorithm = otp::Algorithm::Aes128;
+    let capabilities =
+        Capability::CREATE_OTP_AEAD | Capability::RANDOMIZE_OTP_AEAD | Capability::DECRYPT_OTP;
+
+    clear_test_key_slot(&client, object::Type::OtpAeadKey);
+
+    let key_id = client
+        .generate_otp_aead_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            capabilities,
+            algorithm,
+            otp::Nonce::generate(),
+        )
+        .unwrap_or_else(|err| panic!("error generating OTP AEAD key: {err}"));
+
+    assert_eq!(key_id, TEST_KEY_ID);
+
+    let object_info = client
+        .get_object_info(TEST_KEY_ID, object::Type::OtpAeadKey)
+        .unwrap_or_else(|err| panic!("error getting object info: {err}"));
+
+    assert_eq!(object_info.capabilities, capabilities);
+    assert_eq!(object_info.algorithm, algorithm.into());
+    assert_eq!(object_info.origin, object::Origin::Generated);
+
+    let random_aead = client
+        .randomize_otp_aead(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error randomizing OTP AEAD: {err}"));
+
+    let aead = client
+        .create_otp_aead(TEST_KEY_ID, OTP_KEY, PRIVATE_ID.into())
+        .unwrap_or_else(|err| panic!("error creating OTP AEAD: {err}"));
+
+    assert_ne!(aead, random_aead);
+
+    let expected = otp::DecryptedOtp {
+        use_counter: 0x0102,
+        session_counter: 0x03,
+        timestamp: 0x04_0506,
+    };
+
+    let decrypted = client
+        .decrypt_otp(
+            TEST_KEY_ID,
+            aead.clone(),
+            encrypt_otp(&PRIVATE_ID, &expected),
+        )
+        .unwrap_or_else(|err| panic!("error decrypting OTP: {err}"));
+
+    assert_eq!(decrypted, expected);
+
+    // OTPs for a different private ID must be rejected
+    assert!(client
+        .decrypt_otp(TEST_KEY_ID, aead, encrypt_otp(&[0xff; 6], &expected))
+        .is_err());
+}
+
+/// Re-encrypt an AEAD under another OTP AEAD key and decrypt an OTP with it
+#[test]
+fn rewrap_otp_aead_test() {
+    let client = crate::get_hsm_client();
+    let algorithm = otp::Algorithm::Aes256;
+
+    clear_test_key_slot(&client, object::Type::OtpAeadKey);
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, object::Type::OtpAeadKey);
+
+    for (key_id, label, capabilities) in [
+        (
+            TEST_KEY_ID,
+            TEST_KEY_LABEL,
+            Capability::CREATE_OTP_AEAD | Capability::REWRAP_FROM_OTP_AEAD_KEY,
+        ),
+        (
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL,
+            Capability::REWRAP_TO_OTP_AEAD_KEY | Capability::DECRYPT_OTP,
+        ),
+    ] {
+        client
+            .put_otp_aead_key(
+                key_id,
+                label.into(),
+                TEST_DOMAINS,
+                capabilities,
+                algorithm,
+                otp::Nonce::generate(),
+                vec![key_id as u8; algorithm.key_len()],
+            )
+            .unwrap_or_else(|err| panic!("error putting OTP AEAD key: {err}"));
+    }
+
+    let aead = client
+        .create_otp_aead(TEST_KEY_ID, OTP_KEY, PRIVATE_ID.into())
+        .unwrap_or_else(|err| panic!("error creating OTP AEAD: {err}"));
+
+    let rewrapped_aead = client
+        .rewrap_otp_aead(TEST_KEY_ID, TEST_EXPORTED_KEY_ID, aead.clone())
+        .unwrap_or_else(|err| panic!("error rewrapping OTP AEAD: {err}"));
+
+    let expected = otp::DecryptedOtp {
+        use_counter: 0x0201,
+        session_counter: 0x30,
+        timestamp: 0x06_0504,
+    };
+
+    let decrypted = client
+        .decrypt_otp(
+            TEST_EXPORTED_KEY_ID,
+            rewrapped_aead,
+            encrypt_otp(&PRIVATE_ID, &expected),
+        )
+        .unwrap_or_else(|err| panic!("error decrypting OTP: {err}"));
+
+    assert_eq!(decrypted, expected);
+
+    // The original AEAD can't be decrypted with the key it was rewrapped to
+    assert!(client
+        .decrypt_otp(
+            TEST_EXPORTED_KEY_ID,
+            aead,
+            encrypt_otp(&PRIVATE_ID, &expected)
+        )
+        .is_err());
+}
+
+/// Decrypt an OTP with a known AEAD.
+///
+/// This is not a capture from a device or `yubihsm-shell`: the AEAD and OTP
+/// were computed independently of this crate with pyca/cryptography 48.0.0
+/// (`AESCCM` with an 8-byte tag, and AES-ECB), following the AEAD layout
+/// documented by Yubico: a 6-byte nonce, then the OTP key and private ID
+/// encrypted with AES-CCM under the nonce ID, the nonce and three zero
+/// bytes, then the 8-byte MAC.
+#[test]
+fn otp_aead_known_answer_test() {
+    /// OTP AEAD key (AES-128)
+    const AEAD_KEY: [u8; 16] = [
+        0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e,
+        0x1f,
+    ];
+
+    /// Nonce ID of the OTP AEAD key
+    const NONCE_ID: [u8; otp::nonce::SIZE] = [0x01, 0x02, 0x03, 0x04];
+
+    /// AEAD containing `OTP_KEY` and `PRIVATE_ID`
+    const AEAD: [u8; otp::aead::SIZE] = [
+        0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0x4f, 0x1b, 0x77, 0x70, 0x42, 0x26, 0xcf, 0x1c, 0x52,
+        0x8d, 0xe1, 0x66, 0x2f, 0x81, 0x21, 0xb1, 0x83, 0x07, 0xf9, 0xe0, 0x77, 0x96, 0x53, 0x90,
+        0x28, 0xe3, 0x7f, 0x56, 0x1f, 0x86,
+    ];
+
+    /// OTP for `PRIVATE_ID` with a use counter of 0x0102, a timestamp of
+    /// 0x040506, a session counter of 0x03, and random bytes 0x11 0x22
+    const OTP: [u8; otp::OTP_SIZE] = [
+        0x4f, 0x9a, 0x1c, 0x46, 0x38, 0x77, 0x6b, 0x33, 0x4a, 0x20, 0x65, 0x85, 0xec, 0x66, 0xcf,
+        0xf6,
+    ];
+
+    let client = crate::get_hsm_client();
+
+    clear_test_key_slot(&client, object::Type::OtpAeadKey);
+
+    client
+        .put_otp_aead_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::DECRYPT_OTP,
+            otp::Algorithm::Aes128,
+            otp::Nonce(NONCE_ID),
+            AEAD_KEY,
+        )
+        .unwrap_or_else(|err| panic!("error putting OTP AEAD key: {err}"));
+
+    let decrypted = client
+        .decrypt_otp(TEST_KEY_ID, otp::Aead(AEAD), OTP)
+        .unwrap_or_else(|err| panic!("error decrypting OTP: {err}"));
+
+    assert_eq!(
+        decrypted,
+        otp::DecryptedOtp {
+            use_counter: 0x0102,
+            session_counter: 0x03,
+            timestamp: 0x04_0506,
+        }
+    );
+
+    // AEADs with a corrupted MAC must be rejected
+    let mut corrupted = AEAD;
+    corrupted[otp::aead::SIZE - 1] ^= 0x01;
+    assert!(client
+        .decrypt_otp(TEST_KEY_ID, otp::Aead(corrupted), OTP)
+        .is_err());
+}
+
+/// AEADs can only be parsed from slices of exactly the right length
+#[test]
+fn aead_from_slice_test() {
+    let bytes = [0x42u8; otp::aead::SIZE + 1];
+
+    let aead = otp::Aead::try_from(&bytes[..otp::aead::SIZE]).unwrap();
+    assert_eq!(aead.as_slice(), &bytes[..otp::aead::SIZE]);
+
+    for len in [0, otp::aead::SIZE - 1, otp::aead::SIZE + 1] {
+        let err = otp::Aead::try_from(&bytes[..len]).unwrap_err();
+        assert_eq!(*err.kind(), otp::ErrorKind::SizeInvalid);
+    }
+}
+
+/// Build a YubiKey OTP token and encrypt it under `OTP_KEY`
+fn encrypt_otp(private_id: &[u8], fields: &otp::DecryptedOtp) -> [u8; otp::OTP_SIZE] {
+    let mut token = [0u8; otp::OTP_SIZE];
+    token[..6].copy_from_slice(private_id);
+    token[6..8].copy_from_slice(&fields.use_counter.to_le_bytes());
+    token[8..10].copy_from_slice(&(fields.timestamp as u16).to_le_bytes());
+    token[10] = (fields.timestamp >> 16) as u8;
+    token[11] = fields.session_counter;
+    token[12..14].copy_from_slice(&[0x55, 0xaa]);
+
+    let crc = !crc16(&token[..14]);
+    token[14..].copy_from_slice(&crc.to_le_bytes());
+
+    let mut block = token.into();
+    aes::Aes128::new(&OTP_KEY.into()).encrypt_block(&mut block);
+    block.into()
+}
+
+/// CRC-16 (ISO 13239) as used by YubiKey OTP tokens
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+
+    for byte in data {
+        crc ^= u16::from(*byte);
+
+        for _ in 0..8 {
+            let carry = crc & 1;
+            crc >>= 1;
+
+            if carry != 0 {
+                crc ^= 0x8408;
+            }
+        }
+    }
+
+    crc
+}
@@ -0,0 +1,37 @@
+use crate::{generate_asymmetric_key, TEST_KEY_ID};
+use yubihsm::{asymmetric, Capability};
+
+/// Test RSA PKCS#1v1.5 decryption
+#[test]
+fn rsa_decrypt_pkcs1v15_test() {
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::Rsa2048,
+        Capability::DECRYPT_PKCS,
+    );
+
+    let raw_public_key = client
+        .get_public_key(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"));
+
+    assert_eq!(raw_public_key.algorithm, asymmetric::Algorithm::Rsa2048);
+    assert_eq!(raw_public_key.bytes.len(), 256);
+
+    let plaintext = b"Secret message!";
+
+    let rsa_modulus = rsa::BigUint::from_bytes_be(raw_public_key.as_slice());
+    let rsa_exponent = rsa::BigUint::from(65537u32);
+    let rsa_public_key = rsa::RsaPublicKey::new(rsa_modulus, rsa_exponent).unwrap();
+
+    let ciphertext = rsa_public_key
+        .encrypt(&mut rand_core::OsRng, rsa::Pkcs1v15Encrypt, plaintext)
+        .expect("failed to encrypt");
+
+    let decrypted_data = client
+        .decrypt_pkcs1v15(TEST_KEY_ID, ciphertext)
+        .unwrap_or_else(|err| panic!("error decrypting data: {err}"));
+
+    assert_eq!(decrypted_data.as_slice(), plaintext);
+}
@@ -0,0 +1,91 @@
+use crate::{generate_asymmetric_key, TEST_KEY_ID};
+use ::ecdsa::elliptic_curve::{
+    ecdh::EphemeralSecret,
+    sec1::{self, FromEncodedPoint, ToEncodedPoint},
+    AffinePoint, CurveArithmetic, FieldBytesSize, PublicKey,
+};
+use yubihsm::{
+    asymmetric,
+    ecdsa::{algorithm::CurveAlgorithm, NistP256, NistP384},
+    Capability,
+};
+
+#[cfg(feature = "nistp521")]
+use yubihsm::ecdsa::NistP521;
+
+#[cfg(feature = "secp256k1")]
+use yubihsm::ecdsa::Secp256k1;
+
+/// Perform ECDH between a key in the HSM and an ephemeral key, checking
+/// both sides derive the same shared secret
+fn ecdh_test<C>()
+where
+    C: CurveAlgorithm + CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: sec1::ModulusSize,
+{
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(&client, C::asymmetric_algorithm(), Capability::DERIVE_ECDH);
+
+    let hsm_public_key = client
+        .get_public_key(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"));
+
+    let mut hsm_point = vec![0x04];
+    hsm_point.extend_from_slice(hsm_public_key.as_slice());
+    let hsm_public_key = PublicKey::<C>::from_sec1_bytes(&hsm_point).unwrap();
+
+    let ephemeral_secret = EphemeralSecret::<C>::random(&mut rand_core::OsRng);
+
+    let shared_secret = client
+        .derive_ecdh(TEST_KEY_ID, &ephemeral_secret.public_key())
+        .unwrap_or_else(|err| panic!("error deriving ECDH shared secret: {err}"));
+
+    let expected_secret = ephemeral_secret.diffie_hellman(&hsm_public_key);
+
+    assert_eq!(
+        shared_secret.raw_secret_bytes(),
+        expected_secret.raw_secret_bytes()
+    );
+}
+
+#[test]
+fn ecdh_nistp256_test() {
+    ecdh_test::<NistP256>();
+}
+
+#[test]
+fn ecdh_nistp384_test() {
+    ecdh_test::<NistP384>();
+}
+
+#[cfg(feature = "nistp521")]
+#[test]
+fn ecdh_nistp521_test() {
+    ecdh_test::<NistP521>();
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn ecdh_secp256k1_test() {
+    ecdh_test::<Secp256k1>();
+}
+
+/// ECDH with a key that isn't an EC key must fail
+#[test]
+fn ecdh_wrong_key_type_test() {
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::Ed25519,
+        Capability::DERIVE_ECDH,
+    );
+
+    let ephemeral_secret = EphemeralSecret::<NistP256>::random(&mut rand_core::OsRng);
+
+    assert!(client
+        .derive_ecdh(TEST_KEY_ID, &ephemeral_secret.public_key())
+        .is_err());
+}
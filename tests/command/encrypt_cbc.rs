@@ -0,0 +1,48 @@
+use crate::{
+    clear_test_key_slot, object, test_vectors::AES_CBC_TEST_VECTORS, TEST_DOMAINS, TEST_KEY_ID,
+    TEST_KEY_LABEL,
+};
+use yubihsm::{symmetric, Capability};
+
+/// Test AES-CBC encryption and decryption against NIST SP 800-38A test vectors
+#[test]
+fn aes_cbc_test_vectors() {
+    let client = crate::get_hsm_client();
+    let capabilities = Capability::ENCRYPT_CBC | Capability::DECRYPT_CBC;
+
+    for vector in AES_CBC_TEST_VECTORS {
+        let algorithm = match vector.key.len() {
+            16 => symmetric::Algorithm::Aes128,
+            24 => symmetric::Algorithm::Aes192,
+            32 => symmetric::Algorithm::Aes256,
+            len => panic!("unexpected AES key length: {len}"),
+        };
+
+        clear_test_key_slot(&client, object::Type::SymmetricKey);
+
+        let key_id = client
+            .put_symmetric_key(
+                TEST_KEY_ID,
+                TEST_KEY_LABEL.into(),
+                TEST_DOMAINS,
+                capabilities,
+                algorithm,
+                vector.key,
+            )
+            .unwrap_or_else(|err| panic!("error putting symmetric key: {err}"));
+
+        assert_eq!(key_id, TEST_KEY_ID);
+
+        let ciphertext = client
+            .encrypt_cbc(TEST_KEY_ID, *vector.iv, vector.plaintext)
+            .unwrap_or_else(|err| panic!("error encrypting data: {err}"));
+
+        assert_eq!(ciphertext, vector.ciphertext);
+
+        let plaintext = client
+            .decrypt_cbc(TEST_KEY_ID, *vector.iv, vector.ciphertext)
+            .unwrap_or_else(|err| panic!("error decrypting data: {err}"));
+
+        assert_eq!(plaintext, vector.plaintext);
+    }
+}
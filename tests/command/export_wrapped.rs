@@ -46,6 +46,10 @@ fn wrap_key_test() {
         )
         .unwrap_or_else(|err| panic!("error generating asymmetric key: {err}"));
 
+    let public_key = client
+        .get_public_key(TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"));
+
     let wrap_data = client
         .export_wrapped(TEST_KEY_ID, exported_key_type, TEST_EXPORTED_KEY_ID)
         .unwrap_or_else(|err| panic!("error exporting key: {err}"));
@@ -77,6 +81,14 @@ fn wrap_key_test() {
         &imported_key_info.label.to_string(),
         TEST_EXPORTED_KEY_LABEL
     );
+
+    // The re-imported key is the same key
+    assert_eq!(
+        client
+            .get_public_key(TEST_EXPORTED_KEY_ID)
+            .unwrap_or_else(|err| panic!("error getting public key: {err}")),
+        public_key
+    );
 }
 
 #[test]
@@ -143,3 +155,155 @@ fn wrap_deserialize() {
         public_key
     );
 }
+
+/// Export an RSA key under wrap, decrypt it offline, and re-import it
+#[test]
+fn wrap_rsa_key_test() {
+    let client = crate::get_hsm_client();
+    let capabilities = Capability::EXPORT_WRAPPED | Capability::IMPORT_WRAPPED;
+
+    clear_test_key_slot(&client, object::Type::WrapKey);
+
+    client
+        .put_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            capabilities,
+            Capability::all(),
+            wrap::Algorithm::Aes128Ccm,
+            AESCCM_TEST_VECTORS[0].key,
+        )
+        .unwrap_or_else(|err| panic!("error putting wrap key: {err}"));
+
+    let exported_key_type = object::Type::AsymmetricKey;
+    let exported_key_capabilities = Capability::DECRYPT_PKCS | Capability::EXPORTABLE_UNDER_WRAP;
+
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, exported_key_type);
+
+    client
+        .generate_asymmetric_key(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            exported_key_capabilities,
+            asymmetric::Algorithm::Rsa2048,
+        )
+        .unwrap_or_else(|err| panic!("error generating asymmetric key: {err}"));
+
+    let wrap_data = client
+        .export_wrapped(TEST_KEY_ID, exported_key_type, TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error exporting key: {err}"));
+
+    let wrap_key = wrap::Key::from_bytes(TEST_KEY_ID, AESCCM_TEST_VECTORS[0].key).unwrap();
+
+    let private_key = wrap_data
+        .decrypt(&wrap_key)
+        .expect("failed to decrypt the wrapped key")
+        .rsa()
+        .expect("object did not contain an RSA key");
+
+    private_key.validate().expect("invalid RSA key");
+
+    let public_key = client
+        .get_public_key(TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"))
+        .rsa()
+        .expect("public key was not an RSA key");
+
+    assert_eq!(public_key, private_key.to_public_key());
+
+    // Delete the object from the HSM prior to re-importing it
+    assert!(client
+        .delete_object(TEST_EXPORTED_KEY_ID, exported_key_type)
+        .is_ok());
+
+    client
+        .import_wrapped(TEST_KEY_ID, wrap_data)
+        .unwrap_or_else(|err| panic!("error importing key: {err}"));
+
+    let plaintext = b"Secret message!";
+
+    let ciphertext = public_key
+        .encrypt(&mut rand_core::OsRng, rsa::Pkcs1v15Encrypt, plaintext)
+        .expect("failed to encrypt");
+
+    let decrypted_data = client
+        .decrypt_pkcs1v15(TEST_EXPORTED_KEY_ID, ciphertext)
+        .unwrap_or_else(|err| panic!("error decrypting: {err}"));
+
+    assert_eq!(decrypted_data.as_slice(), plaintext);
+}
+
+/// Export a NIST P-384 key under wrap, decrypt it offline, and re-import it
+#[test]
+fn wrap_nistp384_key_test() {
+    let client = crate::get_hsm_client();
+
+    clear_test_key_slot(&client, object::Type::WrapKey);
+
+    client
+        .put_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::EXPORT_WRAPPED | Capability::IMPORT_WRAPPED,
+            Capability::all(),
+            wrap::Algorithm::Aes128Ccm,
+            AESCCM_TEST_VECTORS[0].key,
+        )
+        .unwrap_or_else(|err| panic!("error generating wrap key: {err}"));
+
+    let exported_key_type = object::Type::AsymmetricKey;
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, exported_key_type);
+
+    client
+        .generate_asymmetric_key(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_ECDSA | Capability::EXPORTABLE_UNDER_WRAP,
+            asymmetric::Algorithm::EcP384,
+        )
+        .unwrap_or_else(|err| panic!("error generating asymmetric key: {err}"));
+
+    let public_key = client
+        .get_public_key(TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"))
+        .ecdsa::<p384::NistP384>()
+        .expect("public key was not a NistP384 object");
+
+    let wrap_data = client
+        .export_wrapped(TEST_KEY_ID, exported_key_type, TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error exporting key: {err}"));
+
+    let wrap_key = wrap::Key::from_bytes(TEST_KEY_ID, AESCCM_TEST_VECTORS[0].key).unwrap();
+
+    let private_key: p384::SecretKey = wrap_data
+        .decrypt(&wrap_key)
+        .expect("failed to decrypt the wrapped key")
+        .ecdsa()
+        .expect("Object did not contain a NistP384 object");
+
+    assert_eq!(
+        p384::EncodedPoint::from(private_key.public_key()),
+        public_key
+    );
+
+    assert!(client
+        .delete_object(TEST_EXPORTED_KEY_ID, exported_key_type)
+        .is_ok());
+
+    client
+        .import_wrapped(TEST_KEY_ID, wrap_data)
+        .unwrap_or_else(|err| panic!("error importing key: {err}"));
+
+    assert_eq!(
+        client
+            .get_public_key(TEST_EXPORTED_KEY_ID)
+            .unwrap_or_else(|err| panic!("error getting public key: {err}"))
+            .ecdsa::<p384::NistP384>()
+            .unwrap(),
+        public_key
+    );
+}
@@ -0,0 +1,240 @@
+use crate::{
+    clear_test_key_slot, TEST_DOMAINS, TEST_EXPORTED_KEY_ID, TEST_EXPORTED_KEY_LABEL, TEST_KEY_ID,
+    TEST_KEY_LABEL,
+};
+use rsa::RsaPrivateKey;
+use sha2::Sha256;
+use yubihsm::{asymmetric, device, object, symmetric, Capability};
+
+/// Test RSA public wrap key workflow: export under an RSA public wrap key,
+/// decrypt the export offline, then re-import it using the RSA private key
+#[test]
+fn rsa_wrap_key_test() {
+    let client = crate::get_hsm_client();
+    let wrap_key = RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+
+    clear_test_key_slot(&client, object::Type::PublicWrapKey);
+
+    let key_id = client
+        .put_public_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::EXPORT_WRAPPED,
+            Capability::all(),
+            &wrap_key.to_public_key(),
+        )
+        .unwrap_or_else(|err| panic!("error putting public wrap key: {err}"));
+
+    assert_eq!(key_id, TEST_KEY_ID);
+
+    // Create a key to export
+    let exported_key_type = object::Type::AsymmetricKey;
+    let exported_key_capabilities = Capability::SIGN_ECDSA | Capability::EXPORTABLE_UNDER_WRAP;
+    let exported_key_algorithm = asymmetric::Algorithm::EcP256;
+
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, exported_key_type);
+
+    client
+        .generate_asymmetric_key(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            exported_key_capabilities,
+            exported_key_algorithm,
+        )
+        .unwrap_or_else(|err| panic!("error generating asymmetric key: {err}"));
+
+    let wrap_data = client
+        .export_wrapped_rsa::<Sha256>(
+            TEST_KEY_ID,
+            exported_key_type,
+            TEST_EXPORTED_KEY_ID,
+            symmetric::Algorithm::Aes256,
+        )
+        .unwrap_or_else(|err| panic!("error exporting key: {err}"));
+
+    let plaintext = wrap_data
+        .decrypt::<Sha256>(&wrap_key)
+        .expect("failed to decrypt the wrapped key");
+
+    assert_eq!(plaintext.object_info.object_id, TEST_EXPORTED_KEY_ID);
+    assert_eq!(plaintext.object_info.object_type, exported_key_type);
+    assert_eq!(
+        plaintext.object_info.algorithm,
+        exported_key_algorithm.into()
+    );
+
+    let private_key: p256::SecretKey = plaintext
+        .ecdsa()
+        .expect("Object did not contain a NistP256 object");
+    let public_key: p256::EncodedPoint = private_key.public_key().into();
+
+    assert_eq!(
+        client
+            .get_public_key(TEST_EXPORTED_KEY_ID)
+            .unwrap_or_else(|err| panic!("error getting public key: {err}"))
+            .ecdsa::<p256::NistP256>()
+            .expect("public key was not a NistP256 object"),
+        public_key
+    );
+
+    // Put the RSA private key into the HSM so it can import the export
+    clear_test_key_slot(&client, object::Type::WrapKey);
+
+    client
+        .put_rsa_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::IMPORT_WRAPPED,
+            exported_key_capabilities,
+            &wrap_key,
+        )
+        .unwrap_or_else(|err| panic!("error putting RSA wrap key: {err}"));
+
+    // Delete the object from the HSM prior to re-importing it
+    assert!(client
+        .delete_object(TEST_EXPORTED_KEY_ID, exported_key_type)
+        .is_ok());
+
+    let import_response = client
+        .import_wrapped_rsa::<Sha256, _>(TEST_KEY_ID, wrap_data)
+        .unwrap_or_else(|err| panic!("error importing key: {err}"));
+
+    assert_eq!(import_response.object_type, exported_key_type);
+    assert_eq!(import_response.object_id, TEST_EXPORTED_KEY_ID);
+
+    let imported_key_info = client
+        .get_object_info(TEST_EXPORTED_KEY_ID, exported_key_type)
+        .unwrap_or_else(|err| panic!("error getting object info: {err}"));
+
+    assert_eq!(imported_key_info.capabilities, exported_key_capabilities);
+    assert_eq!(imported_key_info.algorithm, exported_key_algorithm.into());
+    assert_eq!(imported_key_info.origin, object::Origin::WrappedGenerated);
+}
+
+/// RSA keys exported under an RSA public wrap key can be parsed offline
+#[test]
+fn rsa_wrap_key_export_rsa_key_test() {
+    let client = crate::get_hsm_client();
+    let wrap_key = RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+
+    clear_test_key_slot(&client, object::Type::PublicWrapKey);
+
+    client
+        .put_public_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::EXPORT_WRAPPED,
+            Capability::all(),
+            &wrap_key.to_public_key(),
+        )
+        .unwrap_or_else(|err| panic!("error putting public wrap key: {err}"));
+
+    let exported_key_type = object::Type::AsymmetricKey;
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, exported_key_type);
+
+    client
+        .generate_asymmetric_key(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_PKCS | Capability::EXPORTABLE_UNDER_WRAP,
+            asymmetric::Algorithm::Rsa2048,
+        )
+        .unwrap_or_else(|err| panic!("error generating asymmetric key: {err}"));
+
+    let private_key = client
+        .export_wrapped_rsa::<Sha256>(
+            TEST_KEY_ID,
+            exported_key_type,
+            TEST_EXPORTED_KEY_ID,
+            symmetric::Algorithm::Aes256,
+        )
+        .unwrap_or_else(|err| panic!("error exporting key: {err}"))
+        .decrypt::<Sha256>(&wrap_key)
+        .expect("failed to decrypt the wrapped key")
+        .rsa()
+        .expect("object did not contain an RSA key");
+
+    private_key.validate().expect("invalid RSA key");
+
+    let public_key = client
+        .get_public_key(TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"))
+        .rsa()
+        .expect("public key was not an RSA key");
+
+    assert_eq!(public_key, private_key.to_public_key());
+}
+
+/// Objects can't be imported under an RSA wrap key with capabilities beyond
+/// those delegated to it
+#[test]
+fn rsa_wrap_key_delegated_capabilities_test() {
+    let client = crate::get_hsm_client();
+    let wrap_key = RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+
+    clear_test_key_slot(&client, object::Type::PublicWrapKey);
+    clear_test_key_slot(&client, object::Type::WrapKey);
+
+    client
+        .put_public_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::EXPORT_WRAPPED,
+            Capability::all(),
+            &wrap_key.to_public_key(),
+        )
+        .unwrap_or_else(|err| panic!("error putting public wrap key: {err}"));
+
+    client
+        .put_rsa_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::IMPORT_WRAPPED,
+            Capability::EXPORTABLE_UNDER_WRAP,
+            &wrap_key,
+        )
+        .unwrap_or_else(|err| panic!("error putting RSA wrap key: {err}"));
+
+    let exported_key_type = object::Type::AsymmetricKey;
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, exported_key_type);
+
+    client
+        .generate_asymmetric_key(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_ECDSA | Capability::EXPORTABLE_UNDER_WRAP,
+            asymmetric::Algorithm::EcP256,
+        )
+        .unwrap_or_else(|err| panic!("error generating asymmetric key: {err}"));
+
+    let wrap_data = client
+        .export_wrapped_rsa::<Sha256>(
+            TEST_KEY_ID,
+            exported_key_type,
+            TEST_EXPORTED_KEY_ID,
+            symmetric::Algorithm::Aes256,
+        )
+        .unwrap_or_else(|err| panic!("error exporting key: {err}"));
+
+    client
+        .delete_object(TEST_EXPORTED_KEY_ID, exported_key_type)
+        .unwrap();
+
+    // SIGN_ECDSA isn't delegated to the RSA wrap key
+    let err = client
+        .import_wrapped_rsa::<Sha256, _>(TEST_KEY_ID, wrap_data)
+        .expect_err("expected import to be denied");
+
+    assert_eq!(
+        err.device_error(),
+        Some(device::ErrorKind::InsufficientPermissions)
+    );
+}
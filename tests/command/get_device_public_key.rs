@@ -0,0 +1,17 @@
+use yubihsm::asymmetric;
+
+use crate::EC_P256_PUBLIC_KEY_SIZE;
+
+/// Get the device public key used for asymmetric authentication
+#[test]
+fn get_device_public_key_test() {
+    let client = crate::get_hsm_client();
+
+    let public_key = client
+        .get_device_public_key()
+        .unwrap_or_else(|err| panic!("error getting device public key: {err}"));
+
+    assert_eq!(public_key.algorithm, asymmetric::Algorithm::EcP256);
+    assert_eq!(public_key.len(), EC_P256_PUBLIC_KEY_SIZE);
+    assert!(public_key.ecdsa::<p256::NistP256>().is_some());
+}
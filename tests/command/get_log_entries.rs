@@ -1,10 +1,106 @@
+use sha2::{Digest, Sha256};
+use yubihsm::{
+    audit::{LogEntry, LOG_DIGEST_SIZE},
+    command, AuditOption,
+};
+
+#[cfg(feature = "mockhsm")]
+use yubihsm::device;
+
 /// Get audit log
 #[test]
 fn get_audit_logs_test() {
     let client = crate::get_hsm_client();
 
-    // TODO: test audit logging functionality
     client
+        .set_command_audit_option(command::Code::GetPseudoRandom, AuditOption::On)
+        .unwrap_or_else(|err| panic!("error setting audit option: {err}"));
+
+    client
+        .get_pseudo_random(32)
+        .unwrap_or_else(|err| panic!("error getting random data: {err}"));
+
+    let audit_logs = client
         .get_log_entries()
         .unwrap_or_else(|err| panic!("error getting logs: {err}"));
+
+    assert_eq!(
+        usize::from(audit_logs.num_entries),
+        audit_logs.entries.len()
+    );
+
+    let last_entry = audit_logs.entries.last().unwrap();
+    assert_eq!(last_entry.cmd, command::Code::GetPseudoRandom);
+    assert!(last_entry.result.is_success());
+
+    for pair in audit_logs.entries.windows(2) {
+        assert_eq!(pair[1].item, pair[0].item.wrapping_add(1));
+        assert_eq!(pair[1].digest.0, chained_digest(&pair[1], &pair[0]));
+    }
+}
+
+/// Refuse audited commands when forcing auditing and the log is full
+#[cfg(feature = "mockhsm")]
+#[test]
+fn force_audit_log_full_test() {
+    let client = crate::get_hsm_client();
+
+    let last_item = |client: &yubihsm::Client| {
+        client
+            .get_log_entries()
+            .unwrap_or_else(|err| panic!("error getting logs: {err}"))
+            .entries
+            .last()
+            .unwrap()
+            .item
+    };
+
+    client
+        .set_command_audit_option(command::Code::GetPseudoRandom, AuditOption::On)
+        .unwrap_or_else(|err| panic!("error setting audit option: {err}"));
+
+    client
+        .set_log_index(last_item(&client))
+        .unwrap_or_else(|err| panic!("error setting audit log position: {err}"));
+
+    client
+        .set_force_audit_option(AuditOption::On)
+        .unwrap_or_else(|err| panic!("error setting force option: {err}"));
+
+    let err = (0..100)
+        .find_map(|_| client.get_pseudo_random(1).err())
+        .expect("expected audit log to fill up");
+
+    assert_eq!(err.device_error(), Some(device::ErrorKind::LogFull));
+
+    client
+        .set_log_index(last_item(&client))
+        .unwrap_or_else(|err| panic!("error setting audit log position: {err}"));
+
+    client
+        .get_pseudo_random(1)
+        .unwrap_or_else(|err| panic!("error getting random data: {err}"));
+
+    client
+        .set_force_audit_option(AuditOption::Off)
+        .unwrap_or_else(|err| panic!("error setting force option: {err}"));
+}
+
+/// Compute the truncated SHA-256 digest of a log entry chained onto the
+/// digest of the previous entry
+fn chained_digest(entry: &LogEntry, previous: &LogEntry) -> [u8; LOG_DIGEST_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(entry.item.to_be_bytes());
+    hasher.update([entry.cmd as u8]);
+    hasher.update(entry.length.to_be_bytes());
+    hasher.update(entry.session_key.to_be_bytes());
+    hasher.update(entry.target_key.to_be_bytes());
+    hasher.update(entry.second_key.to_be_bytes());
+    hasher.update([entry.result.to_u8()]);
+    hasher.update(entry.tick.to_be_bytes());
+    hasher.update(previous.digest.0);
+
+    let mut digest = [0u8; LOG_DIGEST_SIZE];
+    digest.copy_from_slice(&hasher.finalize()[..LOG_DIGEST_SIZE]);
+    digest
 }
@@ -1,29 +1,45 @@
 //! Integration tests for YubiHSM 2 commands
 
 pub mod blink_device;
-#[cfg(not(feature = "mockhsm"))]
+#[cfg(feature = "passwords")]
+pub mod change_authentication_key;
+#[cfg(feature = "mockhsm")]
+pub mod create_session;
 pub mod decrypt_oaep;
+pub mod decrypt_otp;
+pub mod decrypt_pkcs1;
 pub mod delete_object;
+pub mod derive_ecdh;
 pub mod device_info;
+pub mod encrypt_cbc;
+pub mod encrypt_ecb;
 pub mod export_wrapped;
+#[cfg(feature = "untested")]
+pub mod export_wrapped_rsa;
 pub mod generate_asymmetric_key;
 pub mod generate_hmac_key;
+pub mod generate_symmetric_key;
 pub mod generate_wrap_key;
+pub mod get_device_public_key;
 pub mod get_log_entries;
 pub mod get_object_info;
 pub mod get_option;
 pub mod get_pseudo_random;
 pub mod get_storage_info;
 pub mod list_objects;
+#[cfg(feature = "asymmetric-auth")]
+pub mod put_asymmetric_authentication_key;
 pub mod put_asymmetric_key;
 pub mod put_authentication_key;
 pub mod put_opaque;
+pub mod put_template;
 #[cfg(feature = "mockhsm")]
 pub mod reset_device;
 pub mod set_option;
-#[cfg(not(feature = "mockhsm"))]
 pub mod sign_attestation_certificate;
-#[cfg(not(feature = "mockhsm"))]
 pub mod sign_ecdsa;
 pub mod sign_eddsa;
+#[cfg(feature = "untested")]
+pub mod sign_ssh_certificate;
 pub mod verify_hmac;
+pub mod wrap_data;
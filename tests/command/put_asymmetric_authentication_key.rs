@@ -0,0 +1,97 @@
+use yubihsm::{authentication, client, object, Capability, Client, Credentials};
+
+use crate::{clear_test_key_slot, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL, TEST_MESSAGE};
+
+/// P-256 private key to authenticate with
+const TEST_AUTHENTICATION_PRIVATE_KEY: [u8; 32] = [
+    0xc9, 0xaf, 0xa9, 0xd8, 0x45, 0xba, 0x75, 0x16, 0x6b, 0x5c, 0x21, 0x57, 0x67, 0xb1, 0xd6, 0x93,
+    0x4e, 0x50, 0xc3, 0xdb, 0x36, 0xe8, 0x9b, 0x12, 0x7b, 0x8a, 0x62, 0x2b, 0x12, 0x0f, 0x67, 0x21,
+];
+
+/// Put an asymmetric authentication key into the `YubiHSM` and open a
+/// session with the corresponding private key
+#[test]
+fn put_asymmetric_authentication_key() {
+    let client = crate::get_hsm_client();
+    let capabilities = Capability::GET_PSEUDO_RANDOM;
+
+    clear_test_key_slot(&client, object::Type::AuthenticationKey);
+
+    let private_key = p256::SecretKey::from_slice(&TEST_AUTHENTICATION_PRIVATE_KEY).unwrap();
+
+    let key_id = client
+        .put_asymmetric_authentication_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            capabilities,
+            Capability::empty(),
+            &private_key.public_key(),
+        )
+        .unwrap_or_else(|err| panic!("error putting asymmetric auth key: {err}"));
+
+    assert_eq!(key_id, TEST_KEY_ID);
+
+    let object_info = client
+        .get_object_info(TEST_KEY_ID, object::Type::AuthenticationKey)
+        .unwrap_or_else(|err| panic!("error getting object info: {err}"));
+
+    assert_eq!(object_info.capabilities, capabilities);
+    assert_eq!(object_info.object_type, object::Type::AuthenticationKey);
+    assert_eq!(
+        object_info.algorithm,
+        authentication::Algorithm::EcP256.into()
+    );
+
+    let device_public_key = client
+        .get_device_public_key()
+        .unwrap_or_else(|err| panic!("error getting device public key: {err}"))
+        .ecdsa::<p256::NistP256>()
+        .and_then(|point| p256::PublicKey::from_sec1_bytes(point.as_bytes()).ok())
+        .expect("invalid device public key");
+
+    let test_client = Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new_asymmetric(TEST_KEY_ID, private_key.clone(), device_public_key),
+        false,
+    )
+    .unwrap_or_else(|err| panic!("error authenticating with asymmetric auth key: {err}"));
+
+    let echo_response = test_client
+        .echo(TEST_MESSAGE)
+        .unwrap_or_else(|err| panic!("error sending echo: {err}"));
+
+    assert_eq!(TEST_MESSAGE, echo_response.as_slice());
+
+    let wrong_private_key = p256::SecretKey::from_slice(&[0x01; 32]).unwrap();
+
+    assert!(Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new_asymmetric(TEST_KEY_ID, wrong_private_key.clone(), device_public_key),
+        false,
+    )
+    .is_err());
+
+    // Sessions aren't opened with a device presenting an unexpected public key
+    let err = Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new_asymmetric(
+            TEST_KEY_ID,
+            private_key.clone(),
+            wrong_private_key.public_key(),
+        ),
+        false,
+    )
+    .err()
+    .expect("expected device public key mismatch");
+
+    assert_eq!(*err.kind(), client::ErrorKind::AuthenticationError);
+
+    // ...unless pinning is explicitly disabled
+    Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new_asymmetric_unpinned(TEST_KEY_ID, private_key),
+        false,
+    )
+    .unwrap_or_else(|err| panic!("error authenticating without device key pinning: {err}"));
+}
@@ -1,6 +1,11 @@
-use yubihsm::{authentication, object, Capability};
+use yubihsm::{
+    asymmetric, authentication, device, object, opaque, Capability, Client, Credentials, Domain,
+};
 
-use crate::{clear_test_key_slot, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL, TEST_MESSAGE};
+use crate::{
+    clear_test_key_slot, generate_asymmetric_key, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL,
+    TEST_MESSAGE,
+};
 
 /// Put a new authentication key into the `YubiHSM`
 #[test]
@@ -40,3 +45,86 @@ fn put_authentication_key() {
     assert_eq!(object_info.origin, object::Origin::Imported);
     assert_eq!(&object_info.label.to_string(), TEST_KEY_LABEL);
 }
+
+/// Ensure sessions are limited to the capabilities and domains of their
+/// authentication key
+#[test]
+fn put_authentication_key_permissions() {
+    let client = crate::get_hsm_client();
+
+    clear_test_key_slot(&client, object::Type::AuthenticationKey);
+
+    let authentication_key = authentication::Key::derive_from_password(TEST_MESSAGE);
+
+    client
+        .put_authentication_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_ECDSA,
+            Capability::empty(),
+            authentication::Algorithm::YubicoAes,
+            authentication_key.clone(),
+        )
+        .unwrap_or_else(|err| panic!("error putting auth key: {err}"));
+
+    // Signing key which lacks the `SIGN_ECDSA` capability
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::EcP256,
+        Capability::DERIVE_ECDH,
+    );
+
+    // Object outside of the authentication key's domains
+    clear_test_key_slot(&client, object::Type::Opaque);
+
+    client
+        .put_opaque(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            Domain::DOM2,
+            Capability::default(),
+            opaque::Algorithm::Data,
+            TEST_MESSAGE,
+        )
+        .unwrap_or_else(|err| panic!("error putting opaque object: {err}"));
+
+    let test_client = Client::open(
+        crate::HSM_CONNECTOR.clone(),
+        Credentials::new(TEST_KEY_ID, authentication_key),
+        false,
+    )
+    .unwrap_or_else(|err| panic!("error authenticating with test auth key: {err}"));
+
+    let err = test_client
+        .sign_ecdsa_prehash_raw(TEST_KEY_ID, [0u8; 32])
+        .unwrap_err();
+
+    assert_eq!(
+        err.device_error(),
+        Some(device::ErrorKind::InsufficientPermissions)
+    );
+
+    let err = test_client
+        .generate_asymmetric_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_ECDSA,
+            asymmetric::Algorithm::EcP256,
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.device_error(),
+        Some(device::ErrorKind::InsufficientPermissions)
+    );
+
+    let err = test_client
+        .get_object_info(TEST_KEY_ID, object::Type::Opaque)
+        .unwrap_err();
+
+    assert_eq!(err.device_error(), Some(device::ErrorKind::ObjectNotFound));
+
+    clear_test_key_slot(&client, object::Type::Opaque);
+}
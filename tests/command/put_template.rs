@@ -0,0 +1,56 @@
+use yubihsm::{asymmetric, object, ssh, Capability};
+
+use crate::{clear_test_key_slot, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL};
+
+/// Put an SSH certificate template and read it back
+#[test]
+fn ssh_template_test() {
+    let client = crate::get_hsm_client();
+
+    clear_test_key_slot(&client, object::Type::Template);
+
+    let template = ssh_template(&[0xc5; 256], TEST_KEY_ID, &["root"]);
+
+    let object_id = client
+        .put_template(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            ssh::Template::from_bytes(template.clone()),
+        )
+        .unwrap_or_else(|err| panic!("error putting template: {err}"));
+
+    assert_eq!(object_id, TEST_KEY_ID);
+
+    let template_data = client
+        .get_template(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting template: {err}"));
+
+    assert_eq!(template_data, template);
+}
+
+/// Serialize an SSH certificate template which allows the given CA key to
+/// sign certificates valid for up to an hour around the request's timestamp
+pub fn ssh_template(
+    timestamp_key_modulus: &[u8],
+    ca_key_id: object::Id,
+    principals_blocklist: &[&str],
+) -> Vec<u8> {
+    let mut template = vec![];
+
+    for (tag, value) in [
+        (0x01, vec![asymmetric::Algorithm::Rsa2048.to_u8()]),
+        (0x02, timestamp_key_modulus.to_vec()),
+        (0x03, ca_key_id.to_be_bytes().to_vec()),
+        (0x04, 3600u32.to_be_bytes().to_vec()),
+        (0x05, 3600u32.to_be_bytes().to_vec()),
+        (0x06, principals_blocklist.join("\0").into_bytes()),
+    ] {
+        template.push(tag);
+        template.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        template.extend_from_slice(&value);
+    }
+
+    template
+}
@@ -1,5 +1,38 @@
-use crate::{generate_asymmetric_key, EC_P256_PUBLIC_KEY_SIZE, TEST_KEY_ID};
-use yubihsm::{asymmetric, Capability};
+use crate::{
+    generate_asymmetric_key, TEST_DOMAINS, TEST_EXPORTED_KEY_ID, TEST_EXPORTED_KEY_LABEL,
+    TEST_KEY_ID, TEST_KEY_LABEL,
+};
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use x509_cert::{
+    der::{
+        asn1::{BitString, OctetString},
+        oid::ObjectIdentifier,
+        Decode, Encode,
+    },
+    Certificate,
+};
+use yubihsm::{asymmetric, object, opaque, Capability, Client};
+
+/// Firmware version extension
+const FIRMWARE_VERSION_EXTENSION: &str = "1.3.6.1.4.1.41482.4.1";
+
+/// Serial number extension
+const SERIAL_NUMBER_EXTENSION: &str = "1.3.6.1.4.1.41482.4.2";
+
+/// Origin extension
+const ORIGIN_EXTENSION: &str = "1.3.6.1.4.1.41482.4.3";
+
+/// Domains extension
+const DOMAINS_EXTENSION: &str = "1.3.6.1.4.1.41482.4.4";
+
+/// Capabilities extension
+const CAPABILITIES_EXTENSION: &str = "1.3.6.1.4.1.41482.4.5";
+
+/// Object ID extension
+const OBJECT_ID_EXTENSION: &str = "1.3.6.1.4.1.41482.4.6";
+
+/// Label extension
+const LABEL_EXTENSION: &str = "1.3.6.1.4.1.41482.4.9";
 
 /// Generate an attestation about a key in the HSM
 #[test]
@@ -16,6 +49,160 @@ fn attest_asymmetric_test() {
         .sign_attestation_certificate(TEST_KEY_ID, None)
         .unwrap_or_else(|err| panic!("error getting attestation certificate: {}", err));
 
-    // TODO: more tests, e.g. test that the certificate validates
-    assert!(certificate.len() > EC_P256_PUBLIC_KEY_SIZE);
+    let certificate = Certificate::from_der(certificate.as_slice())
+        .unwrap_or_else(|err| panic!("error parsing attestation certificate: {}", err));
+
+    check_attested_key(&client, &certificate);
+}
+
+/// Generate an attestation signed by a user-supplied attestation key and
+/// check it validates
+#[test]
+fn attest_asymmetric_with_attestation_key_test() {
+    let client = crate::get_hsm_client();
+
+    // Attestation keys need a certificate stored as an opaque object with
+    // the same ID. Any certificate will do: only its subject is used.
+    let device_certificate = client
+        .get_opaque(0)
+        .unwrap_or_else(|err| panic!("error getting device certificate: {}", err));
+
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, object::Type::AsymmetricKey);
+    let _ = client.delete_object(TEST_EXPORTED_KEY_ID, object::Type::Opaque);
+
+    client
+        .generate_asymmetric_key(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_ATTESTATION_CERTIFICATE,
+            asymmetric::Algorithm::EcP256,
+        )
+        .unwrap_or_else(|err| panic!("error generating attestation key: {}", err));
+
+    client
+        .put_opaque(
+            TEST_EXPORTED_KEY_ID,
+            TEST_EXPORTED_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            opaque::Algorithm::X509Certificate,
+            device_certificate.clone(),
+        )
+        .unwrap_or_else(|err| panic!("error putting attestation certificate: {}", err));
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::EcP256,
+        Capability::SIGN_ECDSA,
+    );
+
+    let certificate = client
+        .sign_attestation_certificate(TEST_KEY_ID, Some(TEST_EXPORTED_KEY_ID))
+        .unwrap_or_else(|err| panic!("error getting attestation certificate: {}", err));
+
+    let certificate = Certificate::from_der(certificate.as_slice())
+        .unwrap_or_else(|err| panic!("error parsing attestation certificate: {}", err));
+
+    let attestation_certificate = Certificate::from_der(&device_certificate).unwrap();
+
+    assert_eq!(
+        certificate.tbs_certificate.issuer,
+        attestation_certificate.tbs_certificate.subject
+    );
+
+    let signature = Signature::from_der(certificate.signature.raw_bytes()).unwrap();
+
+    let attestation_public_key = client
+        .get_public_key(TEST_EXPORTED_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {}", err))
+        .ecdsa::<p256::NistP256>()
+        .unwrap();
+
+    let verifying_key = VerifyingKey::from_encoded_point(&attestation_public_key).unwrap();
+    let tbs_certificate = certificate.tbs_certificate.to_der().unwrap();
+    assert!(verifying_key.verify(&tbs_certificate, &signature).is_ok());
+
+    check_attested_key(&client, &certificate);
+}
+
+/// Check an attestation certificate for the test key contains its public key
+/// and the Yubico extensions describing it
+fn check_attested_key(client: &Client, certificate: &Certificate) {
+    let tbs_certificate = &certificate.tbs_certificate;
+
+    let public_key = client
+        .get_public_key(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {}", err));
+
+    // Uncompressed SEC1 point
+    assert_eq!(
+        tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .raw_bytes(),
+        [&[0x04], public_key.as_slice()].concat()
+    );
+
+    let device_info = client
+        .device_info()
+        .unwrap_or_else(|err| panic!("error getting device info: {}", err));
+
+    let object_info = client
+        .get_object_info(TEST_KEY_ID, object::Type::AsymmetricKey)
+        .unwrap_or_else(|err| panic!("error getting object info: {}", err));
+
+    let extension = |oid: &str| {
+        let oid = ObjectIdentifier::new_unwrap(oid);
+
+        tbs_certificate
+            .extensions
+            .iter()
+            .flatten()
+            .find(|extension| extension.extn_id == oid)
+            .unwrap_or_else(|| panic!("missing extension: {}", oid))
+            .extn_value
+            .as_bytes()
+    };
+
+    assert_eq!(
+        OctetString::from_der(extension(FIRMWARE_VERSION_EXTENSION))
+            .unwrap()
+            .as_bytes(),
+        [
+            device_info.major_version,
+            device_info.minor_version,
+            device_info.build_version
+        ]
+    );
+    assert_eq!(
+        u32::from_der(extension(SERIAL_NUMBER_EXTENSION)).unwrap(),
+        u32::from(device_info.serial_number)
+    );
+    assert_eq!(
+        BitString::from_der(extension(ORIGIN_EXTENSION))
+            .unwrap()
+            .raw_bytes(),
+        [object_info.origin.to_u8()]
+    );
+    assert_eq!(
+        BitString::from_der(extension(DOMAINS_EXTENSION))
+            .unwrap()
+            .raw_bytes(),
+        object_info.domains.bits().to_be_bytes()
+    );
+    assert_eq!(
+        BitString::from_der(extension(CAPABILITIES_EXTENSION))
+            .unwrap()
+            .raw_bytes(),
+        object_info.capabilities.bits().to_be_bytes()
+    );
+    assert_eq!(
+        u16::from_der(extension(OBJECT_ID_EXTENSION)).unwrap(),
+        TEST_KEY_ID
+    );
+    assert_eq!(
+        String::from_der(extension(LABEL_EXTENSION)).unwrap(),
+        TEST_KEY_LABEL
+    );
 }
@@ -2,10 +2,13 @@
 
 use crate::{generate_asymmetric_key, TEST_KEY_ID, TEST_MESSAGE};
 use p256::{
-    ecdsa::{signature::Verifier, Signature, VerifyingKey},
+    ecdsa::{
+        signature::{hazmat::PrehashVerifier, Verifier},
+        Signature, VerifyingKey,
+    },
     NistP256,
 };
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
 use yubihsm::{asymmetric, Capability};
 
 /// Test ECDSA signatures (using NIST P-256)
@@ -39,3 +42,30 @@ fn generated_nistp256_key_test() {
     let verify_key = VerifyingKey::from_encoded_point(&public_key).unwrap();
     assert!(verify_key.verify(TEST_MESSAGE, &signature).is_ok());
 }
+
+/// Test ECDSA signatures with a digest larger than the curve's field size
+#[test]
+fn truncated_digest_nistp256_key_test() {
+    let client = crate::get_hsm_client();
+
+    generate_asymmetric_key(
+        &client,
+        asymmetric::Algorithm::EcP256,
+        Capability::SIGN_ECDSA,
+    );
+
+    let public_key = client
+        .get_public_key(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {}", err))
+        .ecdsa::<NistP256>()
+        .unwrap();
+
+    let signature = client
+        .sign_ecdsa::<NistP256, Sha512>(TEST_KEY_ID, TEST_MESSAGE)
+        .unwrap_or_else(|err| panic!("error performing ECDSA signature: {}", err));
+
+    let verify_key = VerifyingKey::from_encoded_point(&public_key).unwrap();
+    assert!(verify_key
+        .verify_prehash(&Sha512::digest(TEST_MESSAGE), &signature)
+        .is_ok());
+}
@@ -0,0 +1,278 @@
+//! SSH certificate authority tests
+
+use super::put_template::ssh_template;
+use crate::{
+    clear_test_key_slot, generate_asymmetric_key, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL,
+};
+use ::rsa::{rand_core::OsRng, traits::PublicKeyParts, Pkcs1v15Sign, RsaPrivateKey};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use ssh_key::{Certificate, HashAlg, PublicKey};
+use yubihsm::{asymmetric, device, ecdsa, object, rsa, ssh, Algorithm, Capability, Client};
+
+/// Timestamp key which certificate requests are signed with
+static TIMESTAMP_KEY: Lazy<RsaPrivateKey> =
+    Lazy::new(|| RsaPrivateKey::new(&mut OsRng, 2048).unwrap());
+
+/// Timestamp of certificate requests
+const TIMESTAMP: u32 = 1_700_000_000;
+
+/// Sign an SSH certificate with an Ed25519 CA key, and validate it
+#[test]
+fn ed25519_certificate_test() {
+    certificate_test(
+        asymmetric::Algorithm::Ed25519,
+        asymmetric::Algorithm::Ed25519.into(),
+    );
+}
+
+/// Sign an SSH certificate with an ECDSA P-256 CA key, and validate it
+#[test]
+fn ecdsa_p256_certificate_test() {
+    certificate_test(
+        asymmetric::Algorithm::EcP256,
+        ecdsa::Algorithm::Sha256.into(),
+    );
+}
+
+/// Sign an SSH certificate with an ECDSA P-384 CA key, and validate it
+#[test]
+fn ecdsa_p384_certificate_test() {
+    certificate_test(
+        asymmetric::Algorithm::EcP384,
+        ecdsa::Algorithm::Sha384.into(),
+    );
+}
+
+/// Sign an SSH certificate with an RSA CA key using `rsa-sha2-256`, and
+/// validate it
+#[test]
+fn rsa_sha256_certificate_test() {
+    certificate_test(
+        asymmetric::Algorithm::Rsa2048,
+        rsa::Algorithm::from(rsa::pkcs1::Algorithm::Sha256).into(),
+    );
+}
+
+/// Sign an SSH certificate with an RSA CA key using `rsa-sha2-512`, and
+/// validate it
+#[test]
+fn rsa_sha512_certificate_test() {
+    certificate_test(
+        asymmetric::Algorithm::Rsa2048,
+        rsa::Algorithm::from(rsa::pkcs1::Algorithm::Sha512).into(),
+    );
+}
+
+/// Reject requests which aren't signed with the template's timestamp key
+#[test]
+fn timestamp_signature_test() {
+    let client = crate::get_hsm_client();
+    let ca_key = setup_ssh_ca(&client, asymmetric::Algorithm::Ed25519, &[]);
+
+    let request = certificate_request(&ca_key, "alice", TIMESTAMP - 60, TIMESTAMP + 60);
+
+    // Signed over a different timestamp than the one sent
+    let err = client
+        .sign_ssh_certificate(
+            TEST_KEY_ID,
+            TEST_KEY_ID,
+            asymmetric::Algorithm::Ed25519,
+            TIMESTAMP,
+            &timestamp_signature(TIMESTAMP + 1, &request),
+            &request,
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.device_error(),
+        Some(device::ErrorKind::SshCaConstraintViolation)
+    );
+}
+
+/// Reject requests which violate the template's constraints
+#[test]
+fn template_constraints_test() {
+    let client = crate::get_hsm_client();
+    let ca_key = setup_ssh_ca(&client, asymmetric::Algorithm::Ed25519, &["root"]);
+
+    for request in [
+        certificate_request(&ca_key, "root", TIMESTAMP - 60, TIMESTAMP + 60),
+        certificate_request(&ca_key, "alice", TIMESTAMP - 7200, TIMESTAMP + 60),
+        certificate_request(&ca_key, "alice", TIMESTAMP - 60, TIMESTAMP + 7200),
+    ] {
+        let err = client
+            .sign_ssh_certificate(
+                TEST_KEY_ID,
+                TEST_KEY_ID,
+                asymmetric::Algorithm::Ed25519,
+                TIMESTAMP,
+                &timestamp_signature(TIMESTAMP, &request),
+                &request,
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            err.device_error(),
+            Some(device::ErrorKind::SshCaConstraintViolation)
+        );
+    }
+}
+
+/// Sign an SSH certificate with a CA key of the given algorithm, and validate
+/// it with `ssh-key`
+fn certificate_test(ca_algorithm: asymmetric::Algorithm, signature_algorithm: Algorithm) {
+    let client = crate::get_hsm_client();
+    let ca_key = setup_ssh_ca(&client, ca_algorithm, &[]);
+
+    let request = certificate_request(&ca_key, "alice", TIMESTAMP - 60, TIMESTAMP + 60);
+
+    let certificate = client
+        .sign_ssh_certificate(
+            TEST_KEY_ID,
+            TEST_KEY_ID,
+            signature_algorithm,
+            TIMESTAMP,
+            &timestamp_signature(TIMESTAMP, &request),
+            &request,
+        )
+        .unwrap_or_else(|err| panic!("error signing SSH certificate: {err}"));
+
+    let certificate = Certificate::from_bytes(certificate.as_slice())
+        .unwrap_or_else(|err| panic!("error parsing SSH certificate: {err}"));
+
+    let ca_fingerprint = PublicKey::from_bytes(&ca_key)
+        .unwrap()
+        .fingerprint(HashAlg::Sha256);
+
+    certificate
+        .validate_at(u64::from(TIMESTAMP), [&ca_fingerprint])
+        .unwrap_or_else(|err| panic!("error validating SSH certificate: {err}"));
+
+    assert_eq!(certificate.valid_principals(), ["alice"]);
+}
+
+/// Generate a CA key and put a template for it, returning the CA's public key
+/// in the SSH wire format
+fn setup_ssh_ca(
+    client: &Client,
+    algorithm: asymmetric::Algorithm,
+    principals_blocklist: &[&str],
+) -> Vec<u8> {
+    generate_asymmetric_key(client, algorithm, Capability::SIGN_SSH_CERTIFICATE);
+
+    clear_test_key_slot(client, object::Type::Template);
+
+    let template = ssh_template(
+        &TIMESTAMP_KEY.n().to_bytes_be(),
+        TEST_KEY_ID,
+        principals_blocklist,
+    );
+
+    client
+        .put_template(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            ssh::Template::from_bytes(template),
+        )
+        .unwrap_or_else(|err| panic!("error putting template: {err}"));
+
+    let public_key = client
+        .get_public_key(TEST_KEY_ID)
+        .unwrap_or_else(|err| panic!("error getting public key: {err}"));
+
+    let mut ca_key = vec![];
+
+    match algorithm {
+        asymmetric::Algorithm::Ed25519 => {
+            put_string(&mut ca_key, b"ssh-ed25519");
+            put_string(&mut ca_key, &public_key.bytes);
+        }
+        asymmetric::Algorithm::EcP256 => {
+            put_string(&mut ca_key, b"ecdsa-sha2-nistp256");
+            put_string(&mut ca_key, b"nistp256");
+            put_string(
+                &mut ca_key,
+                &[&[0x04], public_key.bytes.as_slice()].concat(),
+            );
+        }
+        asymmetric::Algorithm::EcP384 => {
+            put_string(&mut ca_key, b"ecdsa-sha2-nistp384");
+            put_string(&mut ca_key, b"nistp384");
+            put_string(
+                &mut ca_key,
+                &[&[0x04], public_key.bytes.as_slice()].concat(),
+            );
+        }
+        asymmetric::Algorithm::Rsa2048 => {
+            put_string(&mut ca_key, b"ssh-rsa");
+            put_mpint(&mut ca_key, &65537u32.to_be_bytes());
+            put_mpint(&mut ca_key, &public_key.bytes);
+        }
+        other => panic!("unsupported SSH CA key algorithm: {other:?}"),
+    }
+
+    ca_key
+}
+
+/// Serialize a request for an Ed25519 user certificate
+fn certificate_request(
+    ca_key: &[u8],
+    principal: &str,
+    valid_after: u32,
+    valid_before: u32,
+) -> Vec<u8> {
+    let mut principals = vec![];
+    put_string(&mut principals, principal.as_bytes());
+
+    let mut request = vec![];
+    put_string(&mut request, b"ssh-ed25519-cert-v01@openssh.com");
+    put_string(&mut request, &[0x42; 32]);
+    put_string(&mut request, &[0x17; 32]);
+    request.extend_from_slice(&1u64.to_be_bytes());
+    request.extend_from_slice(&1u32.to_be_bytes());
+    put_string(&mut request, b"yubihsm.rs test certificate");
+    put_string(&mut request, &principals);
+    request.extend_from_slice(&u64::from(valid_after).to_be_bytes());
+    request.extend_from_slice(&u64::from(valid_before).to_be_bytes());
+
+    // Critical options, extensions, and the reserved field
+    for _ in 0..3 {
+        put_string(&mut request, &[]);
+    }
+
+    put_string(&mut request, ca_key);
+    request
+}
+
+/// Sign the given timestamp and certificate request with the timestamp key
+fn timestamp_signature(timestamp: u32, request: &[u8]) -> Vec<u8> {
+    let digest = Sha256::new()
+        .chain_update(timestamp.to_be_bytes())
+        .chain_update(request)
+        .finalize();
+
+    TIMESTAMP_KEY
+        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+        .unwrap()
+}
+
+/// Append a length-prefixed string in the SSH wire format
+fn put_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Append a multiple precision integer, given as unsigned big endian bytes,
+/// in the SSH wire format
+fn put_mpint(buf: &mut Vec<u8>, bytes: &[u8]) {
+    let bytes = &bytes[bytes.iter().take_while(|&&b| b == 0).count()..];
+
+    if bytes.first().map_or(false, |b| b & 0x80 != 0) {
+        put_string(buf, &[&[0], bytes].concat());
+    } else {
+        put_string(buf, bytes);
+    }
+}
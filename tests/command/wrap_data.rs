@@ -0,0 +1,38 @@
+use crate::{clear_test_key_slot, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL, TEST_MESSAGE};
+use yubihsm::{object, wrap, Capability};
+
+/// Encrypt data under a wrap key and decrypt it again
+#[test]
+fn wrap_data_test() {
+    let client = crate::get_hsm_client();
+
+    clear_test_key_slot(&client, object::Type::WrapKey);
+
+    client
+        .generate_wrap_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::WRAP_DATA | Capability::UNWRAP_DATA,
+            Capability::default(),
+            wrap::Algorithm::Aes256Ccm,
+        )
+        .unwrap_or_else(|err| panic!("error generating wrap key: {err}"));
+
+    let wrap_message = client
+        .wrap_data(TEST_KEY_ID, TEST_MESSAGE.into())
+        .unwrap_or_else(|err| panic!("error wrapping data: {err}"));
+
+    assert_ne!(wrap_message.ciphertext, TEST_MESSAGE);
+
+    let plaintext = client
+        .unwrap_data(TEST_KEY_ID, wrap_message.clone())
+        .unwrap_or_else(|err| panic!("error unwrapping data: {err}"));
+
+    assert_eq!(plaintext, TEST_MESSAGE);
+
+    // Tampered ciphertexts must be rejected
+    let mut tampered_message = wrap_message;
+    tampered_message.ciphertext[0] ^= 0x01;
+    assert!(client.unwrap_data(TEST_KEY_ID, tampered_message).is_err());
+}
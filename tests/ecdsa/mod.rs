@@ -10,10 +10,13 @@ use ::ecdsa::{
 };
 use yubihsm::{
     asymmetric::signature::Signer as _,
-    ecdsa::{self, algorithm::CurveAlgorithm, NistP256},
+    ecdsa::{self, algorithm::CurveAlgorithm, NistP256, NistP384},
     object, Client,
 };
 
+#[cfg(feature = "nistp521")]
+use yubihsm::ecdsa::NistP521;
+
 #[cfg(feature = "secp256k1")]
 use {
     ::ecdsa::signature::{digest::Digest, DigestSigner, DigestVerifier},
@@ -73,6 +76,48 @@ fn ecdsa_nistp256_sign_test() {
     assert!(verify_key.verify(TEST_MESSAGE, &signature).is_ok());
 }
 
+#[test]
+fn ecdsa_nistp384_sign_test() {
+    let signer = create_signer::<NistP384>(204);
+    let verify_key = p384::ecdsa::VerifyingKey::from_encoded_point(signer.public_key()).unwrap();
+
+    let signature = signer.sign(TEST_MESSAGE);
+    assert!(verify_key.verify(TEST_MESSAGE, &signature).is_ok());
+}
+
+#[cfg(feature = "nistp521")]
+#[test]
+fn ecdsa_nistp521_sign_test() {
+    use ::ecdsa::signature::{digest::Digest, DigestSigner};
+
+    let signer = create_signer::<NistP521>(205);
+    let verify_key = p521::ecdsa::VerifyingKey::from_encoded_point(signer.public_key()).unwrap();
+
+    let signature = signer.sign_digest(sha2::Sha512::new_with_prefix(TEST_MESSAGE));
+    assert!(verify_key.verify(TEST_MESSAGE, &signature).is_ok());
+}
+
+#[cfg(feature = "nistp521")]
+#[test]
+fn ecdsa_nistp521_sign_prehash_test() {
+    use ::ecdsa::signature::{
+        digest::Digest,
+        hazmat::{PrehashSigner, PrehashVerifier},
+    };
+
+    let signer = create_signer::<NistP521>(206);
+    let verify_key = p521::ecdsa::VerifyingKey::from_encoded_point(signer.public_key()).unwrap();
+
+    // Digests shorter than the 66-byte field size are used as-is
+    for prehash in [
+        sha2::Sha512::digest(TEST_MESSAGE).to_vec(),
+        sha2::Sha384::digest(TEST_MESSAGE).to_vec(),
+    ] {
+        let signature: ecdsa::Signature<NistP521> = signer.sign_prehash(&prehash).unwrap();
+        assert!(verify_key.verify_prehash(&prehash, &signature).is_ok());
+    }
+}
+
 #[cfg(feature = "secp256k1")]
 #[test]
 fn ecdsa_secp256k1_sign_test() {
@@ -13,6 +13,14 @@ mod ecdsa;
 /// Ed25519 tests
 mod ed25519;
 
+/// MockHsm-specific tests
+#[cfg(feature = "mockhsm")]
+mod mockhsm;
+
+/// RSA tests
+#[cfg(feature = "untested")]
+mod rsa;
+
 /// Cryptographic test vectors taken from standards documents
 mod test_vectors;
 
@@ -0,0 +1,159 @@
+//! Sessions are limited to the capabilities and domains of their
+//! authentication key
+
+use crate::{TEST_KEY_LABEL, TEST_MESSAGE};
+use yubihsm::{
+    asymmetric, authentication, device, mockhsm::MockHsm, object, wrap, Capability, Client,
+    Connector, Credentials, Domain,
+};
+
+/// ID of the restricted authentication key used by these tests
+const RESTRICTED_KEY_ID: object::Id = 200;
+
+/// ID of the key objects used by these tests
+const KEY_ID: object::Id = 201;
+
+/// Open a client for the given MockHsm using the default authentication key
+fn open_client(connector: &Connector) -> Client {
+    Client::open(connector.clone(), Credentials::default(), true)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"))
+}
+
+/// Put a restricted authentication key and open a session with it
+fn open_restricted_client(
+    client: &Client,
+    connector: &Connector,
+    domains: Domain,
+    capabilities: Capability,
+) -> Client {
+    let authentication_key = authentication::Key::derive_from_password(TEST_MESSAGE);
+
+    client
+        .put_authentication_key(
+            RESTRICTED_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            domains,
+            capabilities,
+            Capability::all(),
+            authentication::Algorithm::YubicoAes,
+            authentication_key.clone(),
+        )
+        .unwrap_or_else(|err| panic!("error putting auth key: {err}"));
+
+    Client::open(
+        connector.clone(),
+        Credentials::new(RESTRICTED_KEY_ID, authentication_key),
+        false,
+    )
+    .unwrap_or_else(|err| panic!("error opening restricted session: {err}"))
+}
+
+/// Reading a public key requires a capability which operates on asymmetric keys
+#[test]
+fn get_public_key_without_capability_test() {
+    let connector = Connector::from(MockHsm::new());
+    let client = open_client(&connector);
+
+    client
+        .generate_asymmetric_key(
+            KEY_ID,
+            TEST_KEY_LABEL.into(),
+            Domain::DOM1,
+            Capability::SIGN_ECDSA,
+            asymmetric::Algorithm::EcP256,
+        )
+        .unwrap();
+
+    let restricted_client = open_restricted_client(
+        &client,
+        &connector,
+        Domain::DOM1,
+        Capability::GET_PSEUDO_RANDOM,
+    );
+
+    let err = restricted_client.get_public_key(KEY_ID).unwrap_err();
+    assert_eq!(
+        err.device_error(),
+        Some(device::ErrorKind::InsufficientPermissions)
+    );
+}
+
+/// Public keys of objects outside the session's domains aren't found
+#[test]
+fn get_public_key_other_domain_test() {
+    let connector = Connector::from(MockHsm::new());
+    let client = open_client(&connector);
+
+    client
+        .generate_asymmetric_key(
+            KEY_ID,
+            TEST_KEY_LABEL.into(),
+            Domain::DOM2,
+            Capability::SIGN_ECDSA,
+            asymmetric::Algorithm::EcP256,
+        )
+        .unwrap();
+
+    let restricted_client =
+        open_restricted_client(&client, &connector, Domain::DOM1, Capability::SIGN_ECDSA);
+
+    let err = restricted_client.get_public_key(KEY_ID).unwrap_err();
+    assert_eq!(err.device_error(), Some(device::ErrorKind::ObjectNotFound));
+}
+
+/// Objects can't be imported under a wrap key into domains the importing
+/// session can't access
+#[test]
+fn import_wrapped_other_domain_test() {
+    let connector = Connector::from(MockHsm::new());
+    let client = open_client(&connector);
+
+    client
+        .generate_wrap_key(
+            KEY_ID,
+            TEST_KEY_LABEL.into(),
+            Domain::DOM1 | Domain::DOM2,
+            Capability::EXPORT_WRAPPED | Capability::IMPORT_WRAPPED,
+            Capability::all(),
+            wrap::Algorithm::Aes128Ccm,
+        )
+        .unwrap();
+
+    client
+        .generate_asymmetric_key(
+            KEY_ID,
+            TEST_KEY_LABEL.into(),
+            Domain::DOM2,
+            Capability::SIGN_ECDSA | Capability::EXPORTABLE_UNDER_WRAP,
+            asymmetric::Algorithm::EcP256,
+        )
+        .unwrap();
+
+    let wrapped = client
+        .export_wrapped(KEY_ID, object::Type::AsymmetricKey, KEY_ID)
+        .unwrap();
+
+    client
+        .delete_object(KEY_ID, object::Type::AsymmetricKey)
+        .unwrap();
+
+    let restricted_client =
+        open_restricted_client(&client, &connector, Domain::DOM1, Capability::IMPORT_WRAPPED);
+
+    let err = restricted_client
+        .import_wrapped(KEY_ID, wrapped.clone())
+        .unwrap_err();
+    assert_eq!(
+        err.device_error(),
+        Some(device::ErrorKind::InsufficientPermissions)
+    );
+
+    // Nothing was imported
+    let err = client
+        .get_object_info(KEY_ID, object::Type::AsymmetricKey)
+        .unwrap_err();
+    assert_eq!(err.device_error(), Some(device::ErrorKind::ObjectNotFound));
+
+    // A session with access to the object's domains can import it
+    client.import_wrapped(KEY_ID, wrapped).unwrap();
+}
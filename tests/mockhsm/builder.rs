@@ -0,0 +1,164 @@
+//! Building a `MockHsm` with custom device information and preloaded objects
+
+use crate::{
+    test_vectors::AES_ECB_TEST_VECTORS, TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL, TEST_MESSAGE,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use yubihsm::{
+    asymmetric, authentication, command, mockhsm::MockHsm, object, otp, ssh, symmetric,
+    AuditOption, Capability, Client, Connector, Credentials, Domain,
+};
+
+/// Password for the authentication key replacing the default one
+const TEST_PASSWORD: &[u8] = b"mockhsm builder test password";
+
+/// Preloaded objects can be used by clients, and inspected by tests
+#[test]
+fn builder_test() {
+    let signing_key = SigningKey::from_bytes(&[0x42; 32]);
+
+    let hsm = MockHsm::builder()
+        .serial_number("1234567890".parse().unwrap())
+        .firmware_version(2, 4, 0)
+        .authentication_key(
+            1,
+            "custom auth key".into(),
+            Domain::all(),
+            Capability::all(),
+            Capability::all(),
+            authentication::Key::derive_from_password(TEST_PASSWORD),
+        )
+        .asymmetric_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_EDDSA,
+            asymmetric::Algorithm::Ed25519,
+            signing_key.to_bytes(),
+        )
+        .build()
+        .unwrap_or_else(|err| panic!("error building MockHsm: {err}"));
+
+    let client = Client::open(
+        Connector::from(hsm.clone()),
+        Credentials::from_password(1, TEST_PASSWORD),
+        true,
+    )
+    .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    let device_info = client.device_info().unwrap();
+    assert_eq!(device_info.serial_number.to_string(), "1234567890");
+    assert_eq!(
+        (
+            device_info.major_version,
+            device_info.minor_version,
+            device_info.build_version
+        ),
+        (2, 4, 0)
+    );
+
+    let signature = client.sign_ed25519(TEST_KEY_ID, TEST_MESSAGE).unwrap();
+    assert_eq!(signature, signing_key.sign(TEST_MESSAGE));
+
+    // The default authentication key was replaced
+    let objects = hsm.objects();
+    assert_eq!(objects.len(), 2);
+
+    let authentication_key_info = hsm.object_info(1, object::Type::AuthenticationKey).unwrap();
+    assert_eq!(authentication_key_info.label.to_string(), "custom auth key");
+
+    let key_info = hsm
+        .object_info(TEST_KEY_ID, object::Type::AsymmetricKey)
+        .unwrap();
+    assert_eq!(key_info.origin, object::Origin::Imported);
+    assert_eq!(key_info.capabilities, Capability::SIGN_EDDSA);
+
+    // Audit state can be read back without a session
+    client
+        .set_command_audit_option(command::Code::SignEddsa, AuditOption::Fix)
+        .unwrap();
+
+    assert_eq!(
+        hsm.command_audit_option(command::Code::SignEddsa),
+        AuditOption::Fix
+    );
+    assert_eq!(hsm.force_audit_option(), AuditOption::Off);
+
+    let log_entries = hsm.log_entries();
+    assert!(log_entries
+        .entries
+        .iter()
+        .any(|entry| entry.cmd == command::Code::SignEddsa));
+}
+
+/// Symmetric keys, OTP AEAD keys and templates can be preloaded
+#[test]
+fn builder_objects_test() {
+    let vector = &AES_ECB_TEST_VECTORS[0];
+
+    let hsm = MockHsm::builder()
+        .symmetric_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::ENCRYPT_ECB,
+            symmetric::Algorithm::Aes128,
+            vector.key,
+        )
+        .otp_aead_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::RANDOMIZE_OTP_AEAD,
+            otp::Algorithm::Aes128,
+            otp::Nonce([0x01, 0x02, 0x03, 0x04]),
+            [0x42; 16],
+        )
+        .template(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            ssh::Template::from_bytes(TEST_MESSAGE.to_vec()),
+        )
+        .build()
+        .unwrap_or_else(|err| panic!("error building MockHsm: {err}"));
+
+    let client = Client::open(Connector::from(hsm), Credentials::default(), true)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    assert_eq!(
+        client.encrypt_ecb(TEST_KEY_ID, vector.plaintext).unwrap(),
+        vector.ciphertext
+    );
+    assert!(client.randomize_otp_aead(TEST_KEY_ID).is_ok());
+    assert_eq!(client.get_template(TEST_KEY_ID).unwrap(), TEST_MESSAGE);
+}
+
+/// Sessions can be opened with preloaded asymmetric authentication keys
+#[cfg(feature = "asymmetric-auth")]
+#[test]
+fn builder_asymmetric_authentication_key_test() {
+    let private_key = p256::SecretKey::from_slice(&[0x42; 32]).unwrap();
+
+    let hsm = MockHsm::builder()
+        .asymmetric_authentication_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            Domain::all(),
+            Capability::GET_PSEUDO_RANDOM,
+            Capability::empty(),
+            &private_key.public_key(),
+        )
+        .build()
+        .unwrap_or_else(|err| panic!("error building MockHsm: {err}"));
+
+    let client = Client::open(
+        Connector::from(hsm),
+        Credentials::new_asymmetric_unpinned(TEST_KEY_ID, private_key),
+        false,
+    )
+    .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    assert_eq!(client.get_pseudo_random(32).unwrap().len(), 32);
+}
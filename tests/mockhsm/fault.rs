@@ -0,0 +1,150 @@
+//! Injecting faults into the `MockHsm`'s connections and responses
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use yubihsm::{
+    client, command, device,
+    mockhsm::{Fault, FaultPlan, ManualClock, MockHsm},
+    Client, Connector, Credentials,
+};
+
+/// Open a client for the given MockHsm
+fn open_client(hsm: &MockHsm) -> Client {
+    Client::open(Connector::from(hsm.clone()), Credentials::default(), true)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"))
+}
+
+/// Device errors injected into commands sent within a session are returned
+/// without closing the session
+#[test]
+fn device_error_test() {
+    let hsm = MockHsm::new();
+    let client = open_client(&hsm);
+
+    hsm.set_fault_plan(FaultPlan::new().on_command(
+        command::Code::Echo,
+        Fault::DeviceError(device::ErrorKind::InvalidData),
+    ));
+
+    let err = client.echo(b"hello").expect_err("expected device error");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidData));
+
+    // Other commands are unaffected
+    assert_eq!(client.get_pseudo_random(16).unwrap().len(), 16);
+}
+
+/// Unencrypted device errors in response to session messages close the
+/// session, after which the client reconnects
+#[test]
+fn unencrypted_device_error_test() {
+    let hsm = MockHsm::new();
+    let client = open_client(&hsm);
+    let session_id = client.session().unwrap().id();
+
+    hsm.set_fault_plan(
+        FaultPlan::new().on_message(1, Fault::DeviceError(device::ErrorKind::InvalidSession)),
+    );
+
+    let err = client.echo(b"hello").expect_err("expected device error");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidSession));
+
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+    assert_ne!(client.session().unwrap().id(), session_id);
+}
+
+/// Dropped responses fail the request, after which the client reconnects
+#[test]
+fn drop_response_test() {
+    let hsm = MockHsm::new();
+    let client = open_client(&hsm);
+
+    hsm.set_fault_plan(FaultPlan::new().on_message(1, Fault::DropResponse));
+    assert!(client.echo(b"hello").is_err());
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+}
+
+/// Responses with corrupt MACs are rejected
+#[test]
+fn corrupt_mac_test() {
+    let hsm = MockHsm::new();
+    let client = open_client(&hsm);
+
+    hsm.set_fault_plan(FaultPlan::new().on_command(command::Code::Echo, Fault::CorruptMac));
+
+    let err = client
+        .echo(b"hello")
+        .expect_err("expected MAC verification failure");
+    assert_eq!(*err.kind(), client::ErrorKind::ProtocolError);
+
+    hsm.set_fault_plan(FaultPlan::new());
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+}
+
+/// Responses can be delayed
+#[test]
+fn delay_test() {
+    let hsm = MockHsm::new();
+    let client = open_client(&hsm);
+    let delay = Duration::from_millis(100);
+
+    hsm.set_fault_plan(FaultPlan::new().on_command(command::Code::Echo, Fault::Delay(delay)));
+
+    let started_at = Instant::now();
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+    assert!(started_at.elapsed() >= delay);
+}
+
+/// Delayed responses don't block other connections to the MockHsm
+#[test]
+fn delay_concurrent_test() {
+    let hsm = MockHsm::new();
+    let delayed_client = open_client(&hsm);
+    let client = open_client(&hsm);
+    let delay = Duration::from_millis(500);
+
+    hsm.set_fault_plan(FaultPlan::new().on_command(command::Code::Echo, Fault::Delay(delay)));
+
+    let delayed = thread::spawn(move || delayed_client.echo(b"hello").unwrap());
+    thread::sleep(Duration::from_millis(50));
+
+    let started_at = Instant::now();
+    assert_eq!(client.get_pseudo_random(32).unwrap().len(), 32);
+    assert!(started_at.elapsed() < delay);
+
+    assert_eq!(delayed.join().unwrap(), b"hello");
+}
+
+/// Delays advance a `ManualClock` instead of waiting
+#[test]
+fn delay_manual_clock_test() {
+    let clock = ManualClock::new();
+    let hsm = MockHsm::with_clock(clock);
+    let client = open_client(&hsm);
+
+    hsm.set_fault_plan(
+        FaultPlan::new().on_command(command::Code::Echo, Fault::Delay(Duration::from_secs(30))),
+    );
+
+    let started_at = Instant::now();
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+    assert!(started_at.elapsed() < Duration::from_secs(30));
+
+    // The session timed out while the response was delayed
+    hsm.set_fault_plan(FaultPlan::new());
+    let err = client.echo(b"hello").unwrap_err();
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidSession));
+}
+
+/// Connection attempts can fail
+#[test]
+fn fail_connect_test() {
+    let hsm = MockHsm::builder()
+        .fault_plan(FaultPlan::new().fail_connect(1))
+        .build()
+        .unwrap();
+
+    assert!(Client::open(Connector::from(hsm.clone()), Credentials::default(), true).is_err());
+    assert_eq!(open_client(&hsm).echo(b"hello").unwrap(), b"hello");
+}
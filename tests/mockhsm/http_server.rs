@@ -0,0 +1,94 @@
+//! Smoke test for serving a `MockHsm` over HTTP, as the `mockhsm_connector`
+//! example does
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+use yubihsm::{
+    connector::{http::Server, HttpConfig},
+    device::SerialNumber,
+    mockhsm::MockHsm,
+    Client, Connector, Credentials,
+};
+
+/// Start an HTTP server for the given MockHsm on a free local port
+fn start_server(mockhsm: MockHsm) -> HttpConfig {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .unwrap()
+        .port();
+
+    let http_config = HttpConfig {
+        port,
+        ..Default::default()
+    };
+
+    let server = Server::new(&http_config, Connector::from(mockhsm)).unwrap();
+    thread::spawn(move || server.run().unwrap());
+
+    http_config
+}
+
+/// Send a raw (unencrypted) command to the server, returning the response body
+fn post_command(http_config: &HttpConfig, body: &[u8]) -> Vec<u8> {
+    let mut stream = TcpStream::connect((http_config.addr.as_str(), http_config.port)).unwrap();
+
+    write!(
+        stream,
+        "POST /connector/api HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        http_config.addr,
+        body.len()
+    )
+    .unwrap();
+    stream.write_all(body).unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+
+    let body_start = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .expect("malformed HTTP response")
+        + 4;
+
+    assert!(response.starts_with(b"HTTP/1.1 200"));
+    response.split_off(body_start)
+}
+
+/// Unencrypted device info and echo commands, followed by a session
+#[test]
+fn server_smoke_test() {
+    let serial_number: SerialNumber = "1234567890".parse().unwrap();
+    let http_config = start_server(
+        MockHsm::builder()
+            .serial_number(serial_number)
+            .build()
+            .unwrap(),
+    );
+
+    // DeviceInfo (0x06) with an empty payload
+    let response = post_command(&http_config, &[0x06, 0x00, 0x00]);
+    assert_eq!(response[0], 0x86);
+    assert_eq!(
+        usize::from(u16::from_be_bytes([response[1], response[2]])),
+        response.len() - 3
+    );
+
+    // Firmware version followed by the serial number
+    assert_eq!(response[6..10], 1_234_567_890u32.to_be_bytes());
+
+    // Echo (0x01)
+    let response = post_command(
+        &http_config,
+        &[0x01, 0x00, 0x05, b'h', b'e', b'l', b'l', b'o'],
+    );
+    assert_eq!(response, [0x81, 0x00, 0x05, b'h', b'e', b'l', b'l', b'o']);
+
+    let client = Client::open(Connector::http(&http_config), Credentials::default(), true)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"));
+
+    assert_eq!(client.device_info().unwrap().serial_number, serial_number);
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+}
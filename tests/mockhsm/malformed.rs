@@ -0,0 +1,96 @@
+//! Malformed requests are answered with device errors, rather than crashing
+//! the `MockHsm`
+
+use crate::{TEST_DOMAINS, TEST_KEY_LABEL};
+use yubihsm::{
+    asymmetric, client, device, hmac, mockhsm::MockHsm, Capability, Client, Connector, Credentials,
+};
+
+/// Open a client for a new MockHsm
+fn open_client() -> Client {
+    Client::open(
+        Connector::from(MockHsm::new()),
+        Credentials::default(),
+        true,
+    )
+    .unwrap_or_else(|err| panic!("error opening session: {err}"))
+}
+
+/// Sessions can't be created with authentication keys which don't exist
+#[test]
+fn unknown_authentication_key_test() {
+    let credentials = Credentials::from_password(42, b"password");
+
+    let err = Client::open(Connector::from(MockHsm::new()), credentials, false)
+        .err()
+        .expect("expected session creation to fail");
+
+    assert_eq!(*err.kind(), client::ErrorKind::AuthenticationError);
+}
+
+/// Malformed key material is rejected
+#[test]
+fn invalid_key_test() {
+    let client = open_client();
+
+    let err = client
+        .put_asymmetric_key(
+            200,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_PKCS,
+            asymmetric::Algorithm::Rsa2048,
+            vec![0; asymmetric::Algorithm::Rsa2048.key_len()],
+        )
+        .expect_err("expected invalid data error");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidData));
+
+    // The session is still usable
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+}
+
+/// Putting an object with the same ID and type as an existing one fails
+#[test]
+fn object_exists_test() {
+    let client = open_client();
+
+    let put_key = || {
+        client.put_hmac_key(
+            200,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_HMAC,
+            hmac::Algorithm::Sha256,
+            vec![0x42; 32],
+        )
+    };
+
+    put_key().unwrap();
+
+    let err = put_key().expect_err("expected object exists error");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::ObjectExists));
+}
+
+/// Commands the MockHsm can't perform fail without closing the session
+#[test]
+fn unsupported_hmac_algorithm_test() {
+    let client = open_client();
+
+    client
+        .put_hmac_key(
+            200,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_HMAC,
+            hmac::Algorithm::Sha512,
+            vec![0x42; 64],
+        )
+        .unwrap();
+
+    let err = client
+        .sign_hmac(200, b"hello".as_ref())
+        .expect_err("expected invalid command error");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidCommand));
+
+    assert_eq!(client.echo(b"hello").unwrap(), b"hello");
+}
@@ -0,0 +1,10 @@
+//! Tests for functionality specific to the `MockHsm`
+
+mod access;
+mod builder;
+mod fault;
+#[cfg(all(feature = "http", feature = "http-server"))]
+mod http_server;
+mod malformed;
+mod snapshot;
+mod storage;
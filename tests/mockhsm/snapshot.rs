@@ -0,0 +1,201 @@
+//! Saving and loading snapshots of the `MockHsm` state
+
+use crate::{TEST_DOMAINS, TEST_KEY_ID, TEST_KEY_LABEL, TEST_MESSAGE};
+use ed25519_dalek::Verifier;
+use std::{env, fs, path::PathBuf, time::Duration};
+use yubihsm::{
+    asymmetric, command, device,
+    mockhsm::{self, ManualClock, MockHsm},
+    object, opaque, AuditOption, Capability, Client, Connector, Credentials,
+};
+
+/// Get a path in the temporary directory which is unique to this test
+fn snapshot_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("yubihsm-{}-{}.mockhsm", name, std::process::id()))
+}
+
+/// Open a client for the given MockHsm
+fn open_client(hsm: &MockHsm) -> Client {
+    Client::open(Connector::from(hsm.clone()), Credentials::default(), true)
+        .unwrap_or_else(|err| panic!("error opening session: {err}"))
+}
+
+/// Objects, options and the device's identity survive a save/load round trip
+#[test]
+fn save_and_load_test() {
+    let path = snapshot_path("save-and-load");
+    let hsm = MockHsm::builder()
+        .serial_number("0012345678".parse().unwrap())
+        .firmware_version(2, 3, 1)
+        .build()
+        .unwrap();
+    let client = open_client(&hsm);
+
+    client
+        .generate_asymmetric_key(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_EDDSA,
+            asymmetric::Algorithm::Ed25519,
+        )
+        .unwrap_or_else(|err| panic!("error generating key: {err}"));
+
+    client
+        .set_command_audit_option(command::Code::Echo, AuditOption::On)
+        .unwrap();
+    client.set_force_audit_option(AuditOption::On).unwrap();
+
+    let public_key = client.get_public_key(TEST_KEY_ID).unwrap();
+    let device_info = client.device_info().unwrap();
+    let device_public_key = client.get_device_public_key().unwrap();
+    let attestation_certificate = client.get_opaque(0).unwrap();
+
+    hsm.save(&path)
+        .unwrap_or_else(|err| panic!("error saving snapshot: {err}"));
+
+    let loaded = MockHsm::load(&path).unwrap_or_else(|err| panic!("error loading snapshot: {err}"));
+    fs::remove_file(&path).unwrap();
+
+    let client = open_client(&loaded);
+    let object_info = client
+        .get_object_info(TEST_KEY_ID, object::Type::AsymmetricKey)
+        .unwrap();
+
+    assert_eq!(object_info.label.to_string(), TEST_KEY_LABEL);
+    assert_eq!(object_info.capabilities, Capability::SIGN_EDDSA);
+    assert_eq!(object_info.origin, object::Origin::Generated);
+    assert_eq!(client.get_public_key(TEST_KEY_ID).unwrap(), public_key);
+
+    // The private key must be restored too, not just the public key
+    let signature = client.sign_ed25519(TEST_KEY_ID, TEST_MESSAGE).unwrap();
+    let verifying_key = ed25519_dalek::VerifyingKey::try_from(public_key.as_slice()).unwrap();
+    assert!(verifying_key.verify(TEST_MESSAGE, &signature).is_ok());
+
+    assert_eq!(
+        client
+            .get_command_audit_option(command::Code::Echo)
+            .unwrap(),
+        AuditOption::On
+    );
+    assert_eq!(client.get_force_audit_option().unwrap(), AuditOption::On);
+
+    let loaded_device_info = client.device_info().unwrap();
+    assert_eq!(loaded_device_info.serial_number, device_info.serial_number);
+    assert_eq!(
+        (
+            loaded_device_info.major_version,
+            loaded_device_info.minor_version,
+            loaded_device_info.build_version
+        ),
+        (2, 3, 1)
+    );
+    assert_eq!(client.get_device_public_key().unwrap(), device_public_key);
+    assert_eq!(client.get_opaque(0).unwrap(), attestation_certificate);
+}
+
+/// Snapshots can be loaded into a MockHsm driven by another clock
+#[test]
+fn builder_load_test() {
+    let path = snapshot_path("builder-load");
+    let hsm = MockHsm::new();
+    hsm.save(&path)
+        .unwrap_or_else(|err| panic!("error saving snapshot: {err}"));
+
+    let clock = ManualClock::new();
+    let loaded = MockHsm::builder()
+        .clock(clock.clone())
+        .load(&path)
+        .unwrap_or_else(|err| panic!("error loading snapshot: {err}"));
+    fs::remove_file(&path).unwrap();
+
+    let client = open_client(&loaded);
+    assert_eq!(client.echo(TEST_MESSAGE).unwrap(), TEST_MESSAGE);
+
+    // Sessions of the loaded MockHsm time out according to its clock
+    clock.advance(Duration::from_secs(30));
+
+    let err = client.echo(TEST_MESSAGE).unwrap_err();
+    assert_eq!(err.device_error(), Some(device::ErrorKind::InvalidSession));
+}
+
+/// With auto-persist enabled, changes are saved after every command which
+/// modifies the MockHsm's objects or options
+#[test]
+fn persist_to_test() {
+    let path = snapshot_path("persist-to");
+    let hsm = MockHsm::new();
+    hsm.persist_to(&path)
+        .unwrap_or_else(|err| panic!("error saving snapshot: {err}"));
+    fs::remove_file(&path).unwrap();
+
+    // Commands which don't modify the state don't save it
+    let client = open_client(&hsm);
+    client.echo(TEST_MESSAGE).unwrap();
+    client.get_opaque(TEST_KEY_ID).unwrap_err();
+    assert!(!path.exists());
+
+    client
+        .put_opaque(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            opaque::Algorithm::Data,
+            TEST_MESSAGE,
+        )
+        .unwrap();
+
+    let loaded = MockHsm::load(&path).unwrap_or_else(|err| panic!("error loading snapshot: {err}"));
+    fs::remove_file(&path).unwrap();
+
+    // Snapshots are written to a temporary file which replaces the old one
+    let mut tmp_path = path.clone().into_os_string();
+    tmp_path.push(".tmp");
+    assert!(!PathBuf::from(tmp_path).exists());
+
+    assert_eq!(
+        open_client(&loaded).get_opaque(TEST_KEY_ID).unwrap(),
+        TEST_MESSAGE
+    );
+}
+
+/// Commands which modify the state still succeed if the snapshot can't be
+/// saved, as they have already taken effect
+#[test]
+fn persist_to_failure_test() {
+    let dir = snapshot_path("persist-to-failure");
+    fs::create_dir(&dir).unwrap();
+
+    let hsm = MockHsm::new();
+    hsm.persist_to(dir.join("snapshot"))
+        .unwrap_or_else(|err| panic!("error saving snapshot: {err}"));
+    fs::remove_dir_all(&dir).unwrap();
+
+    let client = open_client(&hsm);
+    client
+        .put_opaque(
+            TEST_KEY_ID,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            opaque::Algorithm::Data,
+            TEST_MESSAGE,
+        )
+        .unwrap_or_else(|err| panic!("error putting opaque object: {err}"));
+
+    assert!(!dir.exists());
+    assert_eq!(client.get_opaque(TEST_KEY_ID).unwrap(), TEST_MESSAGE);
+}
+
+/// Files which aren't snapshots are rejected
+#[test]
+fn load_invalid_snapshot_test() {
+    let path = snapshot_path("invalid");
+    fs::write(&path, b"not a snapshot").unwrap();
+
+    let err = MockHsm::load(&path).expect_err("expected invalid snapshot");
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(*err.kind(), mockhsm::ErrorKind::ParseError);
+}
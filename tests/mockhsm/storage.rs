@@ -0,0 +1,107 @@
+//! Storage accounting for objects in the `MockHsm`
+
+use crate::{TEST_DOMAINS, TEST_KEY_LABEL};
+use yubihsm::{
+    client, device, hmac, mockhsm::MockHsm, object, opaque, Capability, Client, Connector,
+    Credentials,
+};
+
+/// Open a client for a new MockHsm
+fn open_client() -> Client {
+    Client::open(
+        Connector::from(MockHsm::new()),
+        Credentials::default(),
+        true,
+    )
+    .unwrap_or_else(|err| panic!("error opening session: {err}"))
+}
+
+/// Put an opaque object with the given ID and size
+fn put_opaque(client: &Client, object_id: object::Id, size: usize) -> Result<(), client::Error> {
+    client
+        .put_opaque(
+            object_id,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::default(),
+            opaque::Algorithm::Data,
+            vec![0x42; size],
+        )
+        .map(|_| ())
+}
+
+/// Each object uses a record, and pages for its data
+#[test]
+fn storage_accounting_test() {
+    let client = open_client();
+
+    // The default authentication key uses a record and a page
+    let storage_info = client.get_storage_info().unwrap();
+    assert_eq!(storage_info.free_records, 255);
+    assert_eq!(storage_info.free_pages, 1023);
+
+    put_opaque(&client, 1000, 127).unwrap();
+
+    let storage_info = client.get_storage_info().unwrap();
+    assert_eq!(storage_info.free_records, 254);
+    assert_eq!(storage_info.free_pages, 1021);
+
+    client
+        .delete_object(1000, object::Type::Opaque)
+        .unwrap_or_else(|err| panic!("error deleting object: {err}"));
+
+    let storage_info = client.get_storage_info().unwrap();
+    assert_eq!(storage_info.free_records, 255);
+    assert_eq!(storage_info.free_pages, 1023);
+}
+
+/// Objects can't be stored once all records are used
+#[test]
+fn records_full_test() {
+    let client = open_client();
+
+    for object_id in 1000..1255 {
+        put_opaque(&client, object_id, 1)
+            .unwrap_or_else(|err| panic!("error putting object: {err}"));
+    }
+
+    assert_eq!(client.get_storage_info().unwrap().free_records, 0);
+
+    let err = put_opaque(&client, 1255, 1).expect_err("expected storage to be full");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::StorageFailed));
+
+    client.delete_object(1000, object::Type::Opaque).unwrap();
+    put_opaque(&client, 1255, 1).unwrap_or_else(|err| panic!("error putting object: {err}"));
+}
+
+/// Objects can't be stored once there aren't enough free pages for them
+#[test]
+fn pages_full_test() {
+    let client = open_client();
+
+    // 1,890 byte objects use 15 of the 1,023 free pages
+    for object_id in 1000..1068 {
+        put_opaque(&client, object_id, 1890)
+            .unwrap_or_else(|err| panic!("error putting object: {err}"));
+    }
+
+    assert_eq!(client.get_storage_info().unwrap().free_pages, 3);
+
+    let err = put_opaque(&client, 1068, 1890).expect_err("expected storage to be full");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::StorageFailed));
+
+    // Smaller objects still fit
+    put_opaque(&client, 1068, 378).unwrap_or_else(|err| panic!("error putting object: {err}"));
+    assert_eq!(client.get_storage_info().unwrap().free_pages, 0);
+
+    let err = client
+        .generate_hmac_key(
+            1069,
+            TEST_KEY_LABEL.into(),
+            TEST_DOMAINS,
+            Capability::SIGN_HMAC,
+            hmac::Algorithm::Sha256,
+        )
+        .expect_err("expected storage to be full");
+    assert_eq!(err.device_error(), Some(device::ErrorKind::StorageFailed));
+}
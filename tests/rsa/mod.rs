@@ -0,0 +1,110 @@
+//! RSA signature tests
+
+use ::rsa::{
+    pkcs1::RsaPssParams,
+    pkcs8::{spki::DynSignatureAlgorithmIdentifier, AssociatedOid, ObjectIdentifier},
+    pss,
+    signature::{Keypair, Verifier},
+    BigUint, RsaPublicKey,
+};
+use sha2::{Sha256, Sha384};
+use yubihsm::{asymmetric::signature::Signer as _, rsa, Client};
+
+/// Key ID to use for test key
+const TEST_SIGNING_KEY_ID: yubihsm::object::Id = 203;
+
+/// Domain IDs for test key
+const TEST_SIGNING_KEY_DOMAINS: yubihsm::Domain = yubihsm::Domain::DOM1;
+
+/// Capability for test key
+const TEST_SIGNING_KEY_CAPABILITIES: yubihsm::Capability =
+    yubihsm::Capability::SIGN_PKCS.union(yubihsm::Capability::SIGN_PSS);
+
+/// Label for test key
+const TEST_SIGNING_KEY_LABEL: &str = "Signatory test key";
+
+/// Example message to sign
+const TEST_MESSAGE: &[u8] =
+    b"RSA (Rivest-Shamir-Adleman) is a public-key cryptosystem, one of the oldest \
+      widely used for secure data transmission.";
+
+/// Create the key on the YubiHSM to use for this test
+fn create_yubihsm_key(client: &Client) {
+    // Delete the key in TEST_KEY_ID slot it exists
+    // Ignore errors since the object may not exist yet
+    let _ = client.delete_object(TEST_SIGNING_KEY_ID, yubihsm::object::Type::AsymmetricKey);
+
+    // Create a new key for testing
+    client
+        .generate_asymmetric_key(
+            TEST_SIGNING_KEY_ID,
+            TEST_SIGNING_KEY_LABEL.into(),
+            TEST_SIGNING_KEY_DOMAINS,
+            TEST_SIGNING_KEY_CAPABILITIES,
+            yubihsm::asymmetric::Algorithm::Rsa2048,
+        )
+        .unwrap();
+}
+
+#[test]
+fn rsa_pkcs1v15_sha256_sign_test() {
+    let client = crate::get_hsm_client();
+    create_yubihsm_key(&client);
+
+    let signer = rsa::pkcs1::Signer::<Sha256>::create(client.clone(), TEST_SIGNING_KEY_ID).unwrap();
+    let signature = signer.sign(TEST_MESSAGE);
+
+    assert!(signer
+        .verifying_key()
+        .verify(TEST_MESSAGE, &signature)
+        .is_ok());
+}
+
+#[test]
+fn rsa_pss_sha384_sign_test() {
+    let client = crate::get_hsm_client();
+    create_yubihsm_key(&client);
+
+    let signer = rsa::pss::Signer::<Sha384>::create(client.clone(), TEST_SIGNING_KEY_ID).unwrap();
+    let signature = signer.sign(TEST_MESSAGE);
+
+    assert!(signer
+        .verifying_key()
+        .verify(TEST_MESSAGE, &signature)
+        .is_ok());
+
+    // `id-RSASSA-PSS` with SHA-384, MGF1 with SHA-384 and a 48-byte salt
+    let algorithm = signer.signature_algorithm_identifier().unwrap();
+    assert_eq!(
+        algorithm.oid,
+        ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10")
+    );
+
+    let params = algorithm.parameters.unwrap();
+    let params: RsaPssParams<'_> = params.decode_as().unwrap();
+    assert_eq!(params, RsaPssParams::new::<Sha384>(48));
+    assert_eq!(params.hash.oid, Sha384::OID);
+}
+
+/// `Client::sign_rsa_pss_sha256` signatures verify with the `rsa` crate,
+/// using a public key built directly from the modulus the HSM returns
+#[test]
+fn rsa_pss_sha256_independent_verify_test() {
+    let client = crate::get_hsm_client();
+    create_yubihsm_key(&client);
+
+    let modulus = client.get_public_key(TEST_SIGNING_KEY_ID).unwrap();
+    let public_key = RsaPublicKey::new(
+        BigUint::from_bytes_be(modulus.as_ref()),
+        BigUint::from(65_537u32),
+    )
+    .unwrap();
+
+    let signature = client
+        .sign_rsa_pss_sha256(TEST_SIGNING_KEY_ID, TEST_MESSAGE)
+        .unwrap();
+
+    let verifying_key = pss::VerifyingKey::<Sha256>::new(public_key);
+    let signature = pss::Signature::try_from(signature.as_slice()).unwrap();
+    assert!(verifying_key.verify(TEST_MESSAGE, &signature).is_ok());
+}
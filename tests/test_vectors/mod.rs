@@ -1,5 +1,8 @@
 //! Cryptographic test vectors for use in integration tests
 
+/// AES-ECB and AES-CBC test vectors
+mod aes;
+
 /// AES-CCM (Counter with CBC-MAC) test vectors
 mod aesccm;
 
@@ -9,20 +12,18 @@ mod ed25519;
 /// HMAC-SHA-256 test vectors
 mod hmac;
 
+pub use self::aes::{AES_CBC_TEST_VECTORS, AES_ECB_TEST_VECTORS};
 pub use self::aesccm::AESCCM_TEST_VECTORS;
 pub use self::ed25519::ED25519_TEST_VECTORS;
 pub use self::hmac::HMAC_SHA256_TEST_VECTORS;
 
-/// Authenticated encryption test vector (presently specialized for AES-CCM)
-pub struct EncryptionTestVector {
+/// Block cipher test vector (AES in ECB or CBC mode)
+pub struct BlockCipherTestVector {
     /// Encryption key
     pub key: &'static [u8],
 
-    /// Nonce the given message is encrypted under
-    pub nonce: &'static [u8],
-
-    /// Length of the plaintext part of an AES-CCM packet
-    pub ptlen: usize,
+    /// Initialization vector (all zeroes for ECB)
+    pub iv: &'static [u8; 16],
 
     /// Plaintext to be encrypted
     pub plaintext: &'static [u8],
@@ -31,6 +32,12 @@ pub struct EncryptionTestVector {
     pub ciphertext: &'static [u8],
 }
 
+/// Authenticated encryption test vector (presently specialized for AES-CCM)
+pub struct EncryptionTestVector {
+    /// Encryption key
+    pub key: &'static [u8],
+}
+
 /// Authenticated encryption test vector (presently specialized for AES-CCM)
 pub struct HMACTestVector {
     /// Encryption key